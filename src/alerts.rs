@@ -0,0 +1,163 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use poise::serenity_prelude::{self as serenity, ChannelId, Colour, RoleId};
+use tokio::sync::mpsc;
+
+use crate::{
+    calendar::Event,
+    db::Database,
+    diff::Change,
+    embed::{make_event_field, EmbedOptions},
+    mentions::{role_mentions, Mentions},
+};
+
+/// A republished feed produces its changes in a burst, they're summed up once it's over
+const COALESCE_DELAY: Duration = Duration::from_secs(5);
+/// Discord allows 5 messages per 5 seconds in a channel
+const SEND_INTERVAL: Duration = Duration::from_millis(1200);
+/// Changes listed in a summary, the others are only counted
+const MAX_SUMMARY_LINES: usize = 20;
+
+/// Change of the feed to report in a channel to the members of `roles`
+pub struct Alert {
+    pub channel: ChannelId,
+    /// Group of the event as written in ADE, the alerts are grouped by it
+    pub group: String,
+    pub roles: Vec<RoleId>,
+    pub change: Change,
+    /// Class the changed event now overlaps in another room
+    pub conflict: Option<Event>,
+}
+
+impl Alert {
+    fn conflict_label(&self) -> Option<String> {
+        self.conflict.as_ref().map(|other| {
+            format!(
+                "⚠️ chevauche {} ({}, {}-{})",
+                other.lesson_code(),
+                other.location,
+                other.start.format("%H:%M"),
+                other.end.format("%H:%M")
+            )
+        })
+    }
+}
+
+/// Posts the alerts pushed by the watcher, a single message per group and channel for the changes
+/// that come together, paced to stay under the rate limits
+#[derive(Clone)]
+pub struct AlertQueue {
+    sender: mpsc::UnboundedSender<Alert>,
+}
+
+impl AlertQueue {
+    pub fn start(ctx: serenity::Context, db: Database) -> AlertQueue {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(dispatch(ctx, db, receiver));
+        AlertQueue { sender }
+    }
+
+    pub fn push(&self, alert: Alert) {
+        if self.sender.send(alert).is_err() {
+            println!("Alert queue stopped, dropping an alert");
+        }
+    }
+}
+
+async fn dispatch(
+    ctx: serenity::Context,
+    db: Database,
+    mut receiver: mpsc::UnboundedReceiver<Alert>,
+) {
+    while let Some(first) = receiver.recv().await {
+        tokio::time::sleep(COALESCE_DELAY).await;
+        let mut batches: BTreeMap<(ChannelId, String), Vec<Alert>> = BTreeMap::new();
+        let mut next = Some(first);
+        while let Some(alert) = next {
+            batches
+                .entry((alert.channel, alert.group.clone()))
+                .or_default()
+                .push(alert);
+            next = receiver.try_recv().ok();
+        }
+
+        for ((channel, group), alerts) in batches {
+            if let Err(err) = send(&ctx, &db, channel, &group, &alerts).await {
+                println!(
+                    "Failed to send {} alerts for {}: {}",
+                    alerts.len(),
+                    group,
+                    err
+                );
+            }
+            tokio::time::sleep(SEND_INTERVAL).await;
+        }
+    }
+}
+
+async fn send(
+    ctx: &serenity::Context,
+    db: &Database,
+    channel: ChannelId,
+    group: &str,
+    alerts: &[Alert],
+) -> serenity::Result<()> {
+    let mut roles: Vec<RoleId> = alerts.iter().flat_map(|a| a.roles.clone()).collect();
+    roles.sort();
+    roles.dedup();
+    let mentions = match ctx.cache.guild_channel(channel) {
+        Some(channel) => role_mentions(ctx, db, channel.guild_id, &roles).await,
+        None => Mentions::default(),
+    };
+
+    channel
+        .send_message(ctx, |m| {
+            match alerts {
+                [alert @ Alert {
+                    change: Change::Added(evt),
+                    ..
+                }] => {
+                    let (name, mut value) = make_event_field(evt, &EmbedOptions::default());
+                    if let Some(conflict) = alert.conflict_label() {
+                        value.push_str(&format!("\n**{}**", conflict));
+                    }
+                    m.content(format!(
+                        "⚠️ Cours ajouté au dernier moment {}",
+                        mentions.content
+                    ))
+                    .embed(|e| {
+                        e.title(format!("{} — {}", group, evt.start.format("%d/%m/%Y")))
+                            .field(name, value, false)
+                            .color(Colour::ORANGE)
+                    });
+                }
+                _ => {
+                    let mut lines: Vec<String> = alerts
+                        .iter()
+                        .take(MAX_SUMMARY_LINES)
+                        .map(|a| match a.conflict_label() {
+                            Some(conflict) => format!("{} — {}", a.change, conflict),
+                            None => a.change.to_string(),
+                        })
+                        .collect();
+                    if alerts.len() > MAX_SUMMARY_LINES {
+                        lines.push(format!("… et {} autres", alerts.len() - MAX_SUMMARY_LINES));
+                    }
+                    m.content(format!(
+                        "⚠️ Changements de l'emploi du temps ({}) {}",
+                        alerts.len(),
+                        mentions.content
+                    ))
+                    .embed(|e| {
+                        e.title(group)
+                            .description(lines.join("\n"))
+                            .color(Colour::ORANGE)
+                    });
+                }
+            }
+            m.allowed_mentions(|a| mentions.allowed(a))
+        })
+        .await?;
+
+    Ok(())
+}
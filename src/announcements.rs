@@ -0,0 +1,529 @@
+use std::{collections::HashMap, time::Duration};
+
+use agenda_bot::dates::next_occurrence;
+use chrono::{Datelike, Local, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use poise::serenity_prelude::{
+    self as serenity,
+    json::{hashmap_to_json_map, json, Value},
+    Channel, ChannelId, CreateEmbed, ForumTagId, GuildId, MessageId, RoleId,
+};
+
+use crate::{
+    calendar::{
+        get_events, get_sorted_events, parse_promo_name, parse_role_name, Event, GroupHierarchy,
+        Promo,
+    },
+    config,
+    db::{AnnouncementCleanup, Database, GuildSettings},
+    embed::{make_events_embed, make_timetable_components, EmbedOptions},
+    homework::{homework_due, homework_lines},
+    i18n::Language,
+    mentions::{role_mentions, Mentions},
+    prefs::{Format, View},
+    Error,
+};
+
+/// Threads of the daily announcements are archived after a day of inactivity
+const ANNOUNCEMENT_THREAD_ARCHIVE_MINUTES: u16 = 60 * 24;
+/// Forum posts are reused every day, they only get archived over the holidays
+const FORUM_POST_ARCHIVE_MINUTES: u16 = 60 * 24 * 7;
+
+const FETCH_ATTEMPTS: u32 = 3;
+const FETCH_RETRY_DELAY: Duration = Duration::from_secs(60);
+/// The time set with /config heure is picked up at the next check
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Announcements missed while the bot was restarting are posted when it's back, unless they're
+/// older than this
+const CATCH_UP_HOURS: i64 = 2;
+
+/// Posts the timetable of every promo at the time set with /config heure or in the config, in the announcement
+/// channel or in the forum configured with /config forum
+pub async fn run(ctx: serenity::Context, db: Database) {
+    // the promos already announced today are skipped by `announce`
+    let mut last_check = Local::now() - chrono::Duration::hours(CATCH_UP_HOURS);
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+
+        let now = Local::now();
+        let channel = config::get().announcement_channel();
+        let time = ctx
+            .cache
+            .guild_channel(channel)
+            .and_then(|c| db.get_guild_settings(c.guild_id).ok())
+            .and_then(|s| s.announcement_time)
+            .unwrap_or_else(|| config::get().announcement_time());
+        // due when the announcement time was reached since the previous check
+        if next_occurrence(&last_check, time) <= now {
+            // run apart so that a panic only loses the announcements of the day
+            let res = tokio::spawn({
+                let ctx = ctx.clone();
+                let db = db.clone();
+                async move { announce(&ctx, channel, &db).await }
+            })
+            .await;
+            match res {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => println!("Failed to post the daily announcements: {}", err),
+                Err(err) => println!("Daily announcements panicked: {}", err),
+            }
+        }
+        last_check = now;
+    }
+}
+
+/// Daily timetable of a promo, ready to be posted
+struct Announcement<'a> {
+    promo: &'a Promo,
+    date: NaiveDate,
+    embeds: Vec<CreateEmbed>,
+    mentions: Mentions,
+    /// Short text shown instead of the timetable
+    note: Option<String>,
+}
+
+impl Announcement<'_> {
+    fn content(&self) -> String {
+        match &self.note {
+            Some(note) if !self.mentions.content.is_empty() => {
+                format!("{} {}", self.mentions.content, note)
+            }
+            Some(note) => note.clone(),
+            None => self.mentions.content.clone(),
+        }
+    }
+}
+
+/// ADE is often briefly unavailable, the fetch is retried before giving up on the day
+async fn fetch_events(date: NaiveDate) -> Result<HashMap<Promo, Vec<Event>>, Error> {
+    let mut attempt = 1;
+    loop {
+        match get_sorted_events(date).await {
+            Ok(events) => return Ok(events),
+            Err(err) if attempt < FETCH_ATTEMPTS => {
+                println!(
+                    "Failed to fetch the announced events (attempt {}/{}): {}",
+                    attempt, FETCH_ATTEMPTS, err
+                );
+                tokio::time::sleep(FETCH_RETRY_DELAY * attempt).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+async fn announce(ctx: &serenity::Context, channel: ChannelId, db: &Database) -> Result<(), Error> {
+    let events = fetch_events(Local::now().date_naive()).await?;
+    let guild = ctx.cache.guild_channel(channel).map(|c| c.guild_id);
+    let options = EmbedOptions::for_guild(db, guild).unwrap_or_default();
+    let settings = match guild {
+        Some(guild) => db.get_guild_settings(guild)?,
+        None => GuildSettings::default(),
+    };
+    let ping = settings.announcement_pings && !settings.is_quiet(Local::now().hour());
+
+    if settings.announcement_cleanup == AnnouncementCleanup::Delete {
+        delete_old_announcements(ctx, db, settings.announcement_retention_days).await;
+    }
+
+    let date = Local::now().date_naive();
+    let announced = announced_promos(ctx, guild);
+    // secondary feed events alone aren't worth an announcement
+    let with_classes: Vec<&Promo> = events
+        .iter()
+        .filter(|(promo, _)| announced.as_ref().is_none_or(|a| a.contains(promo)))
+        .filter(|(_, events)| events.iter().any(|e| e.category.is_none()))
+        .map(|(promo, _)| promo)
+        .collect();
+    let hierarchy = GroupHierarchy::from_events(&get_events().await?);
+    let mut announcements = Vec::new();
+    for promo in &with_classes {
+        let Ok(mut embeds) = make_events_embed((*promo).clone(), date, &options).await else {
+            continue;
+        };
+        let homework = guild
+            .map(|guild| homework_due(db, guild, &hierarchy, promo, date))
+            .transpose()?
+            .unwrap_or_default();
+        if let Some(embed) = embeds.first_mut().filter(|_| !homework.is_empty()) {
+            embed.field(
+                "📚 Devoirs à rendre",
+                homework_lines(&homework, false),
+                false,
+            );
+        }
+        let mentions = match guild {
+            Some(guild) if ping => {
+                let roles = promo_roles(ctx, guild, promo);
+                role_mentions(ctx, db, guild, &roles).await
+            }
+            _ => Mentions::default(),
+        };
+        announcements.push(Announcement {
+            promo,
+            date,
+            embeds,
+            mentions,
+            note: None,
+        });
+    }
+
+    // the promos of the guild without classes, so that silence doesn't look like a failure. A
+    // promo whose groups have classes isn't free, and nobody expects a post on days off.
+    let has_classes = |promo: &Promo| {
+        hierarchy.targets(promo).iter().any(|p| {
+            events
+                .get(p)
+                .is_some_and(|events| events.iter().any(|e| e.category.is_none()))
+        })
+    };
+    let day_off =
+        matches!(date.weekday(), Weekday::Sat | Weekday::Sun) || config::get().is_holiday(date);
+    let guild_promos = match guild {
+        Some(guild) if settings.announce_empty_days && !day_off => guild_promos(ctx, guild),
+        _ => Vec::new(),
+    };
+    for promo in guild_promos
+        .iter()
+        .filter(|p| !has_classes(p))
+        .filter(|p| announced.as_ref().is_none_or(|a| a.contains(p)))
+    {
+        let mut note = format!("🏖️ Pas de cours aujourd'hui pour {}", promo);
+        let homework = guild
+            .map(|guild| homework_due(db, guild, &hierarchy, promo, date))
+            .transpose()?
+            .unwrap_or_default();
+        if !homework.is_empty() {
+            note.push_str(&format!("\n{}", homework_lines(&homework, false)));
+        }
+        announcements.push(Announcement {
+            promo,
+            date,
+            embeds: Vec::new(),
+            mentions: Mentions::default(),
+            note: Some(note),
+        });
+    }
+
+    for announcement in &announcements {
+        let promo = announcement.promo;
+        if db.get_announced_day(channel, promo)? == Some(date) {
+            continue;
+        }
+
+        let cleanup = settings.announcement_cleanup;
+        let res = match (guild, settings.announcement_forum) {
+            (Some(guild), Some(forum)) => {
+                post_in_forum(ctx, db, guild, forum, announcement, cleanup).await
+            }
+            _ => post_in_channel(ctx, db, channel, announcement, cleanup).await,
+        };
+        match res {
+            Ok(()) => db.set_announced_day(channel, promo, date)?,
+            Err(err) => println!("Failed to post the announcement of {}: {}", promo, err),
+        }
+    }
+
+    Ok(())
+}
+
+async fn post_in_channel(
+    ctx: &serenity::Context,
+    db: &Database,
+    channel: ChannelId,
+    announcement: &Announcement<'_>,
+    cleanup: AnnouncementCleanup,
+) -> Result<(), Error> {
+    let Announcement {
+        promo,
+        date,
+        mentions,
+        ..
+    } = announcement;
+    if cleanup == AnnouncementCleanup::Edit {
+        if let Some(message) = edit_last_announcement(ctx, db, channel, announcement).await? {
+            // the thread of an announcement has the id of its message
+            let res = ChannelId(message.0)
+                .edit_thread(ctx, |t| t.name(announcement_thread_name(*date, promo)))
+                .await;
+            if let Err(err) = res {
+                println!("Failed to rename announcement thread: {}", err);
+            }
+            return Ok(());
+        }
+    }
+
+    let msg = channel
+        .send_message(ctx, |m| {
+            let content = announcement.content();
+            if !content.is_empty() {
+                m.content(content);
+            }
+            m.set_embeds(announcement.embeds.clone())
+                .set_components(make_timetable_components(
+                    *date,
+                    promo,
+                    View::Day,
+                    Format::Embed,
+                ))
+                .allowed_mentions(|a| mentions.allowed(a))
+        })
+        .await?;
+    db.add_announcement(channel, promo, msg.id)?;
+    if announcement.embeds.is_empty() {
+        return Ok(());
+    }
+
+    // keeps the questions about the day out of the main channel
+    let res = channel
+        .create_public_thread(ctx, msg.id, |t| {
+            t.name(announcement_thread_name(*date, promo))
+                .auto_archive_duration(ANNOUNCEMENT_THREAD_ARCHIVE_MINUTES)
+        })
+        .await;
+    if let Err(err) = res {
+        println!("Failed to create announcement thread: {}", err);
+    }
+
+    Ok(())
+}
+
+/// Sends the announcement in the post of the promo, the post is created again when it was
+/// deleted or never existed
+async fn post_in_forum(
+    ctx: &serenity::Context,
+    db: &Database,
+    guild: GuildId,
+    forum: ChannelId,
+    announcement: &Announcement<'_>,
+    cleanup: AnnouncementCleanup,
+) -> Result<(), Error> {
+    let Announcement {
+        promo,
+        date,
+        mentions,
+        ..
+    } = announcement;
+    let components = make_timetable_components(*date, promo, View::Day, Format::Embed);
+    if let Some(thread) = db.get_forum_post(guild, promo)? {
+        if cleanup == AnnouncementCleanup::Edit
+            && edit_last_announcement(ctx, db, thread, announcement)
+                .await?
+                .is_some()
+        {
+            return Ok(());
+        }
+
+        let res = thread
+            .send_message(ctx, |m| {
+                let content = announcement.content();
+                if !content.is_empty() {
+                    m.content(content);
+                }
+                m.set_embeds(announcement.embeds.clone())
+                    .set_components(components.clone())
+                    .allowed_mentions(|a| mentions.allowed(a))
+            })
+            .await;
+        match res {
+            Ok(msg) => {
+                db.add_announcement(thread, promo, msg.id)?;
+                return Ok(());
+            }
+            Err(err) => println!("Forum post of {} unavailable, creating it: {}", promo, err),
+        }
+    }
+
+    let mut allowed_mentions = serenity::CreateAllowedMentions::default();
+    mentions.allowed(&mut allowed_mentions);
+    let embeds = announcement
+        .embeds
+        .iter()
+        .map(|e| Value::from(hashmap_to_json_map(e.0.clone())))
+        .collect::<Vec<Value>>();
+    let post = json!({
+        "name": promo.to_string(),
+        "auto_archive_duration": FORUM_POST_ARCHIVE_MINUTES,
+        "applied_tags": forum_tags(ctx, forum, promo).await?,
+        "message": {
+            "content": announcement.content(),
+            "embeds": embeds,
+            "components": components.0,
+            "allowed_mentions": Value::from(hashmap_to_json_map(allowed_mentions.0)),
+        },
+    });
+    let Value::Object(post) = post else {
+        unreachable!();
+    };
+
+    // serenity has no builder for forum posts, they use the same endpoint as threads
+    let thread = ctx.http.create_private_thread(forum.0, &post).await?;
+    db.set_forum_post(guild, promo, thread.id)?;
+    // the first message of a post has the id of the post
+    db.add_announcement(thread.id, promo, MessageId(thread.id.0))?;
+
+    Ok(())
+}
+
+/// Replaces the previous announcement of the promo in `channel`, returns the edited message or
+/// `None` when there is nothing to edit. Discord doesn't ping on edits, an announcement with
+/// mentions replaces the previous one with a new message instead.
+async fn edit_last_announcement(
+    ctx: &serenity::Context,
+    db: &Database,
+    channel: ChannelId,
+    announcement: &Announcement<'_>,
+) -> Result<Option<MessageId>, Error> {
+    let Some(message) = db.get_last_announcement(channel, announcement.promo)? else {
+        return Ok(None);
+    };
+
+    if announcement.mentions.pings() {
+        if let Err(err) = channel.delete_message(ctx, message).await {
+            println!("Failed to delete the previous announcement: {}", err);
+        }
+        db.remove_announcement(message)?;
+        return Ok(None);
+    }
+
+    let res = channel
+        .edit_message(ctx, message, |m| {
+            m.content(announcement.content())
+                .set_embeds(announcement.embeds.clone())
+                .components(|c| {
+                    *c = make_timetable_components(
+                        announcement.date,
+                        announcement.promo,
+                        View::Day,
+                        Format::Embed,
+                    );
+                    c
+                })
+        })
+        .await;
+    match res {
+        Ok(_) => Ok(Some(message)),
+        Err(err) => {
+            println!(
+                "Failed to edit the previous announcement, posting a new one: {}",
+                err
+            );
+            db.remove_announcement(message)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Deletes the announcements posted more than `retention_days` days ago
+async fn delete_old_announcements(ctx: &serenity::Context, db: &Database, retention_days: u32) {
+    let oldest = Local::now().date_naive() - chrono::Duration::days(retention_days as i64);
+    let before = Local
+        .from_local_datetime(&oldest.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+    let messages = match db.get_announcements_before(before) {
+        Ok(messages) => messages,
+        Err(err) => {
+            println!("Failed to get old announcements: {}", err);
+            return;
+        }
+    };
+
+    for (channel, message) in messages {
+        if let Err(err) = channel.delete_message(ctx, message).await {
+            println!("Failed to delete announcement: {}", err);
+        }
+        // deleted by hand or not, the message is gone for good
+        if let Err(err) = db.remove_announcement(message) {
+            println!("Failed to forget announcement: {}", err);
+        }
+    }
+}
+
+/// Tags of the department and the year of the promo, added to the forum when missing
+async fn forum_tags(
+    ctx: &serenity::Context,
+    forum: ChannelId,
+    promo: &Promo,
+) -> Result<Vec<ForumTagId>, Error> {
+    let Channel::Guild(channel) = forum.to_channel(ctx).await? else {
+        return Err("The announcement forum isn't a guild channel".into());
+    };
+
+    let names = [promo.deparment.to_string(), format!("{}A", promo.year)];
+    let mut tags = channel.available_tags;
+    if names.iter().any(|n| !tags.iter().any(|t| &t.name == n)) {
+        // the list replaces the tags of the forum, the existing ones are sent back as is
+        let mut available = tags.iter().map(|t| json!(t)).collect::<Vec<Value>>();
+        for name in names.iter().filter(|n| !tags.iter().any(|t| &t.name == *n)) {
+            available.push(json!({ "name": name }));
+        }
+
+        let mut map = serenity::json::JsonMap::new();
+        map.insert("available_tags".to_string(), Value::from(available));
+        tags = ctx
+            .http
+            .edit_channel(forum.0, &map, None)
+            .await?
+            .available_tags;
+    }
+
+    Ok(tags
+        .into_iter()
+        .filter(|t| names.contains(&t.name))
+        .map(|t| t.id)
+        .collect())
+}
+
+/// "Jeudi 10/10 — 3-INFO-31"
+fn announcement_thread_name(date: NaiveDate, promo: &Promo) -> String {
+    format!(
+        "{} {} — {}",
+        Language::French.strings().weekdays[date.weekday().num_days_from_monday() as usize],
+        date.format("%d/%m"),
+        promo
+    )
+}
+
+/// Promos that have a role in the guild
+fn guild_promos(ctx: &serenity::Context, guild: GuildId) -> Vec<Promo> {
+    let mut promos: Vec<Promo> = ctx
+        .cache
+        .guild_roles(guild)
+        .unwrap_or_default()
+        .values()
+        .filter_map(|r| parse_role_name(&r.name))
+        .collect();
+    promos.sort_by_key(|p| p.to_string());
+    promos.dedup();
+
+    promos
+}
+
+/// Promos worth announcing: the configured ones, else the ones with a role in the guild so that
+/// groups nobody follows here aren't posted. `None` announces every promo of the feed, when
+/// neither is known.
+fn announced_promos(ctx: &serenity::Context, guild: Option<GuildId>) -> Option<Vec<Promo>> {
+    let configured: Vec<Promo> = config::get()
+        .announced_promos
+        .iter()
+        .filter_map(|p| parse_promo_name(p))
+        .collect();
+    if !configured.is_empty() {
+        return Some(configured);
+    }
+
+    Some(guild_promos(ctx, guild?)).filter(|promos| !promos.is_empty())
+}
+
+/// Roles of the guild named after exactly this promo
+fn promo_roles(ctx: &serenity::Context, guild: GuildId, promo: &Promo) -> Vec<RoleId> {
+    ctx.cache
+        .guild_roles(guild)
+        .unwrap_or_default()
+        .values()
+        .filter(|r| parse_role_name(&r.name).as_ref() == Some(promo))
+        .map(|r| r.id)
+        .collect()
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use chrono::{Local, Utc};
+
+use crate::{
+    calendar::{get_events, Event},
+    db::Database,
+};
+
+/// Past days are archived again while ADE still returns them, the last version is kept
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// /changements compares the timetable with a snapshot of the feed taken about a day apart
+const FEED_SNAPSHOT_HOURS: i64 = 24;
+const FEED_SNAPSHOT_RETENTION_DAYS: i64 = 35;
+
+/// Keeps a copy of the events of every past day and today, so that /edt and the hour counts
+/// still work once ADE drops them from the feed, and a daily snapshot of the whole feed
+pub async fn run(db: Database) {
+    loop {
+        match get_events().await {
+            Ok(events) => {
+                let today = Local::now().date_naive();
+                let past: Vec<_> = events
+                    .iter()
+                    .filter(|e| e.start.date_naive() <= today)
+                    .cloned()
+                    .collect();
+                if let Err(err) = db.archive_events(&past) {
+                    println!("Failed to archive events: {}", err);
+                }
+                if let Err(err) = snapshot_feed(&db, &events) {
+                    println!("Failed to snapshot the feed: {}", err);
+                }
+            }
+            Err(err) => println!("Failed to fetch events to archive: {}", err),
+        }
+
+        tokio::time::sleep(SNAPSHOT_INTERVAL).await;
+    }
+}
+
+fn snapshot_feed(db: &Database, events: &[Event]) -> rusqlite::Result<()> {
+    let now = Utc::now();
+    let due = db
+        .get_last_snapshot_time()?
+        .is_none_or(|t| now - t >= chrono::Duration::hours(FEED_SNAPSHOT_HOURS));
+    if !due {
+        return Ok(());
+    }
+
+    // /changements compares with the classes still held, the cancelled ones would show up as
+    // removed although they already were when the snapshot was taken
+    let held: Vec<Event> = events
+        .iter()
+        .filter(|e| !e.cancelled && e.category.is_none())
+        .cloned()
+        .collect();
+    db.save_snapshot(now, &held)?;
+    db.remove_snapshots_before(now - chrono::Duration::days(FEED_SNAPSHOT_RETENTION_DAYS))
+}
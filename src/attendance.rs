@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use chrono::{Local, Utc};
+use poise::serenity_prelude::{
+    self as serenity, ButtonStyle, InteractionResponseType, MessageComponentInteraction,
+    ReactionType,
+};
+
+use crate::{calendar::get_sorted_events, db::Database, Error};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Students can check in from the start of the class until this many minutes later
+pub const CHECK_IN_MINUTES: i64 = 15;
+const CHECK_IN_BUTTON_ID: &str = "attendance";
+
+/// Posts a check-in button at the start of every class of the promos enabled with /appel
+pub async fn run(ctx: serenity::Context, db: Database) {
+    loop {
+        if let Err(err) = open_sessions(&ctx, &db).await {
+            println!("Failed to open attendance check-ins: {}", err);
+        }
+
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+async fn open_sessions(ctx: &serenity::Context, db: &Database) -> Result<(), Error> {
+    let promos = db.get_attendance_promos()?;
+    if promos.is_empty() {
+        return Ok(());
+    }
+
+    let events = get_sorted_events(Local::now().date_naive()).await?;
+    let now = Utc::now();
+    let window = chrono::Duration::minutes(CHECK_IN_MINUTES);
+    for (guild, promo, channel) in promos {
+        for evt in events
+            .get(&promo)
+            .into_iter()
+            .flatten()
+            .filter(|e| !e.cancelled && e.category.is_none())
+            .filter(|e| e.start <= now && now < e.start + window)
+        {
+            let closes_at = (evt.start + window).timestamp();
+            let Some(session) = db.add_attendance_session(guild, &promo, evt, closes_at)? else {
+                continue;
+            };
+
+            let res = channel
+                .send_message(ctx, |m| {
+                    m.content(format!(
+                        "✋ Appel — {} ({}), jusqu'à <t:{}:t>",
+                        evt.lesson, promo, closes_at
+                    ))
+                    .components(|c| {
+                        c.create_action_row(|r| {
+                            r.create_button(|b| {
+                                b.custom_id(format!("{}:{}", CHECK_IN_BUTTON_ID, session))
+                                    .label("Présent")
+                                    .emoji(ReactionType::Unicode("✋".to_string()))
+                                    .style(ButtonStyle::Success)
+                            })
+                        })
+                    })
+                })
+                .await;
+            if let Err(err) = res {
+                println!("Failed to post the check-in of {}: {}", promo, err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Records a click on a check-in button, returns false when the component is something else
+pub async fn handle_check_in(
+    ctx: &serenity::Context,
+    db: &Database,
+    component: &MessageComponentInteraction,
+) -> Result<bool, Error> {
+    let Some(session) = component
+        .data
+        .custom_id
+        .strip_prefix(CHECK_IN_BUTTON_ID)
+        .and_then(|id| id.strip_prefix(':'))
+        .and_then(|id| id.parse::<i64>().ok())
+    else {
+        return Ok(false);
+    };
+
+    let found = match component.guild_id {
+        Some(guild) => db.get_attendance_session(guild, session)?,
+        None => None,
+    };
+    let content = match found {
+        None => "Cet appel n'existe plus.",
+        Some(s) if Utc::now().timestamp() > s.closes_at => "L'appel est terminé.",
+        Some(_) if db.check_in(session, component.user.id)? => "Présence enregistrée ✅",
+        Some(_) => "Ta présence est déjà enregistrée.",
+    };
+    component
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(content).ephemeral(true))
+        })
+        .await?;
+
+    Ok(true)
+}
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Duration};
+use chrono_tz::Tz;
+
+use crate::calendar::Event;
+
+/// Gaps of at least `min` between `from` and `to` that no event covers, cancelled events are
+/// ignored and overlapping ones merged
+pub fn free_slots(
+    events: &[Event],
+    from: DateTime<Tz>,
+    to: DateTime<Tz>,
+    min: Duration,
+) -> Vec<(DateTime<Tz>, DateTime<Tz>)> {
+    let mut busy: Vec<(DateTime<Tz>, DateTime<Tz>)> = events
+        .iter()
+        .filter(|e| !e.cancelled && e.start < to && e.end > from)
+        .map(|e| (e.start, e.end))
+        .collect();
+    busy.sort();
+
+    let mut slots = Vec::new();
+    let mut cursor = from;
+    for (start, end) in busy {
+        if start - cursor >= min {
+            slots.push((cursor, start));
+        }
+        cursor = cursor.max(end);
+    }
+    if to - cursor >= min {
+        slots.push((cursor, to));
+    }
+
+    slots
+}
@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::calendar::Event;
+
+/// Fetch time in milliseconds and events
+pub type CachedCalendar = (i64, Vec<Event>);
+
+/// Where the parsed calendars are kept between two fetches, keyed by their URL
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    async fn get(&self, url: &str) -> Option<CachedCalendar>;
+    async fn set(&self, url: &str, calendar: &CachedCalendar);
+    async fn invalidate(&self, url: &str);
+    async fn clear(&self);
+}
+
+/// Cache of a single process, lost on restart
+#[derive(Default)]
+pub struct MemoryCache {
+    calendars: RwLock<HashMap<String, CachedCalendar>>,
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCache {
+    async fn get(&self, url: &str) -> Option<CachedCalendar> {
+        self.calendars
+            .read()
+            .expect("Failed to lock calendar cache!")
+            .get(url)
+            .cloned()
+    }
+
+    async fn set(&self, url: &str, calendar: &CachedCalendar) {
+        self.calendars
+            .write()
+            .expect("Failed to lock calendar cache!")
+            .insert(url.to_string(), calendar.clone());
+    }
+
+    async fn invalidate(&self, url: &str) {
+        self.calendars
+            .write()
+            .expect("Failed to lock calendar cache!")
+            .remove(url);
+    }
+
+    async fn clear(&self) {
+        self.calendars
+            .write()
+            .expect("Failed to lock calendar cache!")
+            .clear();
+    }
+}
+
+/// Cache shared by every instance connected to the same Redis, it survives restarts.
+/// Failing requests are logged and treated as cache misses so that the bot keeps working
+/// from the feed while Redis is down.
+#[cfg(feature = "redis")]
+pub struct RedisCache {
+    connection: redis::aio::ConnectionManager,
+}
+
+/// Hash holding the JSON of each calendar under its URL
+#[cfg(feature = "redis")]
+const REDIS_KEY: &str = "agenda-bot:calendars";
+
+#[cfg(feature = "redis")]
+impl RedisCache {
+    pub async fn connect(url: &str) -> redis::RedisResult<RedisCache> {
+        let client = redis::Client::open(url)?;
+        Ok(RedisCache {
+            connection: redis::aio::ConnectionManager::new(client).await?,
+        })
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl CacheBackend for RedisCache {
+    async fn get(&self, url: &str) -> Option<CachedCalendar> {
+        let json: Option<String> = match redis::cmd("HGET")
+            .arg(REDIS_KEY)
+            .arg(url)
+            .query_async(&mut self.connection.clone())
+            .await
+        {
+            Ok(json) => json,
+            Err(err) => {
+                println!("Failed to read calendar cache: {}", err);
+                return None;
+            }
+        };
+        match serde_json::from_str(&json?) {
+            Ok(calendar) => Some(calendar),
+            // written by another version of the bot, it's fetched again
+            Err(err) => {
+                println!("Invalid cached calendar for {}: {}", url, err);
+                None
+            }
+        }
+    }
+
+    async fn set(&self, url: &str, calendar: &CachedCalendar) {
+        let json = match serde_json::to_string(calendar) {
+            Ok(json) => json,
+            Err(err) => {
+                println!("Failed to serialize calendar: {}", err);
+                return;
+            }
+        };
+        let result: redis::RedisResult<()> = redis::cmd("HSET")
+            .arg(REDIS_KEY)
+            .arg(url)
+            .arg(json)
+            .query_async(&mut self.connection.clone())
+            .await;
+        if let Err(err) = result {
+            println!("Failed to write calendar cache: {}", err);
+        }
+    }
+
+    async fn invalidate(&self, url: &str) {
+        let result: redis::RedisResult<()> = redis::cmd("HDEL")
+            .arg(REDIS_KEY)
+            .arg(url)
+            .query_async(&mut self.connection.clone())
+            .await;
+        if let Err(err) = result {
+            println!("Failed to invalidate cached calendar {}: {}", url, err);
+        }
+    }
+
+    async fn clear(&self) {
+        let result: redis::RedisResult<()> = redis::cmd("DEL")
+            .arg(REDIS_KEY)
+            .query_async(&mut self.connection.clone())
+            .await;
+        if let Err(err) = result {
+            println!("Failed to clear calendar cache: {}", err);
+        }
+    }
+}
@@ -3,22 +3,62 @@ use chrono_tz::Europe::Paris;
 use chrono_tz::Tz;
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use tokio::sync::{Mutex, Semaphore};
+
+use crate::cache::{CacheBackend, MemoryCache};
 
 const ISO_8601: &str = "%Y%m%dT%H%M%SZ";
 
 lazy_static! {
-    static ref CALENDAR_URL: String = std::env::var("CALENDAR_URL").expect("CALENDAR_URL not set!");
-    static ref ROLE_REGEX: Regex = Regex::new("[1-4]-[A-Z]*-[1-4][1-2]").unwrap();
+    /// Set from the config, the `CALENDAR_URL` environment variable is used when it has none
+    static ref CALENDAR_URL: RwLock<Option<String>> = RwLock::new(None);
     static ref CLASS_TYPE_REGEX: Regex =
         Regex::new("(S|R)[1-9].[0-9][0-9](-|_)(CM|TD|TP)").unwrap();
-    static ref GROUP_REGEX: Regex =
-        Regex::new("[1-4]-[A-Z]*-((S[1-4])|([1-4])|([1-4][1-2]))").unwrap();
+    static ref GROUP_REGEXES: RwLock<Vec<Regex>> = RwLock::new(
+        GroupGrammar::default()
+            .compile()
+            .expect("Invalid default group grammar!")
+    );
+    static ref CACHE_BACKEND: RwLock<Arc<dyn CacheBackend>> =
+        RwLock::new(Arc::new(MemoryCache::default()));
+    /// Locked separately for each calendar URL so that different URLs can be fetched at the
+    /// same time while the same one is only fetched once
+    static ref FETCH_LOCKS: Mutex<HashMap<String, Arc<Mutex<()>>>> = Mutex::new(HashMap::new());
+    static ref SECONDARY_FEEDS: RwLock<Vec<SecondaryFeed>> = RwLock::new(Vec::new());
+    static ref OVERRIDES: RwLock<HashMap<String, EventOverride>> = RwLock::new(HashMap::new());
+    static ref CUSTOM_EVENTS: RwLock<Vec<Event>> = RwLock::new(Vec::new());
+    static ref UPLOADED_CALENDARS: RwLock<Vec<(UploadedCalendar, Vec<Event>)>> =
+        RwLock::new(Vec::new());
+    static ref LAST_SUCCESSFUL_FETCH: RwLock<Option<DateTime<Utc>>> = RwLock::new(None);
+    /// Times of the failed downloads of the last `RECENT_ERRORS_HOURS`
+    static ref FETCH_ERRORS: RwLock<Vec<DateTime<Utc>>> = RwLock::new(Vec::new());
+    static ref ARCHIVE_READER: RwLock<Option<Arc<ArchiveReader>>> = RwLock::new(None);
+    /// Sorted by date so that successive renames chain
+    static ref GROUP_REMAPS: RwLock<Vec<(NaiveDate, Promo, Promo)>> = RwLock::new(Vec::new());
+    static ref GROUP_ALIASES: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+    /// Group names of the last fetch that no template nor alias recognizes, with their event count
+    static ref UNCLASSIFIED_GROUPS: RwLock<BTreeMap<String, usize>> = RwLock::new(BTreeMap::new());
 }
 
-static CALENDAR_CACHE: (i64, Vec<Event>) = (0, Vec::new());
+/// Reads the events archived from `start` (inclusive) to `end` (exclusive)
+pub type ArchiveReader = dyn Fn(NaiveDate, NaiveDate) -> Vec<Event> + Send + Sync;
 
-#[derive(Debug, Clone)]
+const CACHE_TTL_MS: i64 = 1000 * 60 * 10;
+/// The ADE feed isn't fetched again by 🔄 when it's more recent than this
+const REFRESH_COOLDOWN_MS: i64 = 1000 * 60;
+/// Window of the errors counted by /botstats
+pub const RECENT_ERRORS_HOURS: i64 = 24;
+/// Secondary feeds fetched at the same time, the others wait for a free slot
+const MAX_CONCURRENT_FETCHES: usize = 4;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum EventType {
     CM,
     TD,
@@ -26,18 +66,108 @@ pub enum EventType {
     OTHER,
 }
 
-#[derive(Debug, Clone)]
+impl EventType {
+    /// Type written in lesson codes (`R3.04_TD`) and in the archive, `OTHER` when unknown
+    pub fn from_code(code: &str) -> EventType {
+        match code {
+            "TD" => EventType::TD,
+            "TP" => EventType::TP,
+            "CM" => EventType::CM,
+            _ => EventType::OTHER,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
+    /// Identifier that stays the same when ADE moves or edits the event
+    pub uid: String,
     pub summary: String,
+    #[serde(with = "paris_timestamp")]
     pub start: DateTime<Tz>,
+    #[serde(with = "paris_timestamp")]
     pub end: DateTime<Tz>,
     pub location: String,
     pub lesson: String,
     pub group: String,
     pub teacher: Option<String>,
+    /// Extra description lines after the teacher ("apporter calculatrice")
+    pub notes: Vec<String>,
     pub event_type: EventType,
+    /// Set with /override
+    pub admin_note: Option<String>,
+    pub cancelled: bool,
+    /// Category of the secondary feed the event comes from, shown to every promo
+    pub category: Option<String>,
+}
+
+/// Events are cached as unix timestamps, chrono only deserializes fixed time zones
+mod paris_timestamp {
+    use chrono::{DateTime, TimeZone};
+    use chrono_tz::{Europe::Paris, Tz};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(date: &DateTime<Tz>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(date.timestamp())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Tz>, D::Error> {
+        let timestamp = i64::deserialize(deserializer)?;
+        Paris
+            .timestamp_opt(timestamp, 0)
+            .single()
+            .ok_or_else(|| D::Error::custom(format!("invalid timestamp {}", timestamp)))
+    }
 }
 
+impl Event {
+    /// Same for the copies ADE makes of an event for each group it concerns, which only differ
+    /// by their uid and group
+    pub fn identity(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (
+            self.start.timestamp(),
+            self.end.timestamp(),
+            &self.summary,
+            &self.lesson,
+            &self.location,
+            &self.category,
+        )
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether the event comes from the ADE feed, not from /evenement nor a secondary feed
+    pub fn is_from_ade(&self) -> bool {
+        self.category.is_none() && !self.uid.starts_with(CUSTOM_UID_PREFIX)
+    }
+
+    /// Code of the lesson (`R3.04`), the first word of its name
+    pub fn lesson_code(&self) -> &str {
+        self.lesson
+            .split_whitespace()
+            .next()
+            .unwrap_or(&self.lesson)
+    }
+}
+
+/// Extra calendar (BDE, sports association...) merged with the ADE feed
+#[derive(Debug, Clone)]
+pub struct SecondaryFeed {
+    pub category: String,
+    pub url: String,
+}
+
+/// Annotation added by an admin on top of what ADE says about an event
+#[derive(Debug, Clone, Default)]
+pub struct EventOverride {
+    pub note: Option<String>,
+    pub cancelled: bool,
+}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub enum Department {
     INFO,
@@ -49,260 +179,1090 @@ pub enum Department {
 pub struct Promo {
     pub year: i8,
     pub deparment: Department,
+    /// Letter of the parcours of third-year groups (`3-INFO-A-31`)
+    pub parcours: Option<char>,
     pub group: i8,
 }
 
-impl std::fmt::Display for Promo {
+impl std::fmt::Display for Department {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let department = match self.deparment {
+        let name = match self {
             Department::INFO => "INFO",
             Department::GEII => "GEII",
             Department::RT => "RT",
         };
 
-        write!(f, "{}-{}-{}", self.year, department, self.group)
+        write!(f, "{}", name)
     }
 }
 
-async fn fetch_events() -> Result<Vec<Event>, String> {
+impl std::fmt::Display for Promo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.parcours {
+            Some(parcours) => write!(
+                f,
+                "{}-{}-{}-{}",
+                self.year, self.deparment, parcours, self.group
+            ),
+            None => write!(f, "{}-{}-{}", self.year, self.deparment, self.group),
+        }
+    }
+}
+
+/// Parses back the output of `Display`, unlike `parse_promo_name` which parses ADE/role names
+impl FromStr for Promo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut split = s.split('-').collect::<Vec<&str>>();
+        let parcours = match split.len() {
+            3 => None,
+            4 => {
+                let mut letters = split.remove(2).chars();
+                match (letters.next(), letters.next()) {
+                    (Some(letter), None) if letter.is_ascii_uppercase() => Some(letter),
+                    _ => return Err(format!("Invalid promo parcours: {}", s)),
+                }
+            }
+            _ => return Err(format!("Invalid promo: {}", s)),
+        };
+
+        let year = split[0]
+            .parse::<i8>()
+            .map_err(|_| format!("Invalid promo year: {}", s))?;
+        let department =
+            parse_department(split[1]).ok_or(format!("Invalid promo department: {}", s))?;
+        let group = split[2]
+            .parse::<i8>()
+            .map_err(|_| format!("Invalid promo group: {}", s))?;
+
+        Ok(Promo {
+            year,
+            deparment: department,
+            parcours,
+            group,
+        })
+    }
+}
+
+fn parse_department(name: &str) -> Option<Department> {
+    match name {
+        "INFO" => Some(Department::INFO),
+        "GEII" => Some(Department::GEII),
+        "RT" => Some(Department::RT),
+        _ => None,
+    }
+}
+
+/// One-off event added with /evenement ajouter
+#[derive(Debug, Clone)]
+pub struct CustomEvent {
+    pub id: i64,
+    pub title: String,
+    pub location: String,
+    pub start: DateTime<Tz>,
+    pub end: DateTime<Tz>,
+    /// Group names as written in ADE (`1-INFO-S1`, `1-INFO-32`...)
+    pub groups: Vec<String>,
+}
+
+const CUSTOM_UID_PREFIX: &str = "custom-";
+
+impl CustomEvent {
+    /// One event per targeted promo, all sharing the same uid
+    pub fn events(&self) -> Vec<Event> {
+        self.groups
+            .iter()
+            .map(|group| Event {
+                uid: format!("{}{}", CUSTOM_UID_PREFIX, self.id),
+                summary: self.title.clone(),
+                start: self.start,
+                end: self.end,
+                location: self.location.clone(),
+                lesson: self.title.clone(),
+                group: group.clone(),
+                teacher: None,
+                notes: Vec::new(),
+                event_type: EventType::OTHER,
+                admin_note: None,
+                cancelled: false,
+                category: None,
+            })
+            .collect()
+    }
+}
+
+/// Replaces the custom events merged with the feed
+pub fn set_custom_events(custom_events: &[CustomEvent]) {
+    *CUSTOM_EVENTS
+        .write()
+        .expect("Failed to lock custom events!") =
+        custom_events.iter().flat_map(|e| e.events()).collect();
+}
+
+/// ICS file uploaded with /calendar upload, used instead of the feed (`replace`) or on top of it
+/// from `start` to `end` (inclusive)
+#[derive(Debug, Clone)]
+pub struct UploadedCalendar {
+    pub id: i64,
+    pub name: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub replace: bool,
+    pub body: String,
+}
+
+impl UploadedCalendar {
+    /// Events of the file within the covered dates
+    pub fn events(&self) -> Result<Vec<Event>, String> {
+        Ok(parse_events(&self.body)?
+            .into_iter()
+            .filter(|e| self.covers(e))
+            .collect())
+    }
+
+    fn covers(&self, evt: &Event) -> bool {
+        let day = evt.start.date_naive();
+        day >= self.start && day <= self.end
+    }
+}
+
+/// Replaces the uploaded calendars merged with the feed, the unreadable ones are skipped
+pub fn set_uploaded_calendars(calendars: &[UploadedCalendar]) {
+    let mut parsed = Vec::new();
+    for calendar in calendars {
+        match calendar.events() {
+            Ok(events) => parsed.push((calendar.clone(), events)),
+            Err(err) => println!("Skipping uploaded calendar {}: {}", calendar.name, err),
+        }
+    }
+
+    *UPLOADED_CALENDARS
+        .write()
+        .expect("Failed to lock uploaded calendars!") = parsed;
+}
+
+/// Replaces the overrides applied to the fetched events, keyed by event uid
+pub fn set_overrides(overrides: HashMap<String, EventOverride>) {
+    *OVERRIDES.write().expect("Failed to lock overrides!") = overrides;
+}
+
+fn apply_overrides(events: &mut [Event]) {
+    let overrides = OVERRIDES.read().expect("Failed to lock overrides!");
+    for evt in events {
+        if let Some(o) = overrides.get(&evt.uid) {
+            evt.admin_note = o.note.clone();
+            evt.cancelled = o.cancelled;
+        }
+    }
+}
+
+/// Replaces the secondary feeds merged with the ADE feed
+pub fn set_calendar_url(url: Option<String>) {
+    *CALENDAR_URL.write().expect("Failed to lock calendar URL!") = url;
+}
+
+/// URL of the ADE export
+fn calendar_url() -> String {
+    CALENDAR_URL
+        .read()
+        .expect("Failed to lock calendar URL!")
+        .clone()
+        .unwrap_or_else(|| std::env::var("CALENDAR_URL").expect("CALENDAR_URL not set!"))
+}
+
+pub fn set_secondary_feeds(feeds: Vec<SecondaryFeed>) {
+    *SECONDARY_FEEDS.write().expect("Failed to lock feeds!") = feeds;
+}
+
+/// Replaces the in-memory cache, for instance by one shared with other instances
+pub fn set_cache_backend(backend: Arc<dyn CacheBackend>) {
+    *CACHE_BACKEND
+        .write()
+        .expect("Failed to lock cache backend!") = backend;
+}
+
+fn cache_backend() -> Arc<dyn CacheBackend> {
+    CACHE_BACKEND
+        .read()
+        .expect("Failed to lock cache backend!")
+        .clone()
+}
+
+/// Forces the next fetch to hit the calendar URLs instead of the cache
+pub async fn invalidate_cache() {
+    cache_backend().clear().await;
+}
+
+/// Forces the next fetch of the ADE feed to hit its URL, unless it was fetched less than
+/// `REFRESH_COOLDOWN_MS` ago so that spamming 🔄 doesn't refetch it every time
+pub async fn refresh_calendar() {
+    let url = calendar_url();
     let now = Utc::now().timestamp_millis();
-    if now - CALENDAR_CACHE.0 < 1000 * 60 * 10 {
-        return Ok(CALENDAR_CACHE.1.clone());
+    match cache_backend().get(&url).await {
+        Some((fetched_at, _)) if now - fetched_at < REFRESH_COOLDOWN_MS => {}
+        _ => cache_backend().invalidate(&url).await,
     }
+}
 
-    let body = reqwest::get(CALENDAR_URL.as_str())
+/// Events of `url`, from the ADE feed when `category` is `None`
+async fn fetch_cached(url: &str, category: Option<&str>) -> Result<Vec<Event>, String> {
+    let lock = FETCH_LOCKS
+        .lock()
         .await
-        .expect("Failed to fetch calendar!")
-        .text()
-        .await
-        .expect("Failed to read calendar!");
-    let unfolded = icalendar::parser::unfold(&body);
-    let res = icalendar::parser::read_calendar(&unfolded);
+        .entry(url.to_string())
+        .or_default()
+        .clone();
+    let _guard = lock.lock().await;
+    let cache = cache_backend();
+    let now = Utc::now().timestamp_millis();
+    if let Some((fetched_at, events)) = cache.get(url).await {
+        if now - fetched_at < CACHE_TTL_MS {
+            // another instance sharing the cache may have done the fetch
+            if category.is_none() {
+                record_fetch_success(DateTime::from_timestamp_millis(fetched_at));
+            }
+            return Ok(events);
+        }
+    }
+
+    let fetched = match category {
+        Some(category) => fetch_secondary_calendar(url, category).await,
+        None => fetch_calendar(url).await,
+    };
+    let events = match fetched {
+        Ok(events) => events,
+        Err(err) => {
+            record_fetch_error();
+            return Err(err);
+        }
+    };
+    if category.is_none() {
+        record_fetch_success(Some(Utc::now()));
+    }
+    cache.set(url, &(now, events.clone())).await;
+    Ok(events)
+}
+
+fn record_fetch_success(fetched_at: Option<DateTime<Utc>>) {
+    let mut last = LAST_SUCCESSFUL_FETCH
+        .write()
+        .expect("Failed to lock fetch stats!");
+    *last = (*last).max(fetched_at);
+}
+
+fn record_fetch_error() {
+    let now = Utc::now();
+    let mut errors = FETCH_ERRORS.write().expect("Failed to lock fetch stats!");
+    errors.retain(|t| now - *t < chrono::Duration::hours(RECENT_ERRORS_HOURS));
+    errors.push(now);
+}
+
+/// Health of the calendar fetching, shown by /botstats
+#[derive(Debug, Clone, Default)]
+pub struct FetchStats {
+    /// Last time the ADE feed was downloaded and parsed, by this instance or one sharing its cache
+    pub last_success: Option<DateTime<Utc>>,
+    /// When the cached ADE events were fetched, `None` when nothing is cached
+    pub cached_at: Option<DateTime<Utc>>,
+    pub cached_events: usize,
+    pub cached_groups: usize,
+    /// Failed downloads of any feed over the last 24 hours
+    pub recent_errors: usize,
+}
+
+pub async fn fetch_stats() -> FetchStats {
+    let cached = cache_backend().get(&calendar_url()).await;
+    let mut stats = FetchStats {
+        last_success: *LAST_SUCCESSFUL_FETCH
+            .read()
+            .expect("Failed to lock fetch stats!"),
+        recent_errors: FETCH_ERRORS
+            .read()
+            .expect("Failed to lock fetch stats!")
+            .iter()
+            .filter(|t| Utc::now() - **t < chrono::Duration::hours(RECENT_ERRORS_HOURS))
+            .count(),
+        ..Default::default()
+    };
+
+    if let Some((fetched_at, events)) = cached {
+        stats.cached_at = DateTime::from_timestamp_millis(fetched_at);
+        stats.cached_events = events.len();
+        stats.cached_groups = events
+            .iter()
+            .map(|e| &e.group)
+            .collect::<BTreeSet<_>>()
+            .len();
+    }
+
+    stats
+}
+
+/// Events of the feed, with the uploaded calendars applied
+async fn fetch_events() -> Result<Vec<Event>, String> {
+    let fetched = fetch_cached(&calendar_url(), None).await;
+    let uploads = UPLOADED_CALENDARS
+        .read()
+        .expect("Failed to lock uploaded calendars!");
+
+    let mut events = match fetched {
+        Ok(events) => events,
+        // uploads are typically made because ADE is down, they're shown on their own meanwhile
+        Err(err) if !uploads.is_empty() => {
+            println!("Failed to fetch calendar, using uploaded ones: {}", err);
+            Vec::new()
+        }
+        Err(err) => return Err(err),
+    };
+    for (upload, upload_events) in uploads.iter() {
+        if upload.replace {
+            events.retain(|e| !upload.covers(e));
+        }
+        events.extend(upload_events.iter().cloned());
+    }
+
+    let unclassified = apply_group_aliases(&mut events);
+    let mut previous = UNCLASSIFIED_GROUPS
+        .write()
+        .expect("Failed to lock unclassified groups!");
+    if !unclassified.is_empty() && unclassified.keys().ne(previous.keys()) {
+        let count: usize = unclassified.values().sum();
+        println!("{} events with unknown groups, see /validate", count);
+    }
+    *previous = unclassified;
+
+    Ok(events)
+}
+
+/// Group names that don't follow the grammar mapped to a group that does, set with /groupes
+pub fn set_group_aliases(aliases: HashMap<String, String>) {
+    *GROUP_ALIASES
+        .write()
+        .expect("Failed to lock group aliases!") = aliases;
+}
+
+/// Renames the groups that have an alias, returns the names left unrecognized with how many
+/// events use them
+pub fn apply_group_aliases(events: &mut [Event]) -> BTreeMap<String, usize> {
+    let aliases = GROUP_ALIASES.read().expect("Failed to lock group aliases!");
+    let mut unclassified: BTreeMap<String, usize> = BTreeMap::new();
+    for evt in events.iter_mut() {
+        if parse_promo_name(&evt.group).is_some() {
+            continue;
+        }
+        match aliases.get(&evt.group) {
+            Some(group) => evt.group = group.clone(),
+            None => *unclassified.entry(evt.group.clone()).or_default() += 1,
+        }
+    }
+
+    unclassified
+}
+
+/// Group names of the feed that no event can be shown for, until an alias is set
+pub fn unclassified_groups() -> BTreeMap<String, usize> {
+    UNCLASSIFIED_GROUPS
+        .read()
+        .expect("Failed to lock unclassified groups!")
+        .clone()
+}
+
+/// Events of every secondary feed, fetched concurrently. A failing feed is skipped rather than
+/// hiding the timetable.
+async fn fetch_secondary_events() -> Vec<Event> {
+    let feeds = SECONDARY_FEEDS
+        .read()
+        .expect("Failed to lock feeds!")
+        .clone();
+
+    let semaphore = Semaphore::new(MAX_CONCURRENT_FETCHES);
+    let semaphore = &semaphore;
+    let results = futures::future::join_all(feeds.iter().map(|feed| async move {
+        let _permit = semaphore.acquire().await.expect("Semaphore closed!");
+        (feed, fetch_cached(&feed.url, Some(&feed.category)).await)
+    }))
+    .await;
+
     let mut events: Vec<Event> = Vec::new();
+    for (feed, result) in results {
+        match result {
+            Ok(feed_events) => events.extend(feed_events),
+            Err(err) => println!("Failed to fetch {} feed: {}", feed.category, err),
+        }
+    }
 
-    if let Ok(calendar) = res {
-        calendar.components.iter().for_each(|c| {
-            let summary = c
-                .properties
-                .iter()
-                .find(|p| p.name == "SUMMARY")
-                .expect("Failed to find summary");
+    events
+}
 
-            let start_datetime = c
-                .properties
-                .iter()
-                .find(|p| p.name == "DTSTART")
-                .expect("Failed to find start");
+/// Events of a calendar published by a teacher, cached like the secondary feeds
+pub async fn get_teacher_events(url: &str, teacher: &str) -> Result<Vec<Event>, String> {
+    fetch_cached(url, Some(teacher)).await
+}
 
-            let end_datetime = c
-                .properties
-                .iter()
-                .find(|p| p.name == "DTEND")
-                .expect("Failed to find end");
+/// Downloads and parses a calendar, bypassing the cache
+pub async fn fetch_calendar(url: &str) -> Result<Vec<Event>, String> {
+    parse_events(&fetch_body(url).await?)
+}
 
-            let location = c
-                .properties
-                .iter()
-                .find(|p| p.name == "LOCATION")
-                .expect("Failed to find location");
+/// `Last-Modified` header of the ADE feed, cheap enough to be polled between full fetches
+pub async fn calendar_last_modified() -> Result<Option<String>, String> {
+    fetch_last_modified(&calendar_url()).await
+}
 
-            let description = c
-                .properties
-                .iter()
-                .find(|p| p.name == "DESCRIPTION")
-                .expect("Failed to find description");
-
-            let start = NaiveDateTime::parse_from_str(start_datetime.val.as_str(), ISO_8601);
-            let end = NaiveDateTime::parse_from_str(end_datetime.val.as_str(), ISO_8601);
-
-            let split = description
-                .val
-                .as_str()
-                .split("\\n\\n")
-                .collect::<Vec<&str>>();
-            let split2 = split[1].split("\\n").collect::<Vec<&str>>();
-
-            let event = Event {
-                summary: summary.val.as_str().to_string(),
-                start: Paris.from_utc_datetime(&start.unwrap()),
-                end: Paris.from_utc_datetime(&end.unwrap()),
-                location: location.val.as_str().to_string(),
-                lesson: split[0].to_string(),
-                group: split2[0].to_string(),
-                teacher: if split2.len() > 1 {
-                    Some(split2[1].to_string())
-                } else {
-                    None
-                },
-                event_type: if CLASS_TYPE_REGEX.is_match(summary.val.as_str()) {
-                    let event_type = &summary.val.as_str()[6..8];
-                    match event_type {
-                        "TD" => EventType::TD,
-                        "TP" => EventType::TP,
-                        "CM" => EventType::CM,
-                        _ => EventType::OTHER,
-                    }
-                } else {
-                    EventType::OTHER
-                },
-            };
+/// `Last-Modified` header returned by a HEAD request, `None` when the server doesn't send it
+pub async fn fetch_last_modified(url: &str) -> Result<Option<String>, String> {
+    let response = reqwest::Client::new()
+        .head(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch calendar headers: {}", e))?;
 
-            events.push(event);
-        });
+    Ok(response
+        .headers()
+        .get(reqwest::header::LAST_MODIFIED)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string()))
+}
 
-        Ok(events)
-    } else {
-        Err("Failed to parse calendar!".to_string())
+async fn fetch_body(url: &str) -> Result<String, String> {
+    reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch calendar: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read calendar: {}", e))
+}
+
+/// Events of an ADE export and the ones that couldn't be parsed
+#[derive(Debug, Default)]
+pub struct ParseReport {
+    pub events: Vec<Event>,
+    /// UID (or summary) of each skipped event and why it was skipped
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Parses an ADE export, events that can't be parsed are skipped
+pub fn parse_events(body: &str) -> Result<Vec<Event>, String> {
+    let report = match parse_events_report(body) {
+        Ok(report) => report,
+        Err(err) => {
+            #[cfg(feature = "sentry")]
+            sentry::capture_message(&err, sentry::Level::Error);
+            return Err(err);
+        }
+    };
+    for (_, err) in &report.skipped {
+        println!("Skipping event: {}", err);
     }
+    #[cfg(feature = "sentry")]
+    report_skipped_events(&report.skipped);
+
+    Ok(report.events)
+}
+
+#[cfg(feature = "sentry")]
+lazy_static! {
+    /// Hashes of the sets of skipped events already sent to Sentry
+    static ref REPORTED_SKIPPED_EVENTS: RwLock<HashSet<u64>> = RwLock::new(HashSet::new());
 }
 
-fn set_events(name: &str, event: Event, current_list: &mut HashMap<Promo, Vec<Event>>) {
-    let split = name.split("-").collect::<Vec<&str>>();
-    let promo = parse_promo_name(name);
-    if promo.is_none() {
-        println!("Failed to parse promo name: {}", name);
+/// The skipped events are grouped in a single Sentry issue, a set of skipped events is only
+/// reported the first time it's seen so that the same broken events don't warn at every fetch
+#[cfg(feature = "sentry")]
+fn report_skipped_events(skipped: &[(String, String)]) {
+    if skipped.is_empty() {
         return;
     }
-    let promo = promo.unwrap();
-    let group_name = split[2];
-
-    if group_name.starts_with("S") {
-        for i in 1..5 {
-            for j in 1..3 {
-                let new_group_name = format!("{}{}", i, j);
-                let mut promo = promo.clone();
-                promo.group = new_group_name.parse::<i8>().unwrap();
-                let mut group_events = if let Some(events) = current_list.get(&promo.clone()) {
-                    events.clone()
-                } else {
-                    Vec::new()
-                };
+    let mut hasher = DefaultHasher::new();
+    skipped.iter().collect::<BTreeSet<_>>().hash(&mut hasher);
+    if !REPORTED_SKIPPED_EVENTS
+        .write()
+        .expect("Failed to lock skipped events!")
+        .insert(hasher.finish())
+    {
+        return;
+    }
+
+    let examples = skipped
+        .iter()
+        .take(10)
+        .map(|(id, err)| format!("{}: {}", id, err))
+        .collect::<Vec<String>>()
+        .join("\n");
+    sentry::with_scope(
+        |scope| {
+            scope.set_fingerprint(Some(&["skipped-events"]));
+            scope.set_extra("examples", examples.into());
+        },
+        || {
+            sentry::capture_message(
+                &format!("{} calendar events skipped", skipped.len()),
+                sentry::Level::Warning,
+            )
+        },
+    );
+}
 
-                group_events.push(event.clone());
-                group_events.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
-                current_list.insert(promo, group_events);
+/// Same as `parse_events`, keeping track of the skipped events instead of logging them
+pub fn parse_events_report(body: &str) -> Result<ParseReport, String> {
+    let unfolded = icalendar::parser::unfold(body);
+    let calendar = icalendar::parser::read_calendar(&unfolded)
+        .map_err(|_| "Failed to parse calendar!".to_string())?;
+
+    let mut report = ParseReport::default();
+    for component in calendar.components.iter().filter(|c| c.name == "VEVENT") {
+        match parse_event(component) {
+            Ok(event) => report.events.push(event),
+            Err(err) => {
+                let id = ["UID", "SUMMARY"]
+                    .iter()
+                    .find_map(|name| component.properties.iter().find(|p| p.name == *name))
+                    .map_or("?".to_string(), |p| p.val.to_string());
+                report.skipped.push((id, err));
             }
+        }
+    }
 
-            let mut promo = promo.clone();
-            promo.group = i;
+    Ok(report)
+}
 
-            let mut group_events = if let Some(events) = current_list.get(&promo.clone()) {
-                events.clone()
-            } else {
-                Vec::new()
-            };
+/// Downloads and parses the ADE feed, bypassing the cache, for /validate
+pub async fn validate_calendar() -> Result<ParseReport, String> {
+    parse_events_report(&fetch_body(&calendar_url()).await?)
+}
+
+fn parse_event(c: &icalendar::parser::Component) -> Result<Event, String> {
+    let property = |name: &str| {
+        c.properties
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.val.as_str())
+            .ok_or(format!("Failed to find {}", name.to_lowercase()))
+    };
+
+    let summary = property("SUMMARY")?;
+    let start_datetime = property("DTSTART")?;
+    let end_datetime = property("DTEND")?;
+    let location = property("LOCATION")?;
+    let description = property("DESCRIPTION")?;
+    let uid = property("UID").ok();
+
+    let start = NaiveDateTime::parse_from_str(start_datetime, ISO_8601)
+        .map_err(|_| format!("Invalid start date: {}", start_datetime))?;
+    let end = NaiveDateTime::parse_from_str(end_datetime, ISO_8601)
+        .map_err(|_| format!("Invalid end date: {}", end_datetime))?;
+
+    let split = description.split("\\n\\n").collect::<Vec<&str>>();
+    if split.len() < 2 {
+        return Err(format!("Invalid description: {}", description));
+    }
+    // group, teacher then free-form notes, ADE ends the description with the export date
+    let split2 = split[1]
+        .split("\\n")
+        .map(|line| line.trim().replace("\\,", ",").replace("\\;", ";"))
+        .filter(|line| !line.is_empty() && !line.starts_with("(Exporté le"))
+        .collect::<Vec<String>>();
+    if split2.is_empty() {
+        return Err(format!("Invalid description: {}", description));
+    }
 
-            group_events.push(event.clone());
-            group_events.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
-            current_list.insert(promo, group_events);
+    Ok(Event {
+        uid: match uid {
+            Some(uid) => uid.to_string(),
+            None => format!("{}-{}-{}", summary, start_datetime, split2[0]),
+        },
+        summary: summary.to_string(),
+        start: Paris.from_utc_datetime(&start),
+        end: Paris.from_utc_datetime(&end),
+        location: location.to_string(),
+        lesson: split[0].to_string(),
+        group: split2[0].clone(),
+        teacher: split2.get(1).cloned(),
+        notes: split2.iter().skip(2).cloned().collect(),
+        event_type: match CLASS_TYPE_REGEX.captures(summary) {
+            Some(captures) => EventType::from_code(&captures[3]),
+            None => EventType::OTHER,
+        },
+        admin_note: None,
+        cancelled: false,
+        category: None,
+    })
+}
+
+/// Downloads and parses a secondary calendar, bypassing the cache
+pub async fn fetch_secondary_calendar(url: &str, category: &str) -> Result<Vec<Event>, String> {
+    parse_secondary_events(&fetch_body(url).await?, category)
+}
+
+/// Parses any ICS calendar, its events aren't tied to a group and go to every promo
+pub fn parse_secondary_events(body: &str, category: &str) -> Result<Vec<Event>, String> {
+    let unfolded = icalendar::parser::unfold(body);
+    let calendar = icalendar::parser::read_calendar(&unfolded)
+        .map_err(|_| "Failed to parse calendar!".to_string())?;
+
+    let mut events: Vec<Event> = Vec::new();
+    for component in calendar.components.iter().filter(|c| c.name == "VEVENT") {
+        match parse_secondary_event(component, category) {
+            Ok(event) => events.push(event),
+            Err(err) => println!("Skipping {} event: {}", category, err),
         }
+    }
 
-        let mut group_events = if let Some(events) = current_list.get(&promo.clone()) {
-            events.clone()
-        } else {
-            Vec::new()
-        };
+    Ok(events)
+}
 
-        group_events.push(event.clone());
-        group_events.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
-        current_list.insert(promo.clone(), group_events);
-    } else if group_name.len() == 2 {
-        let mut group_events = if let Some(events) = current_list.get(&promo.clone()) {
-            events.clone()
-        } else {
-            Vec::new()
-        };
+fn parse_secondary_event(
+    c: &icalendar::parser::Component,
+    category: &str,
+) -> Result<Event, String> {
+    let property = |name: &str| {
+        c.properties
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.val.as_str())
+    };
+    let tzid = |name: &str| {
+        c.properties
+            .iter()
+            .find(|p| p.name == name)?
+            .params
+            .iter()
+            .find(|p| p.key == "TZID")?
+            .val
+            .as_ref()
+            .map(|v| v.as_str())
+    };
 
-        group_events.push(event.clone());
-        group_events.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
-        current_list.insert(promo.clone(), group_events);
-    } else if group_name.len() == 1 {
-        for i in 1..3 {
-            let new_group_name = format!("{}{}", group_name, i);
-            let mut promo = promo.clone();
-            promo.group = new_group_name.parse::<i8>().unwrap();
-            let mut group_events = if let Some(events) = current_list.get(&promo.clone()) {
-                events.clone()
-            } else {
-                Vec::new()
+    let summary = property("SUMMARY").ok_or("Failed to find summary")?;
+    let start_datetime = property("DTSTART").ok_or("Failed to find dtstart")?;
+    let (start, all_day) = parse_ical_datetime(start_datetime, tzid("DTSTART"))
+        .ok_or(format!("Invalid start date: {}", start_datetime))?;
+    let end = match property("DTEND") {
+        Some(end) => {
+            parse_ical_datetime(end, tzid("DTEND"))
+                .ok_or(format!("Invalid end date: {}", end))?
+                .0
+        }
+        None if all_day => start + chrono::Duration::days(1),
+        None => start + chrono::Duration::hours(1),
+    };
+    // keeps all-day events within their last day
+    let end = if all_day {
+        end - chrono::Duration::seconds(1)
+    } else {
+        end
+    };
+
+    Ok(Event {
+        uid: match property("UID") {
+            Some(uid) => uid.to_string(),
+            None => format!("{}-{}-{}", category, summary, start_datetime),
+        },
+        summary: summary.to_string(),
+        start,
+        end,
+        location: property("LOCATION").unwrap_or_default().to_string(),
+        lesson: summary.to_string(),
+        group: String::new(),
+        teacher: None,
+        notes: Vec::new(),
+        event_type: EventType::OTHER,
+        admin_note: None,
+        cancelled: false,
+        category: Some(category.to_string()),
+    })
+}
+
+/// UTC, local to the `TZID` zone (Paris when missing or unknown) or all-day date, the latter
+/// flagged with `true`
+fn parse_ical_datetime(value: &str, tzid: Option<&str>) -> Option<(DateTime<Tz>, bool)> {
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(value, ISO_8601) {
+        return Some((Paris.from_utc_datetime(&datetime), false));
+    }
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        let zone = tzid.and_then(|t| t.parse::<Tz>().ok()).unwrap_or(Paris);
+        let datetime = zone.from_local_datetime(&datetime).earliest()?;
+        return Some((datetime.with_timezone(&Paris), false));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+    Some((
+        Paris
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest()?,
+        true,
+    ))
+}
+
+/// Group hierarchy of the feed: semestre (`1-INFO-S1`) → groupe (`1-INFO-3`) → sous-groupe
+/// (`1-INFO-31`), built from the group names actually present in the calendar. Third-year groups
+/// are split by parcours in between: `3-INFO-S5` → `3-INFO-A-0` → `3-INFO-A-3` → `3-INFO-A-31`.
+pub struct GroupHierarchy {
+    groups: HashMap<(i8, Department), PromoGroups>,
+}
+
+/// Sous-groupes of each parcours and groupe of a year, the whole parcours being groupe 0
+type PromoGroups = BTreeMap<(Option<char>, i8), BTreeSet<i8>>;
+
+impl GroupHierarchy {
+    pub fn from_events(events: &[Event]) -> GroupHierarchy {
+        let mut groups: HashMap<(i8, Department), PromoGroups> = HashMap::new();
+        for evt in events {
+            let Some(promo) = parse_promo_name(&evt.group) else {
+                continue;
             };
 
-            group_events.push(event.clone());
-            group_events.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
-            current_list.insert(promo, group_events);
+            let promo_groups = groups
+                .entry((promo.year, promo.deparment.clone()))
+                .or_default();
+            if promo.parcours.is_some() {
+                promo_groups.entry((promo.parcours, 0)).or_default();
+            }
+            if promo.group >= 10 {
+                promo_groups
+                    .entry((promo.parcours, promo.group / 10))
+                    .or_default()
+                    .insert(promo.group);
+            } else if promo.group > 0 {
+                promo_groups
+                    .entry((promo.parcours, promo.group))
+                    .or_default();
+            }
         }
 
-        let mut group_events = if let Some(events) = current_list.get(&promo.clone()) {
-            events.clone()
-        } else {
-            Vec::new()
+        GroupHierarchy { groups }
+    }
+
+    /// Every promo of the feed: semesters, groupes and sous-groupes
+    pub fn all(&self) -> Vec<Promo> {
+        self.groups
+            .keys()
+            .flat_map(|(year, department)| {
+                self.targets(&Promo {
+                    year: *year,
+                    deparment: department.clone(),
+                    parcours: None,
+                    group: 0,
+                })
+            })
+            .collect()
+    }
+
+    /// Every promo an event for `promo` has to be shown to: itself and all its known descendants
+    pub fn targets(&self, promo: &Promo) -> Vec<Promo> {
+        let mut targets = vec![promo.clone()];
+        let Some(promo_groups) = self.groups.get(&(promo.year, promo.deparment.clone())) else {
+            return targets;
         };
 
-        group_events.push(event.clone());
-        group_events.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
-        current_list.insert(promo.clone(), group_events);
+        let with_group = |parcours: Option<char>, group: i8| Promo {
+            parcours,
+            group,
+            ..promo.clone()
+        };
+
+        if promo.group == 0 {
+            // the semester reaches every parcours, a parcours only its own groups
+            for ((parcours, group), sub_groups) in promo_groups
+                .iter()
+                .filter(|((p, g), _)| promo.parcours.is_none() || (*p == promo.parcours && *g > 0))
+            {
+                targets.push(with_group(*parcours, *group));
+                targets.extend(sub_groups.iter().map(|g| with_group(*parcours, *g)));
+            }
+        } else if promo.group < 10 {
+            if let Some(sub_groups) = promo_groups.get(&(promo.parcours, promo.group)) {
+                targets.extend(sub_groups.iter().map(|g| with_group(promo.parcours, *g)));
+            }
+        }
+
+        targets
     }
 }
 
+/// Every event of the feed, without any fan-out
+pub async fn get_events() -> Result<Vec<Event>, String> {
+    let mut events = fetch_events().await?;
+    apply_overrides(&mut events);
+    Ok(events)
+}
+
 pub async fn get_sorted_events(day: NaiveDate) -> Result<HashMap<Promo, Vec<Event>>, String> {
-    let res = fetch_events().await;
-    if let Ok(events) = res {
-        let mut map: HashMap<Promo, Vec<Event>> = HashMap::new();
+    get_sorted_events_range(day, day + chrono::Duration::days(1)).await
+}
 
-        // only show events for today
-        for evt in events
+/// Events from `start` (inclusive) to `end` (exclusive), fanned out to every concerned promo.
+/// Custom and secondary feed events are included, unlike `get_events`.
+pub async fn get_sorted_events_range(
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<HashMap<Promo, Vec<Event>>, String> {
+    let (events, secondary_events) = tokio::join!(fetch_events(), fetch_secondary_events());
+    let mut events = events?;
+    events.extend(archived_events(&events, start, end));
+    events.extend(
+        CUSTOM_EVENTS
+            .read()
+            .expect("Failed to lock custom events!")
             .iter()
-            .filter(|e| {
-                e.start.date_naive() >= day && e.end.date_naive() < day + chrono::Duration::days(1)
-            })
-            .collect::<Vec<&Event>>()
-        {
-            set_events(&evt.group, evt.clone(), &mut map);
+            .cloned(),
+    );
+    events.extend(secondary_events);
+    apply_overrides(&mut events);
+
+    Ok(sort_events(&events, start, end))
+}
+
+/// Sets where the past events are read from, the archive is kept by the bot
+pub fn set_archive_reader(reader: Arc<ArchiveReader>) {
+    *ARCHIVE_READER.write().expect("Failed to lock archive!") = Some(reader);
+}
+
+/// Archived events of the past days of the range that ADE doesn't return anymore
+fn archived_events(live: &[Event], start: NaiveDate, end: NaiveDate) -> Vec<Event> {
+    let end = end.min(Utc::now().with_timezone(&Paris).date_naive());
+    if start >= end {
+        return Vec::new();
+    }
+    let Some(reader) = ARCHIVE_READER
+        .read()
+        .expect("Failed to lock archive!")
+        .clone()
+    else {
+        return Vec::new();
+    };
+
+    let live_days: BTreeSet<NaiveDate> = live.iter().map(|e| e.start.date_naive()).collect();
+    reader(start, end)
+        .into_iter()
+        .filter(|e| !live_days.contains(&e.start.date_naive()))
+        .collect()
+}
+
+/// Fans out the events from `start` (inclusive) to `end` (exclusive) to every concerned promo
+pub fn sort_events(
+    events: &[Event],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> HashMap<Promo, Vec<Event>> {
+    let hierarchy = GroupHierarchy::from_events(events);
+    let mut map: HashMap<Promo, Vec<Event>> = HashMap::new();
+
+    for evt in events
+        .iter()
+        .filter(|e| e.start.date_naive() >= start && e.end.date_naive() < end)
+    {
+        let targets = if evt.category.is_some() {
+            hierarchy.all()
+        } else {
+            // reported by /validate through `unclassified_groups`
+            let Some(promo) = parse_promo_name(&evt.group) else {
+                continue;
+            };
+            hierarchy.targets(&promo)
+        };
+
+        for target in targets {
+            map.entry(target).or_default().push(evt.clone());
         }
+    }
 
-        Ok(map)
-    } else {
-        Err(res.unwrap_err())
+    for group_events in map.values_mut() {
+        group_events.sort_by_key(|e| e.start);
+        dedup_events(group_events);
     }
+
+    map
 }
 
-pub fn parse_promo_name(name: &str) -> Option<Promo> {
-    if !GROUP_REGEX.is_match(name) {
-        return None;
+/// Keeps the first copy of each event, see `Event::identity`
+pub fn dedup_events(events: &mut Vec<Event>) {
+    let mut seen = HashSet::new();
+    events.retain(|e| seen.insert(e.identity()));
+}
+
+/// How group names are written, in ADE and in role names. Each template is made of literal text
+/// and `{year}`, `{department}`, `{group}` and optionally `{parcours}` segments matching the
+/// corresponding patterns, the first template that matches is used. A group starting with `S` is
+/// a whole semester.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GroupGrammar {
+    pub templates: Vec<String>,
+    pub year: String,
+    pub department: String,
+    pub parcours: String,
+    pub group: String,
+}
+
+impl Default for GroupGrammar {
+    fn default() -> Self {
+        GroupGrammar {
+            templates: vec![
+                "{year}-{department}-{parcours}-{group}".to_string(),
+                "{year}-{department}-{group}".to_string(),
+            ],
+            year: "[1-4]".to_string(),
+            department: "[A-Z]+".to_string(),
+            parcours: "[A-Z]".to_string(),
+            // longest alternatives first, "32" must not stop at "3"
+            group: "S[1-6]|[1-4][1-2]|[1-4]".to_string(),
+        }
     }
+}
 
-    let split = name.split("-").collect::<Vec<&str>>();
-    let year = split[0].parse::<i8>();
-    if year.is_err() {
-        return None;
+impl GroupGrammar {
+    fn compile(&self) -> Result<Vec<Regex>, String> {
+        self.templates
+            .iter()
+            .map(|template| {
+                let mut pattern = String::new();
+                let mut rest = template.as_str();
+                while let Some(start) = rest.find('{') {
+                    let end = rest[start..]
+                        .find('}')
+                        .map(|end| start + end)
+                        .ok_or(format!("Unclosed segment in group template: {}", template))?;
+                    pattern.push_str(&regex::escape(&rest[..start]));
+
+                    let (name, segment) = match &rest[start + 1..end] {
+                        "year" => ("year", &self.year),
+                        "department" => ("department", &self.department),
+                        "parcours" => ("parcours", &self.parcours),
+                        "group" => ("group", &self.group),
+                        other => {
+                            return Err(format!(
+                                "Unknown segment {{{}}} in group template: {}",
+                                other, template
+                            ))
+                        }
+                    };
+                    pattern.push_str(&format!("(?P<{}>{})", name, segment));
+                    rest = &rest[end + 1..];
+                }
+                pattern.push_str(&regex::escape(rest));
+
+                // "3-INFO-3" must not match in "3-INFO-31"
+                Regex::new(&format!("^(?:{})$", pattern))
+                    .map_err(|e| format!("Invalid group template {}: {}", template, e))
+            })
+            .collect()
     }
+}
 
-    let year = year.unwrap();
-    let department = match split[1] {
-        "INFO" => Department::INFO,
-        "GEII" => Department::GEII,
-        "RT" => Department::RT,
-        _ => {
-            return None;
+/// Replaces the grammar used by `parse_promo_name`
+pub fn set_group_grammar(grammar: &GroupGrammar) -> Result<(), String> {
+    let regexes = grammar.compile()?;
+    for segment in ["year", "department", "group"] {
+        if regexes
+            .iter()
+            .any(|r| !r.capture_names().any(|n| n == Some(segment)))
+        {
+            return Err(format!(
+                "Every group template needs a {{{}}} segment",
+                segment
+            ));
         }
+    }
+
+    *GROUP_REGEXES
+        .write()
+        .expect("Failed to lock group grammar!") = regexes;
+    Ok(())
+}
+
+/// Group renamed at a semester change: on `since` the saved groups and subscriptions named `from`
+/// are renamed once, and from then on the roles named `from` mean `to`
+#[derive(Debug, Clone, Deserialize)]
+pub struct GroupRemap {
+    pub from: String,
+    pub to: String,
+    pub since: NaiveDate,
+}
+
+/// Replaces the renames applied by `remap_promo`
+pub fn set_group_remaps(remaps: &[GroupRemap]) -> Result<(), String> {
+    let parse = |name: &str| {
+        parse_promo_name(name)
+            .or_else(|| name.parse().ok())
+            .ok_or_else(|| format!("Invalid group in remap: {}", name))
     };
+    let mut parsed = remaps
+        .iter()
+        .map(|r| Ok((r.since, parse(&r.from)?, parse(&r.to)?)))
+        .collect::<Result<Vec<(NaiveDate, Promo, Promo)>, String>>()?;
+    parsed.sort_by_key(|(since, _, _)| *since);
+
+    *GROUP_REMAPS.write().expect("Failed to lock group remaps!") = parsed;
+    Ok(())
+}
+
+/// Configured renames as `(since, from, to)`, sorted by date
+pub fn group_remaps() -> Vec<(NaiveDate, Promo, Promo)> {
+    GROUP_REMAPS
+        .read()
+        .expect("Failed to lock group remaps!")
+        .clone()
+}
+
+/// Current name on `date` of a group named before the semester changes
+pub fn remap_promo(promo: Promo, date: NaiveDate) -> Promo {
+    let remaps = GROUP_REMAPS.read().expect("Failed to lock group remaps!");
+    remaps
+        .iter()
+        .filter(|(since, _, _)| *since <= date)
+        .fold(
+            promo,
+            |promo, (_, from, to)| {
+                if promo == *from {
+                    to.clone()
+                } else {
+                    promo
+                }
+            },
+        )
+}
+
+/// Promo of a Discord role, role names aren't updated at semester changes so they're remapped
+pub fn parse_role_name(name: &str) -> Option<Promo> {
+    parse_promo_name(name).map(|p| remap_promo(p, Utc::now().with_timezone(&Paris).date_naive()))
+}
 
-    let group = if split[2].starts_with("S") {
+/// Department of a group name that follows the grammar but isn't supported by the bot
+pub fn unknown_department(name: &str) -> Option<String> {
+    let regexes = GROUP_REGEXES.read().expect("Failed to lock group grammar!");
+    let captures = regexes.iter().find_map(|r| r.captures(name))?;
+    let department = &captures["department"];
+
+    match parse_department(department) {
+        Some(_) => None,
+        None => Some(department.to_string()),
+    }
+}
+
+pub fn parse_promo_name(name: &str) -> Option<Promo> {
+    let regexes = GROUP_REGEXES.read().expect("Failed to lock group grammar!");
+    let captures = regexes.iter().find_map(|r| r.captures(name))?;
+
+    let year = captures["year"].parse::<i8>().ok()?;
+    let department = parse_department(&captures["department"])?;
+    let parcours = match captures.name("parcours") {
+        Some(parcours) => Some(parcours.as_str().chars().next()?.to_ascii_uppercase()),
+        None => None,
+    };
+    let group = if captures["group"].starts_with('S') {
         0
     } else {
-        let parsed = split[2].parse::<i8>();
-        if parsed.is_err() {
-            return None;
-        }
-
-        parsed.unwrap()
+        captures["group"].parse::<i8>().ok()?
     };
 
-    let promo = Promo {
-        year: year,
+    Some(Promo {
+        year,
         deparment: department,
-        group: group,
-    };
-
-    Some(promo)
+        parcours,
+        group,
+    })
 }
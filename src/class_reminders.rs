@@ -0,0 +1,207 @@
+use chrono::{NaiveDate, Utc};
+use poise::serenity_prelude::{
+    self as serenity, InteractionResponseType, MessageComponentInteraction,
+};
+
+use crate::{
+    calendar::{get_events, get_sorted_events_range, Event, GroupHierarchy, Promo},
+    config,
+    db::Database,
+    embed::{make_event_field, view_range, EmbedOptions},
+    homework::{homework_for_class, homework_lines},
+    prefs::View,
+    Error,
+};
+
+/// The DM is sent this long before the class
+const REMIND_MINUTES: i64 = 15;
+const REMIND_BUTTON_ID: &str = "remind";
+const REMIND_SELECT_ID: &str = "remind_select";
+/// Discord select menus hold 25 options
+const MAX_OPTIONS: usize = 25;
+
+/// 🔔 button of the timetables, the day, promo and view tell which classes to offer
+pub fn remind_button_id(day: NaiveDate, promo: &Promo, view: View) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        REMIND_BUTTON_ID,
+        day.format("%Y-%m-%d"),
+        promo,
+        view
+    )
+}
+
+fn parse_remind_button_id(custom_id: &str) -> Option<(NaiveDate, Promo, View)> {
+    let split = custom_id.split(':').collect::<Vec<&str>>();
+    if split.len() != 4 || split[0] != REMIND_BUTTON_ID {
+        return None;
+    }
+
+    let day = NaiveDate::parse_from_str(split[1], "%Y-%m-%d").ok()?;
+    Some((day, split[2].parse().ok()?, split[3].parse().ok()?))
+}
+
+/// Handles the 🔔 button and the menu it opens, returns false when the component is something
+/// else
+pub async fn handle_component(
+    ctx: &serenity::Context,
+    db: &Database,
+    component: &MessageComponentInteraction,
+) -> Result<bool, Error> {
+    if component.data.custom_id == REMIND_SELECT_ID {
+        schedule_reminders(ctx, db, component).await?;
+        return Ok(true);
+    }
+    let Some((day, promo, view)) = parse_remind_button_id(&component.data.custom_id) else {
+        return Ok(false);
+    };
+
+    let (start, end) = view_range(view, day);
+    let events = get_sorted_events_range(start, end).await?;
+    let now = Utc::now();
+    let upcoming: Vec<&Event> = events
+        .get(&promo)
+        .into_iter()
+        .flatten()
+        // the reminders follow the ADE feed, custom and secondary events would be dropped
+        .filter(|e| e.is_from_ade() && !e.cancelled && e.start > now)
+        .take(MAX_OPTIONS)
+        .collect();
+
+    component
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    if upcoming.is_empty() {
+                        return d.content("Aucun cours à venir à rappeler.").ephemeral(true);
+                    }
+
+                    d.content(format!(
+                        "🔔 Choisis les cours à te rappeler en MP {} minutes avant",
+                        REMIND_MINUTES
+                    ))
+                    .ephemeral(true)
+                    .components(|c| {
+                        c.create_action_row(|row| {
+                            row.create_select_menu(|m| {
+                                m.custom_id(REMIND_SELECT_ID)
+                                    .placeholder("Cours à rappeler")
+                                    .min_values(1)
+                                    .max_values(upcoming.len() as u64)
+                                    .options(|o| {
+                                        for evt in &upcoming {
+                                            o.create_option(|opt| {
+                                                opt.label(
+                                                    config::get()
+                                                        .subject_label(evt)
+                                                        .chars()
+                                                        .take(100)
+                                                        .collect::<String>(),
+                                                )
+                                                .value(&evt.uid)
+                                                .description(evt.start.format("%d/%m %H:%M"))
+                                            });
+                                        }
+                                        o
+                                    })
+                            })
+                        })
+                    })
+                })
+        })
+        .await?;
+
+    Ok(true)
+}
+
+async fn schedule_reminders(
+    ctx: &serenity::Context,
+    db: &Database,
+    component: &MessageComponentInteraction,
+) -> Result<(), Error> {
+    let events = get_events().await?;
+    let mut scheduled = 0;
+    for uid in &component.data.values {
+        if !events.iter().any(|e| &e.uid == uid) {
+            continue;
+        }
+        db.add_class_reminder(component.user.id, uid, component.guild_id)?;
+        scheduled += 1;
+    }
+
+    let content = match scheduled {
+        0 => "Ces cours n'existent plus.".to_string(),
+        1 => "🔔 Rappel programmé".to_string(),
+        n => format!("🔔 {} rappels programmés", n),
+    };
+    component
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(content).components(|c| c))
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// DMs the reminders that are due. They follow the class when it moves and are dropped when it
+/// is cancelled or disappears.
+pub async fn send_class_reminders(ctx: &serenity::Context, db: &Database, events: &[Event]) {
+    let reminders = match db.get_class_reminders() {
+        Ok(reminders) => reminders,
+        Err(err) => {
+            println!("Failed to get class reminders: {}", err);
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    let hierarchy = GroupHierarchy::from_events(events);
+    for (user, uid, guild) in reminders {
+        let evt = events.iter().find(|e| e.uid == uid);
+        if evt.is_some_and(|e| {
+            !e.cancelled && e.start - chrono::Duration::minutes(REMIND_MINUTES) > now
+        }) {
+            continue;
+        }
+
+        if let Some(evt) = evt.filter(|e| !e.cancelled && e.start > now) {
+            let (name, value) = make_event_field(evt, &EmbedOptions::default());
+            // reminders scheduled from a DM have no server to take the homework from
+            let homework = guild
+                .map(|guild| homework_for_class(db, guild, &hierarchy, evt))
+                .transpose()
+                .unwrap_or_else(|err| {
+                    println!("Failed to get the homework of the class: {}", err);
+                    None
+                })
+                .unwrap_or_default();
+            let res = match user.create_dm_channel(ctx).await {
+                Ok(dm) => dm
+                    .send_message(ctx, |m| {
+                        m.content(format!("🔔 Cours dans {} minutes", REMIND_MINUTES))
+                            .embed(|e| {
+                                e.field(name, value, false);
+                                if !homework.is_empty() {
+                                    e.field(
+                                        "📚 Devoirs à rendre",
+                                        homework_lines(&homework, false),
+                                        false,
+                                    );
+                                }
+                                e
+                            })
+                    })
+                    .await
+                    .map(|_| ()),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = res {
+                println!("Failed to send class reminder to {}: {}", user, err);
+            }
+        }
+        if let Err(err) = db.remove_class_reminder(user, &uid) {
+            println!("Failed to remove class reminder: {}", err);
+        }
+    }
+}
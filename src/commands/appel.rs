@@ -0,0 +1,188 @@
+use chrono::{DateTime, Local};
+use poise::{
+    serenity_prelude::{AttachmentType, Channel},
+    AutocompleteChoice,
+};
+
+use crate::{
+    attendance::CHECK_IN_MINUTES, calendar::parse_promo_name, db::AttendanceSession, Context, Error,
+};
+
+/// Sessions offered by the autocompletion of /appel export
+const AUTOCOMPLETE_SESSIONS: usize = 25;
+
+/// "R1.01 Dev — 1-INFO-32 — 10/10 08:00"
+fn session_label(session: &AttendanceSession) -> String {
+    let start = DateTime::from_timestamp(session.starts_at, 0)
+        .unwrap_or_default()
+        .with_timezone(&Local);
+    format!(
+        "{} — {} — {}",
+        session.lesson,
+        session.promo,
+        start.format("%d/%m %H:%M")
+    )
+    .chars()
+    .take(100)
+    .collect()
+}
+
+async fn autocomplete_session<'a>(
+    ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = AutocompleteChoice<String>> + 'a {
+    let sessions = match ctx.guild_id() {
+        Some(guild) => ctx
+            .data()
+            .db
+            .get_attendance_sessions(guild, AUTOCOMPLETE_SESSIONS)
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let partial = partial.to_lowercase();
+
+    sessions
+        .into_iter()
+        .filter(move |s| session_label(s).to_lowercase().contains(&partial))
+        .map(|s| AutocompleteChoice {
+            name: session_label(&s),
+            value: s.id.to_string(),
+        })
+}
+
+/// Bouton de présence au début des cours, avec export de la liste
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS",
+    subcommands("activer", "desactiver", "export")
+)]
+pub async fn appel(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Publie un bouton de présence au début de chaque cours d'un groupe
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+pub async fn activer(
+    ctx: Context<'_>,
+    #[description = "Groupe (ex: 1-INFO-32)"] groupe: String,
+    #[description = "Salon du bouton, celui-ci par défaut"] salon: Option<Channel>,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let Some(promo) = parse_promo_name(&groupe) else {
+        ctx.say(format!("Groupe invalide: {}", groupe)).await?;
+        return Ok(());
+    };
+    let channel = salon.map(|c| c.id()).unwrap_or(ctx.channel_id());
+
+    ctx.data()
+        .db
+        .set_attendance_promo(guild, &promo, Some(channel))?;
+    ctx.send(|m| {
+        m.content(format!(
+            "Un bouton de présence sera publié dans <#{}> au début de chaque cours de {}, \
+             ouvert pendant {} minutes.",
+            channel, promo, CHECK_IN_MINUTES
+        ))
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// N'ouvre plus l'appel pour un groupe
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+pub async fn desactiver(
+    ctx: Context<'_>,
+    #[description = "Groupe (ex: 1-INFO-32)"] groupe: String,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let Some(promo) = parse_promo_name(&groupe) else {
+        ctx.say(format!("Groupe invalide: {}", groupe)).await?;
+        return Ok(());
+    };
+
+    ctx.data().db.set_attendance_promo(guild, &promo, None)?;
+    ctx.send(|m| {
+        m.content(format!("L'appel ne sera plus ouvert pour {}.", promo))
+            .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Exporte la liste des présents d'un cours en CSV
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+pub async fn export(
+    ctx: Context<'_>,
+    #[description = "Cours"]
+    #[autocomplete = "autocomplete_session"]
+    session: String,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let session = session
+        .parse::<i64>()
+        .ok()
+        .and_then(|id| ctx.data().db.get_attendance_session(guild, id).transpose())
+        .transpose()?;
+    let Some(session) = session else {
+        ctx.send(|m| m.content("Session inconnue.").ephemeral(true))
+            .await?;
+        return Ok(());
+    };
+
+    // attachments can't be sent in the initial response of a slash command
+    ctx.defer_ephemeral().await?;
+    let attendance = ctx.data().db.get_attendance(session.id)?;
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["user_id", "name", "checked_at"])?;
+    for (user, checked_at) in &attendance {
+        let name = match user.to_user(ctx).await {
+            Ok(user) => user.tag(),
+            Err(_) => String::new(),
+        };
+        let checked_at = DateTime::from_timestamp(*checked_at, 0)
+            .unwrap_or_default()
+            .with_timezone(&Local);
+        writer.write_record([
+            user.to_string(),
+            name,
+            checked_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        ])?;
+    }
+
+    let data = writer.into_inner()?;
+    ctx.send(|m| {
+        m.content(format!(
+            "{} présents — {}",
+            attendance.len(),
+            session_label(&session)
+        ))
+        .attachment(AttachmentType::Bytes {
+            data: data.into(),
+            filename: format!("appel-{}.csv", session.id),
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::Colour;
+
+use crate::{
+    calendar::{fetch_stats, RECENT_ERRORS_HOURS},
+    Context, Error,
+};
+
+/// "2j 3h 12min"
+fn format_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes();
+    let (days, hours, minutes) = (minutes / (60 * 24), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}j {}h {}min", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}min", hours, minutes)
+    } else {
+        format!("{}min", minutes)
+    }
+}
+
+fn format_ago(time: Option<DateTime<Utc>>) -> String {
+    match time {
+        Some(time) => format!("il y a {}", format_duration(Utc::now() - time)),
+        None => "jamais".to_string(),
+    }
+}
+
+/// Affiche l'état du bot (disponibilité, emploi du temps en cache, erreurs récentes)
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn botstats(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+    let stats = fetch_stats().await;
+    let uptime = chrono::Duration::from_std(data.started_at.elapsed()).unwrap_or_default();
+    let command_errors = data
+        .command_errors
+        .lock()
+        .expect("Failed to lock mutex!")
+        .iter()
+        .filter(|t| Utc::now() - **t < chrono::Duration::hours(RECENT_ERRORS_HOURS))
+        .count();
+    let db_size = data.db.size()?;
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title("État du bot")
+                .color(
+                    if stats.recent_errors + command_errors == 0 && stats.last_success.is_some() {
+                        Colour::DARK_GREEN
+                    } else {
+                        Colour::ORANGE
+                    },
+                )
+                .field("Disponible depuis", format_duration(uptime), true)
+                .field(
+                    "Dernière récupération d'ADE",
+                    format_ago(stats.last_success),
+                    true,
+                )
+                .field("Âge du cache", format_ago(stats.cached_at), true)
+                .field(
+                    "En cache",
+                    format!(
+                        "{} cours, {} groupes",
+                        stats.cached_events, stats.cached_groups
+                    ),
+                    true,
+                )
+                .field(
+                    "Base de données",
+                    format!("{:.1} Ko", db_size as f64 / 1024.0),
+                    true,
+                )
+                .field(
+                    "Erreurs (24h)",
+                    format!(
+                        "{} téléchargements, {} commandes",
+                        stats.recent_errors, command_errors
+                    ),
+                    true,
+                )
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
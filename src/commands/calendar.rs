@@ -0,0 +1,172 @@
+use agenda_bot::dates::parse_date;
+use chrono::Local;
+use poise::serenity_prelude::Attachment;
+
+use super::autocomplete_date;
+use crate::{
+    calendar::{set_uploaded_calendars, UploadedCalendar},
+    Context, Error,
+};
+
+/// Discord allows bigger files, an ADE export of a whole semester stays well under this
+const MAX_UPLOAD_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Remplace ou complète temporairement l'emploi du temps ADE
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS",
+    subcommands("upload", "liste", "retirer")
+)]
+pub async fn calendar(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Utilise un fichier .ics à la place d'ADE (ou en plus) pendant quelques jours
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+pub async fn upload(
+    ctx: Context<'_>,
+    #[description = "Fichier .ics"] fichier: Attachment,
+    #[description = "Premier jour concerné (ex: lundi, 14/11)"]
+    #[autocomplete = "autocomplete_date"]
+    debut: String,
+    #[description = "Dernier jour concerné (ex: vendredi, 18/11)"]
+    #[autocomplete = "autocomplete_date"]
+    fin: String,
+    #[description = "Remplacer les cours d'ADE sur ces jours (oui par défaut)"] remplacer: Option<
+        bool,
+    >,
+) -> Result<(), Error> {
+    let today = Local::now().date_naive();
+    let (Some(start), Some(end)) = (parse_date(&debut, today), parse_date(&fin, today)) else {
+        ctx.send(|m| {
+            m.content("Date invalide (ex: demain, lundi prochain, 14/11).")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    };
+    if end < start {
+        ctx.send(|m| {
+            m.content("Le dernier jour doit être après le premier.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    }
+    if !fichier.filename.to_lowercase().ends_with(".ics") || fichier.size > MAX_UPLOAD_SIZE {
+        ctx.send(|m| {
+            m.content("Le fichier doit être un .ics de moins de 5 Mo.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    }
+
+    ctx.defer_ephemeral().await?;
+    let body = String::from_utf8_lossy(&fichier.download().await?).into_owned();
+    let mut calendar = UploadedCalendar {
+        id: 0,
+        name: fichier.filename.clone(),
+        start,
+        end,
+        replace: remplacer.unwrap_or(true),
+        body,
+    };
+    let count = match calendar.events() {
+        Ok(events) if !events.is_empty() => events.len(),
+        Ok(_) => {
+            ctx.say("Aucun cours lisible dans ce fichier sur ces jours.")
+                .await?;
+            return Ok(());
+        }
+        Err(err) => {
+            ctx.say(format!("Fichier illisible: {}", err)).await?;
+            return Ok(());
+        }
+    };
+
+    let db = &ctx.data().db;
+    calendar.id = db.add_uploaded_calendar(&calendar)?;
+    set_uploaded_calendars(&db.get_uploaded_calendars()?);
+
+    ctx.say(format!(
+        "{} cours de « {} » {} ADE du {} au {} (n°{}).",
+        count,
+        calendar.name,
+        if calendar.replace {
+            "remplacent"
+        } else {
+            "complètent"
+        },
+        start.format("%d/%m/%Y"),
+        end.format("%d/%m/%Y"),
+        calendar.id
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Liste les fichiers .ics en cours d'utilisation
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+pub async fn liste(ctx: Context<'_>) -> Result<(), Error> {
+    let calendars = ctx.data().db.get_uploaded_calendars()?;
+    let content = if calendars.is_empty() {
+        "Aucun fichier .ics en cours d'utilisation.".to_string()
+    } else {
+        calendars
+            .iter()
+            .map(|c| {
+                format!(
+                    "n°{} « {} » du {} au {}{}",
+                    c.id,
+                    c.name,
+                    c.start.format("%d/%m/%Y"),
+                    c.end.format("%d/%m/%Y"),
+                    if c.replace { "" } else { " (en plus d'ADE)" }
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    ctx.send(|m| m.content(content).ephemeral(true)).await?;
+
+    Ok(())
+}
+
+/// Arrête d'utiliser un fichier .ics
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+pub async fn retirer(
+    ctx: Context<'_>,
+    #[description = "Numéro du fichier (voir /calendar liste)"] numero: i64,
+) -> Result<(), Error> {
+    let db = &ctx.data().db;
+    let content = if db.remove_uploaded_calendar(numero)? {
+        set_uploaded_calendars(&db.get_uploaded_calendars()?);
+        format!("Fichier n°{} retiré, ADE est de nouveau utilisé.", numero)
+    } else {
+        format!("Aucun fichier n°{}.", numero)
+    };
+
+    ctx.send(|m| m.content(content).ephemeral(true)).await?;
+
+    Ok(())
+}
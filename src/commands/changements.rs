@@ -0,0 +1,105 @@
+use chrono::{Local, Utc};
+use poise::serenity_prelude::{self as serenity, Colour};
+
+use super::get_user_promo;
+use crate::{
+    calendar::{get_events, parse_promo_name, sort_events, Event},
+    diff::{diff_events, Change},
+    Context, Error,
+};
+
+const DEFAULT_DAYS: u32 = 7;
+/// Snapshots are only kept this long
+const MAX_DAYS: u32 = 35;
+/// Changes are looked for in the classes of the next two months
+const HORIZON_DAYS: i64 = 60;
+/// Keeps the list within the 4096 characters of an embed description
+const MAX_CHANGES: usize = 25;
+
+/// Event a change is sorted by, where it happens now
+fn changed_event(change: &Change) -> &Event {
+    match change {
+        Change::Added(evt) | Change::Removed(evt) => evt,
+        Change::Moved { after, .. } | Change::RoomChanged { after, .. } => after,
+    }
+}
+
+/// Ce qui a changé dans l'emploi du temps d'un groupe depuis quelques jours
+#[poise::command(slash_command, prefix_command)]
+pub async fn changements(
+    ctx: Context<'_>,
+    #[description = "Numéro du group (ex: 32)"] group: Option<String>,
+    #[description = "Utilisateur"] member: Option<serenity::Member>,
+    #[description = "Depuis combien de jours (7 par défaut, 35 au plus)"] jours: Option<u32>,
+) -> Result<(), Error> {
+    let promo = if let Some(member) = member {
+        get_user_promo(ctx, member.user.id, Some(member))?
+    } else if let Some(group) = group {
+        parse_promo_name(&group)
+    } else {
+        let member = ctx.author_member().await.map(|m| m.into_owned());
+        get_user_promo(ctx, ctx.author().id, member)?
+    };
+    let Some(promo) = promo else {
+        ctx.say("Could not find group for user! Use /setgroup to save a default group.")
+            .await?;
+        return Ok(());
+    };
+
+    let days = jours.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS);
+    let since = Utc::now() - chrono::Duration::days(days as i64);
+    let Some((taken_at, before)) = ctx.data().db.get_snapshot(since)? else {
+        ctx.say("Aucun historique de l'emploi du temps pour l'instant, réessayez demain")
+            .await?;
+        return Ok(());
+    };
+
+    ctx.defer().await?;
+    // cancelled classes show up as removed, secondary feeds aren't part of the snapshots
+    let after: Vec<Event> = get_events()
+        .await?
+        .into_iter()
+        .filter(|e| !e.cancelled && e.category.is_none())
+        .collect();
+    let today = Local::now().date_naive();
+    let end = today + chrono::Duration::days(HORIZON_DAYS);
+    let before = sort_events(&before, today, end)
+        .remove(&promo)
+        .unwrap_or_default();
+    let after = sort_events(&after, today, end)
+        .remove(&promo)
+        .unwrap_or_default();
+
+    let mut changes = diff_events(&before, &after);
+    changes.sort_by_key(|c| changed_event(c).start);
+    let mut lines: Vec<String> = changes
+        .iter()
+        .take(MAX_CHANGES)
+        .map(|c| c.to_string())
+        .collect();
+    if changes.len() > MAX_CHANGES {
+        lines.push(format!("… et {} autres", changes.len() - MAX_CHANGES));
+    }
+    let description = if lines.is_empty() {
+        "Aucun changement".to_string()
+    } else {
+        lines.join("\n")
+    };
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title(format!("Changements — {}", promo))
+                .description(description)
+                .footer(|f| {
+                    f.text(format!(
+                        "Depuis le {}",
+                        taken_at.with_timezone(&Local).format("%d/%m à %H:%M")
+                    ))
+                })
+                .color(Colour::ORANGE)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,390 @@
+use agenda_bot::dates::parse_time;
+use poise::serenity_prelude::{Channel, ChannelType};
+
+use super::autocomplete_campus;
+use crate::{
+    calendar::{parse_promo_name, Promo},
+    db::AnnouncementCleanup,
+    Context, Error,
+};
+
+/// Announcements are kept a week when /config annonces doesn't say otherwise
+const DEFAULT_RETENTION_DAYS: u32 = 7;
+
+/// Configure le bot pour ce serveur
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands(
+        "trous",
+        "evenements",
+        "vocal",
+        "campus",
+        "mentions",
+        "forum",
+        "annonces",
+        "heure",
+        "sans_cours"
+    )
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Affiche les trous entre les cours dans l'emploi du temps
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn trous(
+    ctx: Context<'_>,
+    #[description = "Afficher les trous"] actif: bool,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let mut settings = db.get_guild_settings(guild)?;
+    settings.show_gaps = actif;
+    db.set_guild_settings(guild, &settings)?;
+
+    ctx.send(|m| {
+        m.content(if actif {
+            "Les trous seront affichés dans l'emploi du temps."
+        } else {
+            "Les trous ne seront plus affichés dans l'emploi du temps."
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Crée les cours du lendemain en événements Discord pour les groupes choisis
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn evenements(
+    ctx: Context<'_>,
+    #[description = "Groupes séparés par des virgules (ex: 1-INFO-S1, 2-INFO-3), vide pour désactiver"]
+    groupes: Option<String>,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let mut promos: Vec<Promo> = Vec::new();
+    for group in groupes.iter().flat_map(|g| g.split(',')).map(|g| g.trim()) {
+        if group.is_empty() {
+            continue;
+        }
+        let Some(promo) = parse_promo_name(group) else {
+            ctx.send(|m| {
+                m.content(format!("Groupe invalide: {}", group))
+                    .ephemeral(true)
+            })
+            .await?;
+            return Ok(());
+        };
+        promos.push(promo);
+    }
+
+    let mut settings = db.get_guild_settings(guild)?;
+    settings.scheduled_event_promos = promos;
+    db.set_guild_settings(guild, &settings)?;
+
+    ctx.send(|m| {
+        m.content(if settings.scheduled_event_promos.is_empty() {
+            "Les cours ne seront plus créés en événements Discord.".to_string()
+        } else {
+            format!(
+                "Les cours de {} seront créés en événements Discord la veille \
+                 (le bot a besoin de la permission Gérer les événements).",
+                settings
+                    .scheduled_event_promos
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Crée un salon vocal par TP/TD en cours, supprimé à la fin du cours
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn vocal(
+    ctx: Context<'_>,
+    #[description = "Catégorie où créer les salons, vide pour désactiver"] categorie: Option<
+        Channel,
+    >,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let category = match categorie {
+        Some(Channel::Category(category)) if category.kind == ChannelType::Category => {
+            Some(category.id)
+        }
+        Some(_) => {
+            ctx.send(|m| {
+                m.content("Ce salon n'est pas une catégorie.")
+                    .ephemeral(true)
+            })
+            .await?;
+            return Ok(());
+        }
+        None => None,
+    };
+
+    let mut settings = db.get_guild_settings(guild)?;
+    settings.voice_category = category;
+    db.set_guild_settings(guild, &settings)?;
+
+    ctx.send(|m| {
+        m.content(match category {
+            Some(category) => format!(
+                "Un salon vocal sera créé dans <#{}> pour chaque TP/TD en cours.",
+                category
+            ),
+            None => "Les salons vocaux des cours ne seront plus créés.".to_string(),
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// N'affiche que les cours d'un campus dans ce serveur
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn campus(
+    ctx: Context<'_>,
+    #[description = "Campus, vide pour tout afficher"]
+    #[autocomplete = "autocomplete_campus"]
+    nom: Option<String>,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let campus = match nom {
+        Some(nom) => match crate::config::campus(&nom) {
+            Some(campus) => Some(campus.name),
+            None => {
+                ctx.send(|m| {
+                    m.content(format!("Campus inconnu: {}", nom))
+                        .ephemeral(true)
+                })
+                .await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let mut settings = db.get_guild_settings(guild)?;
+    settings.campus = campus;
+    db.set_guild_settings(guild, &settings)?;
+
+    ctx.send(|m| {
+        m.content(match &settings.campus {
+            Some(campus) => format!("Seuls les cours de {} seront affichés.", campus),
+            None => "Les cours de tous les campus seront affichés.".to_string(),
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Mentionne le rôle du groupe dans les annonces quotidiennes
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn mentions(
+    ctx: Context<'_>,
+    #[description = "Mentionner les rôles"] actif: bool,
+    #[description = "Début des heures silencieuses (ex: 22)"] silence_debut: Option<u32>,
+    #[description = "Fin des heures silencieuses (ex: 8)"] silence_fin: Option<u32>,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let quiet_hours = match (silence_debut, silence_fin) {
+        (Some(start), Some(end)) if start < 24 && end < 24 => Some((start, end)),
+        (None, None) => None,
+        _ => {
+            ctx.send(|m| {
+                m.content("Indiquez le début et la fin des heures silencieuses, entre 0 et 23.")
+                    .ephemeral(true)
+            })
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let mut settings = db.get_guild_settings(guild)?;
+    settings.announcement_pings = actif;
+    settings.quiet_hours = quiet_hours;
+    db.set_guild_settings(guild, &settings)?;
+
+    ctx.send(|m| {
+        m.content(match (actif, quiet_hours) {
+            (false, _) => "Les annonces ne mentionneront plus les rôles.".to_string(),
+            (true, None) => "Les annonces mentionneront le rôle de chaque groupe.".to_string(),
+            (true, Some((start, end))) => format!(
+                "Les annonces mentionneront le rôle de chaque groupe, sauf entre {}h et {}h.",
+                start, end
+            ),
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Publie les annonces quotidiennes dans un forum, avec un post par groupe
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn forum(
+    ctx: Context<'_>,
+    #[description = "Forum des annonces, vide pour revenir au salon d'annonces"] salon: Option<
+        Channel,
+    >,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let forum = match salon {
+        Some(Channel::Guild(forum)) if forum.kind == ChannelType::Forum => Some(forum.id),
+        Some(_) => {
+            ctx.send(|m| m.content("Ce salon n'est pas un forum.").ephemeral(true))
+                .await?;
+            return Ok(());
+        }
+        None => None,
+    };
+
+    let mut settings = db.get_guild_settings(guild)?;
+    settings.announcement_forum = forum;
+    db.set_guild_settings(guild, &settings)?;
+
+    ctx.send(|m| {
+        m.content(match forum {
+            Some(forum) => format!(
+                "Les annonces seront publiées dans <#{}>, avec un post par groupe.",
+                forum
+            ),
+            None => "Les annonces seront publiées dans le salon d'annonces.".to_string(),
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Modifie ou supprime les annonces des jours précédents
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn annonces(
+    ctx: Context<'_>,
+    #[description = "garder, modifier l'annonce de la veille ou supprimer les anciennes"]
+    mode: AnnouncementCleanup,
+    #[description = "Jours de conservation avec supprimer (0: seulement aujourd'hui)"]
+    jours: Option<u32>,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let mut settings = db.get_guild_settings(guild)?;
+    settings.announcement_cleanup = mode;
+    settings.announcement_retention_days = jours.unwrap_or(DEFAULT_RETENTION_DAYS);
+    db.set_guild_settings(guild, &settings)?;
+
+    ctx.send(|m| {
+        m.content(match mode {
+            AnnouncementCleanup::Keep => "Les annonces précédentes seront conservées.".to_string(),
+            AnnouncementCleanup::Edit if settings.announcement_pings => {
+                "Une modification ne notifie personne : avec les mentions activées, l'annonce de \
+                 la veille sera supprimée et remplacée par une nouvelle."
+                    .to_string()
+            }
+            AnnouncementCleanup::Edit => {
+                "L'annonce de la veille sera modifiée au lieu d'en publier une nouvelle."
+                    .to_string()
+            }
+            AnnouncementCleanup::Delete => format!(
+                "Les annonces de plus de {} jours seront supprimées.",
+                settings.announcement_retention_days
+            ),
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Choisit l'heure des annonces quotidiennes
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn heure(
+    ctx: Context<'_>,
+    #[description = "Heure des annonces (ex: 7h30), vide pour celle par défaut"] heure: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let time = match heure.as_deref().map(parse_time) {
+        None => None,
+        Some(Some(time)) => Some(time),
+        Some(None) => {
+            ctx.send(|m| m.content("Heure invalide (ex: 7h30).").ephemeral(true))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let mut settings = db.get_guild_settings(guild)?;
+    settings.announcement_time = time;
+    db.set_guild_settings(guild, &settings)?;
+
+    ctx.send(|m| {
+        m.content(format!(
+            "Les annonces seront publiées chaque jour à {}.",
+            time.unwrap_or_else(|| crate::config::get().announcement_time())
+                .format("%Hh%M")
+        ))
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Annonce aussi les groupes du serveur qui n'ont pas cours
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn sans_cours(
+    ctx: Context<'_>,
+    #[description = "Publier \"Pas de cours\" pour les groupes sans cours"] actif: bool,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let db = &ctx.data().db;
+
+    let mut settings = db.get_guild_settings(guild)?;
+    settings.announce_empty_days = actif;
+    db.set_guild_settings(guild, &settings)?;
+
+    ctx.send(|m| {
+        m.content(if actif {
+            "Les groupes sans cours seront annoncés avec \"Pas de cours aujourd'hui\", sauf le \
+             week-end et pendant les vacances."
+        } else {
+            "Les groupes sans cours ne seront plus annoncés."
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use chrono::Local;
+use poise::serenity_prelude::Colour;
+
+use super::{autocomplete_date, get_user_promo, parse_date_option, parse_group_option};
+use crate::{
+    calendar::{get_events, GroupHierarchy, Promo},
+    db::Homework,
+    homework::{homework_lines, upcoming_homework},
+    Context, Error,
+};
+
+/// Devoirs à rendre par groupe, ajoutés par les délégués
+#[poise::command(slash_command, prefix_command, subcommands("liste", "add", "retirer"))]
+pub async fn devoirs(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// The group given as argument or the one of the author, replies when there is none
+async fn homework_promo(ctx: Context<'_>, groupe: Option<String>) -> Result<Option<Promo>, Error> {
+    if let Some(groupe) = groupe {
+        return parse_group_option(ctx, &groupe).await;
+    }
+
+    let member = ctx.author_member().await.map(|m| m.into_owned());
+    let promo = get_user_promo(ctx, ctx.author().id, member)?;
+    if promo.is_none() {
+        ctx.send(|m| {
+            m.content("Could not find group for user! Use /setgroup to save a default group.")
+                .ephemeral(true)
+        })
+        .await?;
+    }
+
+    Ok(promo)
+}
+
+/// Liste les devoirs à rendre d'un groupe
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn liste(
+    ctx: Context<'_>,
+    #[description = "Groupe (ex: 1-INFO-32)"] groupe: Option<String>,
+) -> Result<(), Error> {
+    let Some(promo) = homework_promo(ctx, groupe).await? else {
+        return Ok(());
+    };
+
+    let guild = ctx.guild_id().expect("guild_only command");
+    let hierarchy = GroupHierarchy::from_events(&get_events().await?);
+    let homework = upcoming_homework(
+        &ctx.data().db,
+        guild,
+        &hierarchy,
+        &promo,
+        Local::now().date_naive(),
+    )?;
+    let description = if homework.is_empty() {
+        format!("Aucun devoir à rendre pour {}", promo)
+    } else {
+        homework_lines(&homework, true)
+    };
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title(format!("Devoirs: {}", promo))
+                .description(description)
+                .color(Colour::GOLD)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Ajoute un devoir, rappelé dans l'annonce du jour et les rappels de cours
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "Matière (ex: R3.04)"] matiere: String,
+    #[description = "À rendre le (ex: lundi, 14/11)"]
+    #[autocomplete = "autocomplete_date"]
+    date: String,
+    #[description = "Consigne (ex: TP2 à déposer sur Moodle)"] description: String,
+    #[description = "Groupe (ex: 1-INFO-32), le tien par défaut"] groupe: Option<String>,
+) -> Result<(), Error> {
+    let Some(due) = parse_date_option(ctx, Some(date)).await? else {
+        return Ok(());
+    };
+    if due < Local::now().date_naive() {
+        ctx.send(|m| {
+            m.content("La date de rendu est déjà passée.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    }
+    let Some(promo) = homework_promo(ctx, groupe).await? else {
+        return Ok(());
+    };
+
+    let mut homework = Homework {
+        id: 0,
+        guild: ctx.guild_id().expect("guild_only command"),
+        promo,
+        subject: matiere,
+        due,
+        description,
+        author: ctx.author().id,
+    };
+    homework.id = ctx.data().db.add_homework(&homework)?;
+
+    ctx.say(format!(
+        "📚 Devoir ajouté pour {} (n°{}), à rendre le {} :\n{}",
+        homework.promo,
+        homework.id,
+        homework.due.format("%d/%m/%Y"),
+        homework_lines(&[homework.clone()], false)
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Retire un devoir
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn retirer(
+    ctx: Context<'_>,
+    #[description = "Numéro du devoir (voir /devoirs liste)"] numero: i64,
+) -> Result<(), Error> {
+    let guild = ctx.guild_id().expect("guild_only command");
+    let content = if ctx.data().db.remove_homework(guild, numero)? {
+        format!("Devoir n°{} retiré.", numero)
+    } else {
+        format!("Aucun devoir n°{}.", numero)
+    };
+
+    ctx.send(|m| m.content(content).ephemeral(true)).await?;
+
+    Ok(())
+}
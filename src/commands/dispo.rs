@@ -0,0 +1,109 @@
+use agenda_bot::{availability::free_slots, dates::short_label};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Europe::Paris;
+use poise::serenity_prelude::Colour;
+
+use super::{autocomplete_date, parse_date_option};
+use crate::{
+    calendar::get_teacher_events,
+    config::{self, Teacher},
+    Context, Error,
+};
+
+/// Working days shown, starting from the requested one
+const DAYS: usize = 5;
+const WORK_START: (u32, u32) = (8, 0);
+const WORK_END: (u32, u32) = (18, 0);
+/// Shorter gaps are too short for a meeting
+const MIN_SLOT_MINUTES: i64 = 30;
+
+async fn autocomplete_teacher<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    config::get()
+        .teachers
+        .iter()
+        .map(|t| t.name.clone())
+        .filter(move |t| t.to_lowercase().contains(&partial.to_lowercase()))
+        .collect::<Vec<String>>()
+        .into_iter()
+}
+
+/// The next `DAYS` days from `date`, without weekends
+fn working_days(date: NaiveDate) -> Vec<NaiveDate> {
+    date.iter_days()
+        .filter(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun))
+        .take(DAYS)
+        .collect()
+}
+
+/// Créneaux libres d'un enseignant sur ses heures de travail
+#[poise::command(slash_command, prefix_command)]
+pub async fn dispo(
+    ctx: Context<'_>,
+    #[description = "Enseignant"]
+    #[autocomplete = "autocomplete_teacher"]
+    prof: String,
+    #[description = "Premier jour (ex: demain, lundi prochain, 14/11)"]
+    #[autocomplete = "autocomplete_date"]
+    date: Option<String>,
+) -> Result<(), Error> {
+    let Some(Teacher { name, url }) = config::teacher(&prof) else {
+        ctx.send(|m| {
+            m.content(format!("Enseignant inconnu: {}", prof))
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    };
+    let Some(date) = parse_date_option(ctx, date).await? else {
+        return Ok(());
+    };
+
+    ctx.defer().await?;
+    let events = get_teacher_events(&url, &name).await?;
+    let now = Utc::now().with_timezone(&Paris);
+    let at = |day: NaiveDate, (hour, minute): (u32, u32)| {
+        let time = NaiveTime::from_hms_opt(hour, minute, 0).expect("Invalid working hours");
+        Paris.from_local_datetime(&day.and_time(time)).earliest()
+    };
+
+    let mut fields = Vec::new();
+    for day in working_days(date) {
+        let (Some(start), Some(end)) = (at(day, WORK_START), at(day, WORK_END)) else {
+            continue;
+        };
+        let slots = free_slots(
+            &events,
+            start.max(now),
+            end,
+            Duration::minutes(MIN_SLOT_MINUTES),
+        );
+        let value = if slots.is_empty() {
+            "Aucun créneau libre".to_string()
+        } else {
+            slots
+                .iter()
+                .map(|(start, end)| format!("{} - {}", start.format("%H:%M"), end.format("%H:%M")))
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+        fields.push((short_label(day), value, true));
+    }
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title(format!("Disponibilités — {}", name))
+                .description(format!(
+                    "Créneaux libres de {}h à {}h",
+                    WORK_START.0, WORK_END.0
+                ))
+                .fields(fields)
+                .color(Colour::DARK_GREEN)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
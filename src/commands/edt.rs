@@ -0,0 +1,162 @@
+use chrono::NaiveDate;
+use poise::serenity_prelude as serenity;
+
+use super::{
+    autocomplete_campus, autocomplete_date, get_user_promo, parse_date_option, parse_group_option,
+};
+use crate::{
+    calendar::Promo,
+    config,
+    embed::{make_timetable, make_timetable_components, EmbedOptions},
+    prefs::{Format, View},
+    state::TimetableMessage,
+    Context, Error,
+};
+
+/// Affiche l'emploie du temps d'un groupe ou d'un utilisateur
+#[poise::command(slash_command, prefix_command)]
+pub async fn edt(
+    ctx: Context<'_>,
+    #[description = "Utilisateur"] member: Option<serenity::Member>,
+    #[description = "Groupe (ex: 32, info32, 1-INFO-32)"] group: Option<String>,
+    #[description = "Vue (jour ou semaine)"] vue: Option<View>,
+    #[description = "Format (embed ou texte)"] format: Option<Format>,
+    #[description = "Jour (ex: demain, lundi prochain, 14/11)"]
+    #[autocomplete = "autocomplete_date"]
+    date: Option<String>,
+    #[description = "Campus à afficher (ex: Lannion, \"tous\" pour tout afficher)"]
+    #[autocomplete = "autocomplete_campus"]
+    campus: Option<String>,
+) -> Result<(), Error> {
+    let Some(date) = parse_date_option(ctx, date).await? else {
+        return Ok(());
+    };
+    let campus = match campus.as_deref() {
+        None => None,
+        Some("tous") => Some(None),
+        Some(name) => match config::campus(name) {
+            Some(campus) => Some(Some(campus.name)),
+            None => {
+                ctx.send(|m| {
+                    m.content(format!("Campus inconnu: {}", name))
+                        .ephemeral(true)
+                })
+                .await?;
+                return Ok(());
+            }
+        },
+    };
+
+    send_timetable(ctx, member, group, vue, format, date, campus).await
+}
+
+/// Replies with the timetable of the member, the group or the author, in this order. `campus`
+/// overrides the campus filter of the guild and the author, `Some(None)` shows every campus.
+pub async fn send_timetable(
+    ctx: Context<'_>,
+    member: Option<serenity::Member>,
+    group: Option<String>,
+    vue: Option<View>,
+    format: Option<Format>,
+    date: NaiveDate,
+    campus: Option<Option<String>>,
+) -> Result<(), Error> {
+    let prefs = ctx.data().db.get_user_prefs(ctx.author().id)?;
+    let _ = if prefs.ephemeral {
+        ctx.defer_ephemeral().await
+    } else {
+        ctx.defer().await
+    };
+
+    let view = vue.unwrap_or(prefs.view);
+    let format = format.unwrap_or(prefs.format);
+
+    let promo: Option<Promo> = if let Some(member) = member {
+        get_user_promo(ctx, member.user.id, Some(member))?
+    } else if let Some(group) = group {
+        let Some(promo) = parse_group_option(ctx, &group).await? else {
+            return Ok(());
+        };
+        Some(promo)
+    } else {
+        let member = ctx.author_member().await.map(|m| m.into_owned());
+        get_user_promo(ctx, ctx.author().id, member)?
+    };
+
+    if let Some(promo) = promo {
+        let options = EmbedOptions::for_guild(&ctx.data().db, ctx.guild_id())?;
+        let options = if prefs.ephemeral || ctx.guild_id().is_none() {
+            options.with_private_prefs(&prefs)
+        } else {
+            options.with_prefs(&prefs)
+        };
+        let options = match campus {
+            Some(campus) => EmbedOptions { campus, ..options },
+            None => options,
+        };
+        let options = EmbedOptions {
+            live_status: true,
+            ..options
+        };
+        ctx.data()
+            .state
+            .set_last_displayed(ctx.author().id, promo.clone(), date)
+            .await;
+
+        let (content, embeds) = make_timetable(view, format, promo.clone(), date, &options).await;
+        let reply = ctx
+            .send(|m| {
+                if !content.is_empty() {
+                    m.content(content);
+                }
+                m.embeds = embeds;
+                m.components(|c| {
+                    *c = make_timetable_components(date, &promo, view, format);
+                    c
+                })
+                .ephemeral(prefs.ephemeral)
+            })
+            .await?;
+
+        // ephemeral messages can't be reacted to, only the refresh button works on them
+        if prefs.ephemeral {
+            return Ok(());
+        }
+
+        if let Ok(msg) = reply.message().await {
+            ctx.data()
+                .state
+                .set_timetable(
+                    msg.id,
+                    TimetableMessage {
+                        date,
+                        promo,
+                        view,
+                        format,
+                        options,
+                    },
+                )
+                .await;
+            let _ = msg
+                .react(
+                    &ctx,
+                    serenity::model::channel::ReactionType::Unicode("⏪".to_string()),
+                )
+                .await;
+
+            let _ = msg
+                .react(
+                    &ctx,
+                    serenity::model::channel::ReactionType::Unicode("⏩".to_string()),
+                )
+                .await;
+        }
+    } else {
+        let _ = ctx
+            .say("Could not find group for user! Use /setgroup to save a default group.")
+            .await;
+        return Ok(());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Europe::Paris;
+
+use crate::{
+    calendar::{parse_promo_name, set_custom_events, CustomEvent},
+    embed::MAX_CONTENT_LENGTH,
+    Context, Error,
+};
+
+/// Événements ponctuels (club, tutorat, soirée BDE...) ajoutés à l'emploi du temps
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS",
+    subcommands("ajouter", "liste", "retirer")
+)]
+pub async fn evenement(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Ajoute un événement ponctuel (club, tutorat, soirée BDE...) à l'emploi du temps
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+pub async fn ajouter(
+    ctx: Context<'_>,
+    #[description = "Titre de l'événement"] titre: String,
+    #[description = "Jour (ex: 10/10/2023)"] date: String,
+    #[description = "Heure de début (ex: 18:00)"] debut: String,
+    #[description = "Heure de fin (ex: 20:00)"] fin: String,
+    #[description = "Groupes concernés, séparés par des virgules (ex: 1-INFO-S1, 2-INFO-3)"]
+    groupes: String,
+    #[description = "Lieu"] salle: Option<String>,
+) -> Result<(), Error> {
+    let (Ok(date), Ok(start), Ok(end)) = (
+        NaiveDate::parse_from_str(&date, "%d/%m/%Y"),
+        NaiveTime::parse_from_str(&debut, "%H:%M"),
+        NaiveTime::parse_from_str(&fin, "%H:%M"),
+    ) else {
+        ctx.send(|m| {
+            m.content("Date ou heure invalide, utilisez les formats JJ/MM/AAAA et HH:MM.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    };
+
+    let (Some(start), Some(end)) = (
+        Paris.from_local_datetime(&date.and_time(start)).earliest(),
+        Paris.from_local_datetime(&date.and_time(end)).earliest(),
+    ) else {
+        ctx.send(|m| {
+            m.content("Cette heure n'existe pas ce jour-là.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    };
+    if end <= start {
+        ctx.send(|m| {
+            m.content("L'événement doit finir après avoir commencé.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    }
+
+    let groups = groupes
+        .split(',')
+        .map(|g| g.trim().to_string())
+        .filter(|g| !g.is_empty())
+        .collect::<Vec<String>>();
+    if let Some(invalid) = groups.iter().find(|g| parse_promo_name(g).is_none()) {
+        ctx.send(|m| {
+            m.content(format!("Groupe invalide: {}", invalid))
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    }
+    if groups.is_empty() {
+        ctx.send(|m| m.content("Aucun groupe indiqué.").ephemeral(true))
+            .await?;
+        return Ok(());
+    }
+
+    let mut event = CustomEvent {
+        id: 0,
+        title: titre,
+        location: salle.unwrap_or_default(),
+        start,
+        end,
+        groups,
+    };
+    let db = &ctx.data().db;
+    event.id = db.add_custom_event(&event)?;
+    set_custom_events(&db.get_custom_events()?);
+
+    ctx.send(|m| {
+        m.content(format!(
+            "« {} » (n°{}) ajouté le {} de {} à {} pour {}.",
+            event.title,
+            event.id,
+            date.format("%d/%m/%Y"),
+            start.format("%H:%M"),
+            end.format("%H:%M"),
+            event.groups.join(", ")
+        ))
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Room kept at the end of the list for the "… et N autres" line
+const MORE_LINE_LENGTH: usize = 30;
+
+/// Lines of the events, the ones that don't fit in a message are counted at the end
+fn event_lines(events: &[CustomEvent]) -> String {
+    let mut lines = String::new();
+    for (i, e) in events.iter().enumerate() {
+        let line = format!(
+            "n°{} « {} » le {} de {} à {} pour {}",
+            e.id,
+            e.title,
+            e.start.format("%d/%m/%Y"),
+            e.start.format("%H:%M"),
+            e.end.format("%H:%M"),
+            e.groups.join(", ")
+        );
+        if lines.chars().count() + line.chars().count() + MORE_LINE_LENGTH > MAX_CONTENT_LENGTH {
+            lines.push_str(&format!("\n… et {} autres", events.len() - i));
+            break;
+        }
+        if !lines.is_empty() {
+            lines.push('\n');
+        }
+        lines.push_str(&line);
+    }
+
+    lines
+}
+
+/// Liste les événements ponctuels à venir
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+pub async fn liste(ctx: Context<'_>) -> Result<(), Error> {
+    let mut events = ctx.data().db.get_custom_events()?;
+    events.retain(|e| e.end > Utc::now());
+    events.sort_by_key(|e| e.start);
+    let content = if events.is_empty() {
+        "Aucun événement ponctuel à venir.".to_string()
+    } else {
+        event_lines(&events)
+    };
+
+    ctx.send(|m| m.content(content).ephemeral(true)).await?;
+
+    Ok(())
+}
+
+/// Retire un événement ponctuel de l'emploi du temps
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_EVENTS"
+)]
+pub async fn retirer(
+    ctx: Context<'_>,
+    #[description = "Numéro de l'événement (voir /evenement liste)"] numero: i64,
+) -> Result<(), Error> {
+    let db = &ctx.data().db;
+    let content = if db.remove_custom_event(numero)? {
+        set_custom_events(&db.get_custom_events()?);
+        format!("Événement n°{} retiré.", numero)
+    } else {
+        format!("Aucun événement n°{}.", numero)
+    };
+
+    ctx.send(|m| m.content(content).ephemeral(true)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use chrono::Datelike;
+use poise::serenity_prelude::Colour;
+
+use super::{autocomplete_date, get_user_promo, parse_date_option};
+use crate::{
+    calendar::{get_sorted_events_range, parse_promo_name},
+    config,
+    i18n::Language,
+    Context, Error,
+};
+
+/// The feed rarely goes further than the end of the semester
+const EXAMS_DAYS: i64 = 180;
+const MAX_EXAMS: usize = 15;
+
+/// Liste les prochaines évaluations d'un groupe
+#[poise::command(slash_command, prefix_command)]
+pub async fn exams(
+    ctx: Context<'_>,
+    #[description = "Numéro du group (ex: 32)"] group: Option<String>,
+    #[description = "À partir du (ex: lundi prochain, 14/11)"]
+    #[autocomplete = "autocomplete_date"]
+    date: Option<String>,
+) -> Result<(), Error> {
+    let Some(date) = parse_date_option(ctx, date).await? else {
+        return Ok(());
+    };
+
+    let promo = if let Some(group) = group {
+        parse_promo_name(&group)
+    } else {
+        let member = ctx.author_member().await.map(|m| m.into_owned());
+        get_user_promo(ctx, ctx.author().id, member)?
+    };
+    let Some(promo) = promo else {
+        ctx.say("Could not find group for user! Use /setgroup to save a default group.")
+            .await?;
+        return Ok(());
+    };
+
+    let events = get_sorted_events_range(date, date + chrono::Duration::days(EXAMS_DAYS)).await?;
+    let weekdays = Language::French.strings().weekdays;
+    let exams = events
+        .get(&promo)
+        .into_iter()
+        .flatten()
+        .filter(|e| !e.cancelled && config::get().is_exam(e))
+        .take(MAX_EXAMS)
+        .map(|e| {
+            let mut line = format!(
+                "📝 {} {} — {}",
+                weekdays[e.start.weekday().num_days_from_monday() as usize],
+                e.start.format("%d/%m %H:%M"),
+                config::get().subject_label(e)
+            );
+            if !e.location.is_empty() {
+                line.push_str(&format!(" ({})", e.location));
+            }
+            line
+        })
+        .collect::<Vec<String>>();
+
+    let description = if exams.is_empty() {
+        format!(
+            "Aucune évaluation prévue pour {} à partir du {}",
+            promo,
+            date.format("%d/%m/%Y")
+        )
+    } else {
+        exams.join("\n")
+    };
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title(format!("Évaluations: {}", promo))
+                .description(description)
+                .color(Colour::GOLD)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
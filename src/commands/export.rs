@@ -0,0 +1,103 @@
+use chrono::NaiveDate;
+use poise::serenity_prelude::AttachmentType;
+
+use super::get_user_promo;
+use crate::{
+    calendar::{get_sorted_events_range, parse_promo_name, Event},
+    Context, Error,
+};
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ExportFormat {
+    #[name = "csv"]
+    Csv,
+}
+
+fn make_csv(events: &[Event]) -> Result<Vec<u8>, Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["date", "start", "end", "subject", "type", "room", "teacher"])?;
+    for evt in events {
+        writer.write_record([
+            evt.start.format("%Y-%m-%d").to_string(),
+            evt.start.format("%H:%M").to_string(),
+            evt.end.format("%H:%M").to_string(),
+            evt.lesson.clone(),
+            format!("{:?}", evt.event_type),
+            evt.location.clone(),
+            evt.teacher.clone().unwrap_or_default(),
+        ])?;
+    }
+
+    Ok(writer.into_inner()?)
+}
+
+/// Exporte l'emploi du temps d'un groupe sur une période
+#[poise::command(slash_command, prefix_command)]
+pub async fn export(
+    ctx: Context<'_>,
+    #[description = "Format du fichier"] format: ExportFormat,
+    #[description = "Premier jour (ex: 02/10/2023)"] debut: String,
+    #[description = "Dernier jour (ex: 27/10/2023)"] fin: String,
+    #[description = "Groupe (ex: 1-INFO-32)"] group: Option<String>,
+) -> Result<(), Error> {
+    // attachments can't be sent in the initial response of a slash command
+    let _ = ctx.defer().await;
+
+    let (Ok(start), Ok(end)) = (
+        NaiveDate::parse_from_str(&debut, "%d/%m/%Y"),
+        NaiveDate::parse_from_str(&fin, "%d/%m/%Y"),
+    ) else {
+        ctx.say("Dates invalides, utilisez le format JJ/MM/AAAA.")
+            .await?;
+        return Ok(());
+    };
+
+    let promo = if let Some(group) = group {
+        parse_promo_name(&group)
+    } else {
+        let member = ctx.author_member().await.map(|m| m.into_owned());
+        get_user_promo(ctx, ctx.author().id, member)?
+    };
+    let Some(promo) = promo else {
+        ctx.say("Could not find group for user! Use /setgroup to save a default group.")
+            .await?;
+        return Ok(());
+    };
+
+    let events = get_sorted_events_range(start, end + chrono::Duration::days(1)).await?;
+    // the events of the secondary feeds aren't classes
+    let events: Vec<Event> = events
+        .get(&promo)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.category.is_none())
+        .cloned()
+        .collect();
+
+    let (data, extension) = match format {
+        ExportFormat::Csv => (make_csv(&events)?, "csv"),
+    };
+
+    ctx.send(|m| {
+        m.content(format!(
+            "{} cours pour {} du {} au {}",
+            events.len(),
+            promo,
+            start.format("%d/%m/%Y"),
+            end.format("%d/%m/%Y")
+        ))
+        .attachment(AttachmentType::Bytes {
+            data: data.into(),
+            filename: format!(
+                "edt-{}-{}-{}.{}",
+                promo,
+                start.format("%Y%m%d"),
+                end.format("%Y%m%d"),
+                extension
+            ),
+        })
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use chrono::Local;
+use poise::serenity_prelude::{ChannelId, Colour};
+
+use super::get_user_promo;
+use crate::{config, Context, Error};
+
+/// Signale une erreur dans l'emploi du temps (salle, horaire, cours manquant...)
+#[poise::command(slash_command, prefix_command)]
+pub async fn feedback(
+    ctx: Context<'_>,
+    #[description = "Description du problème"] message: String,
+) -> Result<(), Error> {
+    let Some(channel) = config::get().feedback_channel.map(ChannelId) else {
+        ctx.send(|m| {
+            m.content("Aucun salon de signalement n'est configuré.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    };
+
+    let displayed = ctx.data().state.last_displayed(ctx.author().id).await;
+    let (promo, date) = match displayed {
+        Some((promo, date)) => (Some(promo), date),
+        None => {
+            let member = ctx.author_member().await.map(|m| m.into_owned());
+            (
+                get_user_promo(ctx, ctx.author().id, member)?,
+                Local::now().date_naive(),
+            )
+        }
+    };
+
+    let author = ctx.author();
+    channel
+        .send_message(ctx, |m| {
+            m.embed(|e| {
+                e.title("📣 Signalement")
+                    .author(|a| a.name(author.tag()).icon_url(author.face()))
+                    .description(&message)
+                    .field(
+                        "Groupe",
+                        promo.map_or("inconnu".to_string(), |p| p.to_string()),
+                        true,
+                    )
+                    .field("Date affichée", date.format("%d/%m/%Y"), true)
+                    .color(Colour::ORANGE)
+                    .timestamp(ctx.created_at())
+            })
+        })
+        .await?;
+
+    ctx.send(|m| {
+        m.content("Merci, ton signalement a été transmis aux administrateurs !")
+            .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
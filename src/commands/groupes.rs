@@ -0,0 +1,96 @@
+use crate::{
+    calendar::{parse_promo_name, set_group_aliases, unclassified_groups},
+    Context, Error,
+};
+
+/// Associe les groupes inconnus d'ADE à un groupe de l'emploi du temps
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("associer", "retirer")
+)]
+pub async fn groupes(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+async fn autocomplete_unclassified<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    unclassified_groups()
+        .into_keys()
+        .filter(move |g| g.to_lowercase().contains(&partial.to_lowercase()))
+        .take(25)
+        .collect::<Vec<String>>()
+        .into_iter()
+}
+
+async fn autocomplete_alias<'a>(
+    ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    ctx.data()
+        .db
+        .get_group_aliases()
+        .unwrap_or_default()
+        .into_keys()
+        .filter(move |g| g.to_lowercase().contains(&partial.to_lowercase()))
+        .take(25)
+        .collect::<Vec<String>>()
+        .into_iter()
+}
+
+/// Affiche les cours d'un groupe inconnu avec ceux d'un groupe
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn associer(
+    ctx: Context<'_>,
+    #[description = "Groupe inconnu, tel qu'écrit dans ADE"]
+    #[autocomplete = "autocomplete_unclassified"]
+    inconnu: String,
+    #[description = "Groupe (ex: 1-INFO-32)"] groupe: String,
+) -> Result<(), Error> {
+    let Some(promo) = parse_promo_name(&groupe) else {
+        ctx.send(|m| {
+            m.content(format!("Groupe invalide: {}", groupe))
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    };
+
+    let db = &ctx.data().db;
+    db.set_group_alias(&inconnu, Some(&groupe))?;
+    set_group_aliases(db.get_group_aliases()?);
+    ctx.send(|m| {
+        m.content(format!(
+            "Les cours de « {} » sont maintenant affichés pour {}.",
+            inconnu, promo
+        ))
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Retire l'association d'un groupe inconnu
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn retirer(
+    ctx: Context<'_>,
+    #[description = "Groupe inconnu, tel qu'écrit dans ADE"]
+    #[autocomplete = "autocomplete_alias"]
+    inconnu: String,
+) -> Result<(), Error> {
+    let db = &ctx.data().db;
+    db.set_group_alias(&inconnu, None)?;
+    set_group_aliases(db.get_group_aliases()?);
+    ctx.send(|m| {
+        m.content(format!("Association de « {} » retirée.", inconnu))
+            .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use poise::serenity_prelude::{Colour, CreateEmbed};
+
+use crate::{i18n::Language, Context, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Consultation,
+    Reminders,
+    Admin,
+}
+
+impl Category {
+    const ALL: [Category; 3] = [Category::Consultation, Category::Reminders, Category::Admin];
+
+    fn name(&self, language: Language) -> &'static str {
+        match (self, language) {
+            (Category::Consultation, Language::French) => "📅 Consultation",
+            (Category::Consultation, Language::English) => "📅 Timetable",
+            (Category::Reminders, Language::French) => "🔔 Rappels",
+            (Category::Reminders, Language::English) => "🔔 Reminders",
+            (Category::Admin, Language::French) => "🛠️ Administration",
+            (Category::Admin, Language::English) => "🛠️ Administration",
+        }
+    }
+
+    fn colour(&self) -> Colour {
+        match self {
+            Category::Consultation => Colour::BLUE,
+            Category::Reminders => Colour::GOLD,
+            Category::Admin => Colour::DARK_GREY,
+        }
+    }
+}
+
+struct HelpEntry {
+    command: &'static str,
+    category: Category,
+    french: &'static str,
+    english: &'static str,
+    example: &'static str,
+}
+
+/// Kept in the order of the categories, then by how often the commands are used
+const COMMANDS: &[HelpEntry] = &[
+    HelpEntry {
+        command: "edt",
+        category: Category::Consultation,
+        french: "Emploi du temps d'un jour ou d'une semaine",
+        english: "Timetable of a day or a week",
+        example: "/edt date:demain group:1-INFO-32",
+    },
+    HelpEntry {
+        command: "next",
+        category: Category::Consultation,
+        french: "Prochain cours",
+        english: "Next class",
+        example: "/next",
+    },
+    HelpEntry {
+        command: "quand",
+        category: Category::Consultation,
+        french: "Prochains cours d'une matière",
+        english: "Next classes of a subject",
+        example: "/quand matiere:R3.04",
+    },
+    HelpEntry {
+        command: "semaine",
+        category: Category::Consultation,
+        french: "Emploi du temps de la semaine",
+        english: "Timetable of the week",
+        example: "/semaine date:semaine prochaine",
+    },
+    HelpEntry {
+        command: "exams",
+        category: Category::Consultation,
+        french: "Prochaines évaluations",
+        english: "Upcoming exams",
+        example: "/exams group:2-INFO-3",
+    },
+    HelpEntry {
+        command: "changements",
+        category: Category::Consultation,
+        french: "Changements de l'emploi du temps depuis quelques jours",
+        english: "Timetable changes over the last days",
+        example: "/changements group:2-INFO-3 jours:7",
+    },
+    HelpEntry {
+        command: "devoirs",
+        category: Category::Consultation,
+        french: "Devoirs à rendre, ajoutés par les délégués",
+        english: "Homework to hand in, added by the delegates",
+        example: "/devoirs add matiere:R3.04 date:lundi description:TP2 sur Moodle",
+    },
+    HelpEntry {
+        command: "vacances",
+        category: Category::Consultation,
+        french: "Jours de cours restants avant les vacances",
+        english: "Days of class left before the holidays",
+        example: "/vacances",
+    },
+    HelpEntry {
+        command: "ou",
+        category: Category::Consultation,
+        french: "Où se trouve une salle",
+        english: "Where a room is",
+        example: "/ou salle:B205",
+    },
+    HelpEntry {
+        command: "heures",
+        category: Category::Consultation,
+        french: "Heures faites et restantes par ressource sur le semestre",
+        english: "Hours done and left per subject over the semester",
+        example: "/heures group:2-INFO-3 matiere:R3.04",
+    },
+    HelpEntry {
+        command: "dispo",
+        category: Category::Consultation,
+        french: "Créneaux libres d'un enseignant",
+        english: "Free slots of a teacher",
+        example: "/dispo prof:M. Dupont date:lundi prochain",
+    },
+    HelpEntry {
+        command: "export",
+        category: Category::Consultation,
+        french: "Exporte l'emploi du temps sur une période",
+        english: "Exports the timetable of a period",
+        example: "/export format:csv debut:02/10/2023 fin:27/10/2023",
+    },
+    HelpEntry {
+        command: "setgroup",
+        category: Category::Consultation,
+        french: "Groupe utilisé par défaut (en privé ou sans rôle)",
+        english: "Default group (in DMs or without a role)",
+        example: "/setgroup group:1-INFO-32",
+    },
+    HelpEntry {
+        command: "prefs",
+        category: Category::Consultation,
+        french: "Préférences d'affichage (vue, langue, fuseau...)",
+        english: "Display preferences (view, language, timezone...)",
+        example: "/prefs langue:en ephemere:True",
+    },
+    HelpEntry {
+        command: "feedback",
+        category: Category::Consultation,
+        french: "Signale une erreur dans l'emploi du temps",
+        english: "Reports a mistake in the timetable",
+        example: "/feedback message:le TP est en B110, pas B205",
+    },
+    HelpEntry {
+        command: "rappels",
+        category: Category::Reminders,
+        french: "Rappels d'évaluations en privé (J-7 et J-1)",
+        english: "Exam reminders by DM (7 days and 1 day before)",
+        example: "/rappels actif:True",
+    },
+    HelpEntry {
+        command: "notifications",
+        category: Category::Reminders,
+        french: "Coupe les mentions du bot dans les annonces",
+        english: "Stops the bot from pinging you in announcements",
+        example: "/notifications etat:off",
+    },
+    HelpEntry {
+        command: "config",
+        category: Category::Admin,
+        french: "Réglages du serveur (trous, événements, vocal, campus, mentions, forum, annonces, heure, sans_cours)",
+        english: "Server settings (gaps, events, voice, campus, mentions, forum, announcements, time, empty days)",
+        example: "/config campus nom:Lannion",
+    },
+    HelpEntry {
+        command: "evenement",
+        category: Category::Admin,
+        french: "Ajoute, liste ou retire les événements ponctuels",
+        english: "Adds, lists or removes one-off events",
+        example: "/evenement ajouter titre:Soirée BDE date:10/10/2023 debut:18:00 fin:22:00 groupes:1-INFO-S1",
+    },
+    HelpEntry {
+        command: "appel",
+        category: Category::Admin,
+        french: "Bouton de présence au début des cours et export CSV",
+        english: "Check-in button at the start of classes and CSV export",
+        example: "/appel activer groupe:1-INFO-32",
+    },
+    HelpEntry {
+        command: "override",
+        category: Category::Admin,
+        french: "Annote ou annule un cours",
+        english: "Annotates or cancels a class",
+        example: "/override annuler groupe:1-INFO-32 date:10/10/2023 heure:08:00",
+    },
+    HelpEntry {
+        command: "calendar",
+        category: Category::Admin,
+        french: "Remplace temporairement ADE par un fichier .ics",
+        english: "Temporarily replaces ADE with an .ics file",
+        example: "/calendar upload debut:lundi fin:vendredi",
+    },
+    HelpEntry {
+        command: "validate",
+        category: Category::Admin,
+        french: "Erreurs de lecture de l'emploi du temps",
+        english: "Timetable parsing errors",
+        example: "/validate",
+    },
+    HelpEntry {
+        command: "groupes",
+        category: Category::Admin,
+        french: "Associe un groupe inconnu d'ADE à un groupe",
+        english: "Maps an unknown ADE group to a group",
+        example: "/groupes associer inconnu:INFO1 TP3 groupe:1-INFO-31",
+    },
+    HelpEntry {
+        command: "register",
+        category: Category::Admin,
+        french: "Enregistre les commandes sur ce serveur ou partout (propriétaire du bot)",
+        english: "Registers the commands in this server or globally (bot owner)",
+        example: "@agenda-bot register",
+    },
+    HelpEntry {
+        command: "reload",
+        category: Category::Admin,
+        french: "Relit config.toml sans redémarrer le bot (propriétaire du bot)",
+        english: "Reads config.toml again without restarting the bot (bot owner)",
+        example: "/reload",
+    },
+    HelpEntry {
+        command: "botstats",
+        category: Category::Admin,
+        french: "État du bot",
+        english: "Bot status",
+        example: "/botstats",
+    },
+];
+
+fn make_help_embeds(language: Language) -> Vec<CreateEmbed> {
+    let example = match language {
+        Language::French => "ex",
+        Language::English => "e.g.",
+    };
+
+    Category::ALL
+        .iter()
+        .map(|category| {
+            let lines = COMMANDS
+                .iter()
+                .filter(|c| c.category == *category)
+                .map(|c| {
+                    let description = match language {
+                        Language::French => c.french,
+                        Language::English => c.english,
+                    };
+                    format!(
+                        "**/{}** — {}\n{}: `{}`",
+                        c.command, description, example, c.example
+                    )
+                })
+                .collect::<Vec<String>>();
+
+            let mut embed = CreateEmbed::default();
+            embed
+                .title(category.name(language))
+                .description(lines.join("\n\n"))
+                .color(category.colour());
+            embed
+        })
+        .collect()
+}
+
+/// Liste les commandes du bot avec des exemples
+#[poise::command(slash_command, prefix_command)]
+pub async fn help(ctx: Context<'_>) -> Result<(), Error> {
+    let prefs = ctx.data().db.get_user_prefs(ctx.author().id)?;
+    let embeds = make_help_embeds(prefs.language);
+
+    ctx.send(|m| {
+        m.embeds = embeds;
+        m.ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use agenda_bot::{
+    dates::semester_bounds,
+    hours::{count_hours, total, Hours},
+};
+use chrono::{Local, Utc};
+use poise::serenity_prelude::Colour;
+
+use super::get_user_promo;
+use crate::{
+    calendar::{get_sorted_events_range, parse_promo_name, EventType},
+    Context, Error,
+};
+
+/// Discord allows 25 fields, the last one is kept for the total
+const MAX_LESSONS: usize = 24;
+
+/// "10h30", "6h"
+fn format_minutes(minutes: i64) -> String {
+    match minutes % 60 {
+        0 => format!("{}h", minutes / 60),
+        m => format!("{}h{:02}", minutes / 60, m),
+    }
+}
+
+fn format_hours(label: &str, hours: &Hours) -> String {
+    format!(
+        "**{}** {} / {} (reste {})",
+        label,
+        format_minutes(hours.done),
+        format_minutes(hours.planned),
+        format_minutes(hours.remaining())
+    )
+}
+
+fn type_label(event_type: &EventType) -> &'static str {
+    match event_type {
+        EventType::CM => "CM",
+        EventType::TD => "TD",
+        EventType::TP => "TP",
+        EventType::OTHER => "Autre",
+    }
+}
+
+/// Heures de CM/TD/TP faites et restantes par ressource sur le semestre
+#[poise::command(slash_command, prefix_command)]
+pub async fn heures(
+    ctx: Context<'_>,
+    #[description = "Numéro du group (ex: 32)"] group: Option<String>,
+    #[description = "Ressource (ex: R3.04)"] matiere: Option<String>,
+) -> Result<(), Error> {
+    let promo = match group {
+        Some(group) => parse_promo_name(&group),
+        None => {
+            let member = ctx.author_member().await.map(|m| m.into_owned());
+            get_user_promo(ctx, ctx.author().id, member)?
+        }
+    };
+    let Some(promo) = promo else {
+        ctx.say("Could not find group for user! Use /setgroup to save a default group.")
+            .await?;
+        return Ok(());
+    };
+
+    ctx.defer().await?;
+    let (start, end) = semester_bounds(Local::now().date_naive());
+    let events = get_sorted_events_range(start, end).await?;
+    let events = events.get(&promo).cloned().unwrap_or_default();
+    let matiere = matiere.map(|m| m.to_lowercase());
+    let lessons: Vec<_> = count_hours(&events, Utc::now())
+        .into_iter()
+        .filter(|(lesson, _)| {
+            matiere
+                .as_ref()
+                .is_none_or(|m| lesson.to_lowercase().contains(m))
+        })
+        .collect();
+    if lessons.is_empty() {
+        ctx.say(format!("Aucun cours trouvé pour {} ce semestre", promo))
+            .await?;
+        return Ok(());
+    }
+
+    let mut all = Hours::default();
+    for (_, hours) in &lessons {
+        all.add(total(hours));
+    }
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title(format!("Heures — {}", promo))
+                .description(format!(
+                    "Semestre du {} au {}, heures faites / prévues",
+                    start.format("%d/%m/%Y"),
+                    end.pred_opt().unwrap_or(end).format("%d/%m/%Y")
+                ))
+                .color(Colour::BLUE);
+            for (lesson, hours) in lessons.iter().take(MAX_LESSONS) {
+                let value = hours
+                    .iter()
+                    .map(|(t, h)| format_hours(type_label(t), h))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                e.field(lesson, value, false);
+            }
+            if lessons.len() > MAX_LESSONS {
+                e.footer(|f| {
+                    f.text(format!(
+                        "{} ressources non affichées, précisez la matière",
+                        lessons.len() - MAX_LESSONS
+                    ))
+                });
+            }
+            e.field("Total", format_hours("Tout", &all), false)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+pub mod appel;
+pub mod botstats;
+pub mod calendar;
+pub mod changements;
+pub mod config;
+pub mod devoirs;
+pub mod dispo;
+pub mod edt;
+pub mod evenement;
+pub mod exams;
+pub mod export;
+pub mod feedback;
+pub mod groupes;
+pub mod help;
+pub mod heures;
+pub mod next;
+pub mod notifications;
+pub mod ou;
+pub mod overrides;
+pub mod prefs;
+pub mod quand;
+pub mod rappels;
+pub mod register;
+pub mod reload;
+pub mod semaine;
+pub mod setgroup;
+pub mod vacances;
+pub mod validate;
+
+use agenda_bot::{
+    dates::{parse_date, short_label},
+    search::resolve_group,
+};
+use chrono::{Local, NaiveDate};
+use poise::{
+    serenity_prelude::{Member, UserId},
+    AutocompleteChoice,
+};
+
+use crate::{
+    calendar::{get_events, parse_promo_name, parse_role_name, GroupHierarchy, Promo},
+    Context, Error,
+};
+
+/// Sous-groupes of the member, read from their role names
+pub fn get_user_groups(ctx: Context<'_>, member: Member) -> Option<Vec<Promo>> {
+    let roles = member.roles(ctx)?;
+    let promos = roles
+        .iter()
+        .filter_map(|r| parse_role_name(&r.name))
+        .filter(|p| p.group >= 10)
+        .collect();
+
+    Some(promos)
+}
+
+/// Finds the promo of a user from their roles, falling back to the default group saved with
+/// `/setgroup` when they have none or when there is no guild to read roles from
+pub fn get_user_promo(
+    ctx: Context<'_>,
+    user: UserId,
+    member: Option<Member>,
+) -> Result<Option<Promo>, Error> {
+    if let Some(member) = member {
+        if let Some(groups) = get_user_groups(ctx, member) {
+            if !groups.is_empty() {
+                return Ok(Some(groups[0].clone()));
+            }
+        }
+    }
+
+    Ok(ctx.data().db.get_user_group(user)?)
+}
+
+const AUTOCOMPLETE_DAYS: i64 = 14;
+
+/// Parses a group argument, exactly written (`1-INFO-32`) or loosely (`32`, `info32`) in which
+/// case it's looked for among the groups of the feed, in the year of the author when ambiguous.
+/// Replies and returns `None` when it matches no group or several.
+pub async fn parse_group_option(ctx: Context<'_>, group: &str) -> Result<Option<Promo>, Error> {
+    if let Some(promo) = parse_promo_name(group) {
+        return Ok(Some(promo));
+    }
+
+    let known = GroupHierarchy::from_events(&get_events().await?).all();
+    let member = ctx.author_member().await.map(|m| m.into_owned());
+    let hint = get_user_promo(ctx, ctx.author().id, member)?;
+    let mut candidates = resolve_group(group, &known, hint.as_ref());
+    match candidates.len() {
+        1 => Ok(candidates.pop()),
+        0 => {
+            ctx.send(|m| {
+                m.content(format!("Groupe invalide: {}", group))
+                    .ephemeral(true)
+            })
+            .await?;
+            Ok(None)
+        }
+        _ => {
+            let mut names = candidates.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+            names.sort();
+            ctx.send(|m| {
+                m.content(format!("Groupe ambigu, précise : {}", names.join(", ")))
+                    .ephemeral(true)
+            })
+            .await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Parses an optional date argument, today when missing. Replies with examples of the accepted
+/// expressions and returns `None` when it is invalid
+pub async fn parse_date_option(
+    ctx: Context<'_>,
+    date: Option<String>,
+) -> Result<Option<NaiveDate>, Error> {
+    let today = Local::now().date_naive();
+    let Some(date) = date else {
+        return Ok(Some(today));
+    };
+
+    match parse_date(&date, today) {
+        Some(date) => Ok(Some(date)),
+        None => {
+            ctx.send(|m| {
+                m.content(format!(
+                    "Date invalide: {} (ex: demain, lundi prochain, dans 3 jours, 14/11)",
+                    date
+                ))
+                .ephemeral(true)
+            })
+            .await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Suggests the next 14 days, or the date typed so far once it can be parsed. The value is a
+/// dd/mm/YYYY date so it goes through `parse_date_option` like a typed one.
+pub async fn autocomplete_date<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = AutocompleteChoice<String>> + 'a {
+    let today = Local::now().date_naive();
+    let typed = parse_date(partial, today).filter(|_| !partial.trim().is_empty());
+    let partial = partial.to_lowercase();
+
+    typed
+        .into_iter()
+        .chain(
+            (0..AUTOCOMPLETE_DAYS)
+                .map(move |days| today + chrono::Duration::days(days))
+                .filter(move |d| typed.is_none() && short_label(*d).contains(&partial)),
+        )
+        .map(|date| AutocompleteChoice {
+            name: short_label(date),
+            value: date.format("%d/%m/%Y").to_string(),
+        })
+}
+
+pub async fn autocomplete_campus<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    crate::config::get()
+        .campuses
+        .iter()
+        .map(|c| c.name.clone())
+        .filter(move |c| c.to_lowercase().starts_with(&partial.to_lowercase()))
+        .collect::<Vec<String>>()
+        .into_iter()
+}
@@ -0,0 +1,75 @@
+use chrono::{Local, Utc};
+use poise::serenity_prelude::{self as serenity, Colour};
+
+use super::get_user_promo;
+use crate::{
+    calendar::{get_sorted_events_range, parse_promo_name},
+    embed::{make_event_field, with_live_status, EmbedOptions},
+    Context, Error,
+};
+
+/// Classes are looked for up to two weeks ahead, past the usual short breaks
+const NEXT_CLASS_DAYS: i64 = 14;
+
+/// Affiche le prochain cours d'un groupe ou d'un utilisateur
+#[poise::command(slash_command, prefix_command)]
+pub async fn next(
+    ctx: Context<'_>,
+    #[description = "Utilisateur"] member: Option<serenity::Member>,
+    #[description = "Numéro du group (ex: 32)"] group: Option<String>,
+) -> Result<(), Error> {
+    let promo = if let Some(member) = member {
+        get_user_promo(ctx, member.user.id, Some(member))?
+    } else if let Some(group) = group {
+        parse_promo_name(&group)
+    } else {
+        let member = ctx.author_member().await.map(|m| m.into_owned());
+        get_user_promo(ctx, ctx.author().id, member)?
+    };
+    let Some(promo) = promo else {
+        ctx.say("Could not find group for user! Use /setgroup to save a default group.")
+            .await?;
+        return Ok(());
+    };
+
+    let today = Local::now().date_naive();
+    let events =
+        get_sorted_events_range(today, today + chrono::Duration::days(NEXT_CLASS_DAYS)).await?;
+    let now = Utc::now();
+    let next = events
+        .get(&promo)
+        .into_iter()
+        .flatten()
+        .find(|e| !e.cancelled && e.end > now);
+    let Some(next) = next else {
+        ctx.say(format!(
+            "Aucun cours prévu pour {} dans les {} prochains jours",
+            promo, NEXT_CLASS_DAYS
+        ))
+        .await?;
+        return Ok(());
+    };
+
+    let prefs = ctx.data().db.get_user_prefs(ctx.author().id)?;
+    let options = EmbedOptions::for_guild(&ctx.data().db, ctx.guild_id())?.with_prefs(&prefs);
+    let (mut name, value) = make_event_field(next, &options);
+    if next.start.date_naive() == today {
+        name = with_live_status(name, next, now, &options);
+    }
+    let title = if next.start <= now {
+        format!("En cours — {}", promo)
+    } else {
+        format!(
+            "Prochain cours — {} — {}",
+            promo,
+            next.start.format("%d/%m/%Y")
+        )
+    };
+    ctx.send(|m| {
+        m.embed(|e| e.title(title).field(name, value, false).color(Colour::BLUE))
+            .ephemeral(prefs.ephemeral)
+    })
+    .await?;
+
+    Ok(())
+}
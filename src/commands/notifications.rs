@@ -0,0 +1,31 @@
+use crate::{Context, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum NotificationState {
+    #[name = "on"]
+    On,
+    #[name = "off"]
+    Off,
+}
+
+/// Active ou coupe les mentions du bot dans les annonces et rappels automatiques
+#[poise::command(slash_command, prefix_command)]
+pub async fn notifications(
+    ctx: Context<'_>,
+    #[description = "on ou off"] etat: NotificationState,
+) -> Result<(), Error> {
+    let enabled = etat == NotificationState::On;
+    ctx.data().db.set_notifications(ctx.author().id, enabled)?;
+
+    ctx.send(|m| {
+        m.content(if enabled {
+            "Le bot te mentionnera de nouveau dans ses annonces."
+        } else {
+            "Le bot ne te mentionnera plus dans ses annonces, elles restent visibles dans le salon."
+        })
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use poise::serenity_prelude::Colour;
+
+use crate::{Context, Error};
+
+async fn autocomplete_room<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    crate::config::get()
+        .rooms
+        .iter()
+        .map(|r| r.name.clone())
+        .filter(move |r| r.to_lowercase().starts_with(&partial.to_lowercase()))
+        .take(25)
+        .collect::<Vec<String>>()
+        .into_iter()
+}
+
+/// Indique où se trouve une salle
+#[poise::command(slash_command, prefix_command)]
+pub async fn ou(
+    ctx: Context<'_>,
+    #[description = "Salle (ex: B205)"]
+    #[autocomplete = "autocomplete_room"]
+    salle: String,
+) -> Result<(), Error> {
+    let config = crate::config::get();
+    let Some(room) = config.room_of(&salle) else {
+        ctx.send(|m| {
+            m.content(format!("Salle inconnue: {}", salle))
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    };
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title(format!("📍 {}", room.name)).color(Colour::BLUE);
+            if let Some(building) = &room.building {
+                e.field("Bâtiment", building, true);
+            }
+            if let Some(floor) = room.floor {
+                e.field(
+                    "Étage",
+                    if floor == 0 {
+                        "Rez-de-chaussée".to_string()
+                    } else {
+                        floor.to_string()
+                    },
+                    true,
+                );
+            }
+            if let Some(map) = &room.map {
+                e.url(map).description(format!("[Voir le plan]({})", map));
+            }
+            if let Some(image) = &room.image {
+                e.image(image);
+            }
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
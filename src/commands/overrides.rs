@@ -0,0 +1,177 @@
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::{
+    calendar::{get_sorted_events, parse_promo_name, set_overrides, Event, EventOverride},
+    Context, Error,
+};
+
+/// Annote ou annule un cours de l'emploi du temps
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "override",
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("note", "annuler", "retirer")
+)]
+pub async fn override_event(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Finds the event of `group` starting at `time`, replying with the reason when there is none
+async fn find_event(
+    ctx: Context<'_>,
+    group: &str,
+    date: &str,
+    time: &str,
+) -> Result<Option<Event>, Error> {
+    let (Ok(date), Ok(time)) = (
+        NaiveDate::parse_from_str(date, "%d/%m/%Y"),
+        NaiveTime::parse_from_str(time, "%H:%M"),
+    ) else {
+        ctx.send(|m| {
+            m.content("Date ou heure invalide, utilisez les formats JJ/MM/AAAA et HH:MM.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(None);
+    };
+
+    let Some(promo) = parse_promo_name(group) else {
+        ctx.send(|m| {
+            m.content(format!("Groupe invalide: {}", group))
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(None);
+    };
+
+    let events = get_sorted_events(date).await?;
+    let event = events
+        .get(&promo)
+        .and_then(|events| events.iter().find(|e| e.start.time() == time))
+        .cloned();
+    if event.is_none() {
+        ctx.send(|m| {
+            m.content(format!(
+                "Aucun cours de {} ne commence le {} à {}.",
+                promo,
+                date.format("%d/%m/%Y"),
+                time.format("%H:%M")
+            ))
+            .ephemeral(true)
+        })
+        .await?;
+    }
+
+    Ok(event)
+}
+
+/// Saves the override and applies it to the cached calendar right away
+async fn save_override(
+    ctx: Context<'_>,
+    event: &Event,
+    event_override: Option<EventOverride>,
+) -> Result<(), Error> {
+    let db = &ctx.data().db;
+    match event_override {
+        Some(event_override) => db.set_event_override(&event.uid, &event_override, event.end)?,
+        None => db.remove_event_override(&event.uid)?,
+    }
+    set_overrides(db.get_event_overrides()?);
+
+    Ok(())
+}
+
+fn describe(event: &Event) -> String {
+    format!(
+        "{} du {} à {}",
+        event.lesson,
+        event.start.format("%d/%m/%Y"),
+        event.start.format("%H:%M")
+    )
+}
+
+/// Ajoute une note affichée avec un cours
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn note(
+    ctx: Context<'_>,
+    #[description = "Groupe (ex: 1-INFO-32)"] groupe: String,
+    #[description = "Jour du cours (ex: 10/10/2023)"] date: String,
+    #[description = "Heure de début (ex: 08:00)"] heure: String,
+    #[description = "Note (ex: amphi changé, voir mail)"] texte: String,
+) -> Result<(), Error> {
+    let Some(event) = find_event(ctx, &groupe, &date, &heure).await? else {
+        return Ok(());
+    };
+
+    let event_override = EventOverride {
+        note: Some(texte),
+        cancelled: event.cancelled,
+    };
+    save_override(ctx, &event, Some(event_override)).await?;
+
+    ctx.send(|m| {
+        m.content(format!("Note ajoutée au cours de {}.", describe(&event)))
+            .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Marque un cours comme annulé
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn annuler(
+    ctx: Context<'_>,
+    #[description = "Groupe (ex: 1-INFO-32)"] groupe: String,
+    #[description = "Jour du cours (ex: 10/10/2023)"] date: String,
+    #[description = "Heure de début (ex: 08:00)"] heure: String,
+) -> Result<(), Error> {
+    let Some(event) = find_event(ctx, &groupe, &date, &heure).await? else {
+        return Ok(());
+    };
+
+    let event_override = EventOverride {
+        note: event.admin_note.clone(),
+        cancelled: true,
+    };
+    save_override(ctx, &event, Some(event_override)).await?;
+
+    ctx.send(|m| {
+        m.content(format!(
+            "Le cours de {} est marqué annulé.",
+            describe(&event)
+        ))
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Retire la note et l'annulation d'un cours
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn retirer(
+    ctx: Context<'_>,
+    #[description = "Groupe (ex: 1-INFO-32)"] groupe: String,
+    #[description = "Jour du cours (ex: 10/10/2023)"] date: String,
+    #[description = "Heure de début (ex: 08:00)"] heure: String,
+) -> Result<(), Error> {
+    let Some(event) = find_event(ctx, &groupe, &date, &heure).await? else {
+        return Ok(());
+    };
+
+    save_override(ctx, &event, None).await?;
+
+    ctx.send(|m| {
+        m.content(format!(
+            "Le cours de {} est de nouveau affiché tel quel.",
+            describe(&event)
+        ))
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
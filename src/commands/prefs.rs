@@ -0,0 +1,135 @@
+use chrono_tz::Tz;
+
+use super::autocomplete_campus;
+use crate::{
+    config,
+    i18n::Language,
+    prefs::{Format, View},
+    Context, Error,
+};
+
+async fn autocomplete_category<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    config::get()
+        .feeds
+        .iter()
+        .map(|f| f.category.clone())
+        .filter(move |c| c.to_lowercase().starts_with(&partial.to_lowercase()))
+        .collect::<Vec<String>>()
+        .into_iter()
+}
+
+/// Définit vos préférences d'affichage, sans option affiche les préférences actuelles
+#[poise::command(slash_command, prefix_command)]
+#[allow(clippy::too_many_arguments)]
+pub async fn prefs(
+    ctx: Context<'_>,
+    #[description = "Vue par défaut de /edt"] vue: Option<View>,
+    #[description = "Format par défaut (texte pour les lecteurs d'écran)"] format: Option<Format>,
+    #[description = "Réponses visibles uniquement par vous"] ephemere: Option<bool>,
+    #[description = "Heures au format 12h"] format_12h: Option<bool>,
+    #[description = "Langue de l'emploi du temps"] langue: Option<Language>,
+    #[description = "Fuseau horaire en privé (ex: America/Montreal, \"aucun\" pour Paris)"]
+    fuseau: Option<String>,
+    #[description = "Catégorie d'événements à masquer (ex: BDE)"]
+    #[autocomplete = "autocomplete_category"]
+    masquer: Option<String>,
+    #[description = "Catégorie d'événements à afficher de nouveau"]
+    #[autocomplete = "autocomplete_category"]
+    afficher: Option<String>,
+    #[description = "Campus à afficher (\"défaut\" pour celui du serveur)"]
+    #[autocomplete = "autocomplete_campus"]
+    campus: Option<String>,
+) -> Result<(), Error> {
+    let db = &ctx.data().db;
+    let mut prefs = db.get_user_prefs(ctx.author().id)?;
+
+    if let Some(vue) = vue {
+        prefs.view = vue;
+    }
+    if let Some(format) = format {
+        prefs.format = format;
+    }
+    if let Some(ephemere) = ephemere {
+        prefs.ephemeral = ephemere;
+    }
+    if let Some(format_12h) = format_12h {
+        prefs.hour12 = format_12h;
+    }
+    if let Some(langue) = langue {
+        prefs.language = langue;
+    }
+    if let Some(fuseau) = fuseau {
+        if fuseau == "aucun" {
+            prefs.timezone = None;
+        } else if let Ok(tz) = fuseau.parse::<Tz>() {
+            prefs.timezone = Some(tz);
+        } else {
+            ctx.send(|m| {
+                m.content(format!("Fuseau horaire inconnu: {}", fuseau))
+                    .ephemeral(true)
+            })
+            .await?;
+            return Ok(());
+        }
+    }
+    for category in masquer.iter().chain(afficher.iter()) {
+        if config::feed(category).is_none() {
+            ctx.send(|m| {
+                m.content(format!("Catégorie inconnue: {}", category))
+                    .ephemeral(true)
+            })
+            .await?;
+            return Ok(());
+        }
+    }
+    if let Some(campus) = campus {
+        if matches!(campus.as_str(), "défaut" | "defaut") {
+            prefs.campus = None;
+        } else if let Some(campus) = config::campus(&campus) {
+            prefs.campus = Some(campus.name);
+        } else {
+            ctx.send(|m| {
+                m.content(format!("Campus inconnu: {}", campus))
+                    .ephemeral(true)
+            })
+            .await?;
+            return Ok(());
+        }
+    }
+    if let Some(masquer) = masquer {
+        if !prefs.hidden_categories.contains(&masquer) {
+            prefs.hidden_categories.push(masquer);
+        }
+    }
+    if let Some(afficher) = afficher {
+        prefs.hidden_categories.retain(|c| *c != afficher);
+    }
+    db.set_user_prefs(ctx.author().id, &prefs)?;
+
+    let yes_no = |b: bool| if b { "oui" } else { "non" };
+    ctx.send(|m| {
+        m.content(format!(
+            "Vue: {}\nFormat: {}\nÉphémère: {}\nFormat 12h: {}\nLangue: {}\nFuseau horaire: {}\n\
+             Catégories masquées: {}\nCampus: {}",
+            prefs.view,
+            prefs.format,
+            yes_no(prefs.ephemeral),
+            yes_no(prefs.hour12),
+            prefs.language,
+            prefs.timezone.map_or("Europe/Paris", |tz| tz.name()),
+            if prefs.hidden_categories.is_empty() {
+                "aucune".to_string()
+            } else {
+                prefs.hidden_categories.join(", ")
+            },
+            prefs.campus.as_deref().unwrap_or("celui du serveur")
+        ))
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
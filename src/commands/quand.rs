@@ -0,0 +1,93 @@
+use agenda_bot::{dates::short_label, search::subject_score};
+use chrono::{Local, Utc};
+use poise::serenity_prelude::{self as serenity, Colour};
+
+use super::get_user_promo;
+use crate::{
+    calendar::{get_sorted_events_range, parse_promo_name, Event},
+    config,
+    embed::{make_event_field, EmbedOptions},
+    Context, Error,
+};
+
+/// Subjects are looked for over two months, long enough for those taught every other week
+const SEARCH_DAYS: i64 = 60;
+const MAX_OCCURRENCES: usize = 5;
+
+/// Prochains cours d'une matière (code ou nom, ex: R3.04, qualité)
+#[poise::command(slash_command, prefix_command)]
+pub async fn quand(
+    ctx: Context<'_>,
+    #[description = "Matière (ex: R3.04, qualité dev)"] matiere: String,
+    #[description = "Numéro du group (ex: 32)"] group: Option<String>,
+    #[description = "Utilisateur"] member: Option<serenity::Member>,
+) -> Result<(), Error> {
+    let promo = if let Some(member) = member {
+        get_user_promo(ctx, member.user.id, Some(member))?
+    } else if let Some(group) = group {
+        parse_promo_name(&group)
+    } else {
+        let member = ctx.author_member().await.map(|m| m.into_owned());
+        get_user_promo(ctx, ctx.author().id, member)?
+    };
+    let Some(promo) = promo else {
+        ctx.say("Could not find group for user! Use /setgroup to save a default group.")
+            .await?;
+        return Ok(());
+    };
+
+    let today = Local::now().date_naive();
+    let events =
+        get_sorted_events_range(today, today + chrono::Duration::days(SEARCH_DAYS)).await?;
+    let now = Utc::now();
+    let upcoming: Vec<(u8, &Event)> = events
+        .get(&promo)
+        .into_iter()
+        .flatten()
+        .filter(|e| !e.cancelled && e.category.is_none() && e.end > now)
+        .filter_map(|e| Some((subject_score(&matiere, &e.lesson)?, e)))
+        .collect();
+    // "dev" shouldn't list every subject containing it when a code matches exactly
+    let Some(best) = upcoming.iter().map(|(score, _)| *score).min() else {
+        ctx.say(format!(
+            "Aucun cours de « {} » prévu pour {} dans les {} prochains jours",
+            matiere, promo, SEARCH_DAYS
+        ))
+        .await?;
+        return Ok(());
+    };
+    let occurrences: Vec<&Event> = upcoming
+        .into_iter()
+        .filter(|(score, _)| *score == best)
+        .map(|(_, e)| e)
+        .take(MAX_OCCURRENCES)
+        .collect();
+
+    let prefs = ctx.data().db.get_user_prefs(ctx.author().id)?;
+    let options = EmbedOptions::for_guild(&ctx.data().db, ctx.guild_id())?.with_prefs(&prefs);
+    let lessons = occurrences.iter().fold(Vec::new(), |mut lessons, e| {
+        let label = config::get().subject_label(e);
+        if !lessons.contains(&label) {
+            lessons.push(label);
+        }
+        lessons
+    });
+    ctx.send(|m| {
+        m.embed(|embed| {
+            embed
+                .title(format!("Prochains cours — {}", promo))
+                .description(lessons.join("\n"))
+                .color(Colour::BLUE);
+            for evt in &occurrences {
+                let (name, value) = make_event_field(evt, &options);
+                let date = short_label(evt.start.with_timezone(&Local).date_naive());
+                embed.field(format!("{} — {}", date, name), value, false);
+            }
+            embed
+        })
+        .ephemeral(prefs.ephemeral)
+    })
+    .await?;
+
+    Ok(())
+}
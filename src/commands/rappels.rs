@@ -0,0 +1,42 @@
+use super::get_user_promo;
+use crate::{Context, Error};
+
+/// Reçois en message privé les rappels d'évaluations de ton groupe (J-7 et J-1)
+#[poise::command(slash_command, prefix_command)]
+pub async fn rappels(
+    ctx: Context<'_>,
+    #[description = "Recevoir les rappels"] actif: bool,
+) -> Result<(), Error> {
+    let db = &ctx.data().db;
+    if !actif {
+        db.set_exam_subscription(ctx.author().id, None)?;
+        ctx.send(|m| {
+            m.content("Tu ne recevras plus les rappels d'évaluations.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    }
+
+    let member = ctx.author_member().await.map(|m| m.into_owned());
+    let Some(promo) = get_user_promo(ctx, ctx.author().id, member)? else {
+        ctx.send(|m| {
+            m.content("Could not find group for user! Use /setgroup to save a default group.")
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    };
+
+    db.set_exam_subscription(ctx.author().id, Some(&promo))?;
+    ctx.send(|m| {
+        m.content(format!(
+            "Tu recevras en privé les rappels d'évaluations de {} une semaine et la veille avant.",
+            promo
+        ))
+        .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,8 @@
+use crate::{Context, Error};
+
+/// Enregistre ou retire les commandes, sur ce serveur ou partout (propriétaire du bot)
+#[poise::command(slash_command, prefix_command, owners_only)]
+pub async fn register(ctx: Context<'_>) -> Result<(), Error> {
+    poise::builtins::register_application_commands_buttons(ctx).await?;
+    Ok(())
+}
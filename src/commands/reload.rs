@@ -0,0 +1,18 @@
+use crate::{config, Context, Error};
+
+/// Relit config.toml sans redémarrer le bot (propriétaire du bot)
+#[poise::command(slash_command, prefix_command, owners_only)]
+pub async fn reload(ctx: Context<'_>) -> Result<(), Error> {
+    match config::reload() {
+        Ok(()) => ctx.say("✅ Configuration rechargée").await?,
+        Err(err) => {
+            ctx.say(format!(
+                "❌ Configuration invalide, l'ancienne est conservée : {}",
+                err
+            ))
+            .await?
+        }
+    };
+
+    Ok(())
+}
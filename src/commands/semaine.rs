@@ -0,0 +1,18 @@
+use super::{autocomplete_date, edt::send_timetable, parse_date_option};
+use crate::{prefs::View, Context, Error};
+
+/// Affiche l'emploie du temps de la semaine
+#[poise::command(slash_command, prefix_command)]
+pub async fn semaine(
+    ctx: Context<'_>,
+    #[description = "Numéro du group (ex: 32)"] group: Option<String>,
+    #[description = "Jour de la semaine (ex: semaine prochaine, lundi, 14/11)"]
+    #[autocomplete = "autocomplete_date"]
+    date: Option<String>,
+) -> Result<(), Error> {
+    let Some(date) = parse_date_option(ctx, date).await? else {
+        return Ok(());
+    };
+
+    send_timetable(ctx, None, group, Some(View::Week), None, date, None).await
+}
@@ -0,0 +1,36 @@
+use crate::{calendar::parse_promo_name, Context, Error};
+
+/// Enregistre le groupe utilisé par défaut (en message privé ou sans rôle)
+#[poise::command(slash_command, prefix_command)]
+pub async fn setgroup(
+    ctx: Context<'_>,
+    #[description = "Groupe (ex: 1-INFO-32)"] group: String,
+) -> Result<(), Error> {
+    let Some(promo) = parse_promo_name(&group) else {
+        ctx.send(|m| {
+            m.content(format!("Groupe invalide: {}", group))
+                .ephemeral(true)
+        })
+        .await?;
+        return Ok(());
+    };
+
+    let db = &ctx.data().db;
+    db.set_user_group(ctx.author().id, &promo)?;
+    // exam reminders follow the new group
+    let subscribed = db
+        .get_exam_subscribers()?
+        .iter()
+        .any(|(user, _)| *user == ctx.author().id);
+    if subscribed {
+        db.set_exam_subscription(ctx.author().id, Some(&promo))?;
+    }
+
+    ctx.send(|m| {
+        m.content(format!("Default group set to {}", promo))
+            .ephemeral(true)
+    })
+    .await?;
+
+    Ok(())
+}
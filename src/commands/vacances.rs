@@ -0,0 +1,106 @@
+use std::collections::BTreeSet;
+
+use chrono::{Datelike, Local, NaiveDate, Weekday};
+
+use super::{get_user_promo, parse_group_option};
+use crate::{calendar::get_sorted_events_range, config, Context, Error};
+
+/// A full week without any class is considered a vacation
+const MIN_VACATION_WEEKDAYS: usize = 5;
+
+/// First run of empty weekdays after `from`, returned as its first and last day
+fn detect_vacation(
+    days_with_events: &BTreeSet<NaiveDate>,
+    from: NaiveDate,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let last = *days_with_events.iter().next_back()?;
+
+    let mut gap: Vec<NaiveDate> = Vec::new();
+    for day in from.iter_days().take_while(|d| *d <= last) {
+        if matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            continue;
+        }
+
+        if days_with_events.contains(&day) {
+            if gap.len() >= MIN_VACATION_WEEKDAYS {
+                break;
+            }
+            gap.clear();
+        } else {
+            gap.push(day);
+        }
+    }
+
+    if gap.len() >= MIN_VACATION_WEEKDAYS {
+        Some((gap[0], gap[gap.len() - 1]))
+    } else {
+        None
+    }
+}
+
+/// Affiche le nombre de jours de cours restants avant les prochaines vacances
+#[poise::command(slash_command, prefix_command)]
+pub async fn vacances(
+    ctx: Context<'_>,
+    #[description = "Groupe (ex: 1-INFO-32)"] group: Option<String>,
+) -> Result<(), Error> {
+    let _ = ctx.defer().await;
+
+    let today = Local::now().date_naive();
+    let promo = if let Some(group) = group {
+        let Some(promo) = parse_group_option(ctx, &group).await? else {
+            return Ok(());
+        };
+        Some(promo)
+    } else {
+        let member = ctx.author_member().await.map(|m| m.into_owned());
+        get_user_promo(ctx, ctx.author().id, member)?
+    };
+
+    let events = get_sorted_events_range(today, NaiveDate::MAX).await?;
+    let days_with_events: BTreeSet<NaiveDate> = events
+        .iter()
+        .filter(|(p, _)| promo.as_ref().is_none_or(|promo| *p == promo))
+        .flat_map(|(_, events)| events.iter())
+        // the events of the secondary feeds aren't classes
+        .filter(|e| e.category.is_none())
+        .map(|e| e.start.date_naive())
+        .collect();
+
+    let configured = config::get()
+        .holidays
+        .iter()
+        .filter(|h| h.end >= today)
+        .min_by_key(|h| h.start)
+        .map(|h| (h.start, h.end, format!("{}, ", h.name)));
+    let detected = || {
+        detect_vacation(&days_with_events, today).map(|(start, end)| (start, end, String::new()))
+    };
+
+    let Some((start, end, name)) = configured.or_else(detected) else {
+        ctx.say("Aucune période de vacances trouvée dans le calendrier.")
+            .await?;
+        return Ok(());
+    };
+
+    if start <= today {
+        ctx.say(format!(
+            "C'est les vacances ! 🎉 Reprise après le {}",
+            end.format("%d/%m/%Y")
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let remaining = days_with_events.range(today..start).count();
+    ctx.say(format!(
+        "Plus que {} jour{} de cours avant les vacances 🎉 ({}à partir du {})",
+        remaining,
+        if remaining > 1 { "s" } else { "" },
+        name,
+        start.format("%d/%m/%Y")
+    ))
+    .await?;
+
+    Ok(())
+}
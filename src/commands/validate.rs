@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, HashSet};
+
+use agenda_bot::{conflicts::find_conflicts, dates::short_label};
+use chrono::Local;
+use poise::serenity_prelude::Colour;
+
+use crate::{
+    calendar::{apply_group_aliases, sort_events, unknown_department, validate_calendar, Event},
+    Context, Error,
+};
+
+/// Each list of the report is cut after this many entries to fit in an embed
+const MAX_ENTRIES: usize = 10;
+/// Overlapping classes are looked for in the coming weeks, the past ones don't matter anymore
+const CONFLICT_DAYS: i64 = 28;
+/// Skipped events shown as examples
+const MAX_EXAMPLES: usize = 3;
+/// Examples are cut so that they fit with the code block in the 1024 characters of a field
+const MAX_EXAMPLE_LENGTH: usize = 300;
+
+/// `text` cut to `max` characters, ending with "…" when it was longer
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+
+    let mut cut = text.chars().take(max - 1).collect::<String>();
+    cut.push('…');
+    cut
+}
+
+/// One line per pair of overlapping classes, with the promo it was found in
+fn conflict_lines(events: &[Event]) -> Vec<String> {
+    let today = Local::now().date_naive();
+    let sorted = sort_events(events, today, today + chrono::Duration::days(CONFLICT_DAYS));
+    let mut promos = sorted.keys().collect::<Vec<_>>();
+    promos.sort_by_key(|p| p.to_string());
+
+    // a conflict of a semester-wide class shows up in every sous-groupe, it's listed once
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+    for promo in promos {
+        for (first, second) in find_conflicts(&sorted[promo]) {
+            if !seen.insert((first.uid.clone(), second.uid.clone())) {
+                continue;
+            }
+            lines.push(format!(
+                "`{}` {} {} {} ({}) ↔ {} ({})",
+                promo,
+                short_label(first.start.date_naive()),
+                first.start.format("%H:%M"),
+                first.lesson_code(),
+                first.location,
+                second.lesson_code(),
+                second.location
+            ));
+        }
+    }
+
+    lines
+}
+
+/// Lines "`key` ×count", the most frequent first
+fn count_lines(counts: &BTreeMap<String, usize>) -> String {
+    let mut counts = counts.iter().collect::<Vec<(&String, &usize)>>();
+    counts.sort_by(|a, b| b.1.cmp(a.1));
+
+    let mut lines = counts
+        .iter()
+        .take(MAX_ENTRIES)
+        .map(|(key, count)| format!("`{}` ×{}", key, count))
+        .collect::<Vec<String>>();
+    if counts.len() > MAX_ENTRIES {
+        lines.push(format!("… et {} autres", counts.len() - MAX_ENTRIES));
+    }
+
+    lines.join("\n")
+}
+
+/// Télécharge l'emploi du temps ADE et affiche les erreurs de lecture
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn validate(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut report = match validate_calendar().await {
+        Ok(report) => report,
+        Err(err) => {
+            ctx.say(format!("❌ {}", err)).await?;
+            return Ok(());
+        }
+    };
+
+    // "Invalid start date: 10/10/2023 14:00" is counted as "Invalid start date"
+    let mut reasons: BTreeMap<String, usize> = BTreeMap::new();
+    for (_, reason) in &report.skipped {
+        let kind = reason.split(':').next().unwrap_or(reason).to_string();
+        *reasons.entry(kind).or_default() += 1;
+    }
+
+    let mut unknown_groups: BTreeMap<String, usize> = BTreeMap::new();
+    let mut unknown_departments: BTreeMap<String, usize> = BTreeMap::new();
+    for (group, count) in apply_group_aliases(&mut report.events) {
+        match unknown_department(&group) {
+            Some(department) => *unknown_departments.entry(department).or_default() += count,
+            None => {
+                unknown_groups.insert(group, count);
+            }
+        }
+    }
+    let aliases = ctx.data().db.get_group_aliases()?;
+    let conflicts = conflict_lines(&report.events);
+
+    let healthy = report.skipped.is_empty() && unknown_groups.is_empty() && conflicts.is_empty();
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title("Validation de l'emploi du temps")
+                .description(format!(
+                    "{} événements lus, {} ignorés",
+                    report.events.len(),
+                    report.skipped.len()
+                ))
+                .color(if healthy {
+                    Colour::DARK_GREEN
+                } else {
+                    Colour::ORANGE
+                });
+            if !reasons.is_empty() {
+                let examples = report
+                    .skipped
+                    .iter()
+                    .take(MAX_EXAMPLES)
+                    .map(|(id, reason)| {
+                        truncate(&format!("{}: {}", id, reason), MAX_EXAMPLE_LENGTH)
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                e.field("Événements ignorés", count_lines(&reasons), false)
+                    .field("Exemples", format!("```\n{}\n```", examples), false);
+            }
+            if !unknown_groups.is_empty() {
+                e.field(
+                    "Groupes inconnus",
+                    format!(
+                        "{}\nAssociez-les à un groupe avec /groupes associer",
+                        count_lines(&unknown_groups)
+                    ),
+                    false,
+                );
+            }
+            if !aliases.is_empty() {
+                let lines = aliases
+                    .iter()
+                    .take(MAX_ENTRIES)
+                    .map(|(name, group)| format!("`{}` → {}", name, group))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                e.field("Groupes associés", lines, false);
+            }
+            if !conflicts.is_empty() {
+                let mut lines = conflicts
+                    .iter()
+                    .take(MAX_ENTRIES)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if conflicts.len() > MAX_ENTRIES {
+                    lines.push(format!("… et {} autres", conflicts.len() - MAX_ENTRIES));
+                }
+                e.field("⚠️ Cours qui se chevauchent", lines.join("\n"), false);
+            }
+            if !unknown_departments.is_empty() {
+                e.field(
+                    "Départements non pris en charge",
+                    count_lines(&unknown_departments),
+                    false,
+                );
+            }
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
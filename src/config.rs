@@ -0,0 +1,259 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use agenda_bot::calendar::{self, Event, GroupGrammar, GroupRemap, SecondaryFeed};
+use chrono::{NaiveDate, NaiveTime};
+use lazy_static::lazy_static;
+use poise::serenity_prelude::ChannelId;
+use regex::Regex;
+use serde::Deserialize;
+
+const DEFAULT_EXAM_KEYWORDS: &[&str] = &["eval", "moodle"];
+const DEFAULT_ANNOUNCEMENT_CHANNEL: ChannelId = ChannelId(1157420627901292704);
+/// Announcements are posted at 7:00 unless the config or /config heure says otherwise
+const DEFAULT_ANNOUNCEMENT_HOUR: u32 = 7;
+
+lazy_static! {
+    static ref CONFIG_PATH: String =
+        std::env::var("CONFIG_PATH").unwrap_or_else(|_| "config.toml".to_string());
+    static ref CONFIG: RwLock<Arc<Config>> =
+        RwLock::new(Arc::new(load().expect("Failed to load config!")));
+}
+
+/// Settings read from `config.toml`, every field is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// ADE export, the `CALENDAR_URL` environment variable when unset
+    pub calendar_url: Option<String>,
+    /// Channel of the daily announcements, change notifications and exam reminders
+    pub announcement_channel: Option<u64>,
+    /// Time of the daily announcements (ex: `"07:30"`), the guilds can change it with /config heure
+    pub announcement_time: Option<NaiveTime>,
+    /// Any date in a "Semaine A", enables the A/B indicator in embed titles
+    pub week_a_anchor: Option<NaiveDate>,
+    /// Vacation periods, detected from gaps in the calendar when empty
+    pub holidays: Vec<Holiday>,
+    /// Channel receiving the reports sent with /feedback
+    pub feedback_channel: Option<u64>,
+    /// Extra calendars (BDE, sports association...) merged into everyone's timetable
+    pub feeds: Vec<Feed>,
+    /// Naming scheme of the groups, `1-INFO-32` by default
+    pub group_grammar: GroupGrammar,
+    /// Regexes matched against event summaries to detect evaluations, `eval` and `moodle` when
+    /// unset
+    pub exam_keywords: Option<Vec<String>>,
+    #[serde(skip)]
+    exam_patterns: Vec<Regex>,
+    /// Group whose next class is shown in the bot's presence (ex: `1-INFO-32`)
+    pub presence_group: Option<String>,
+    /// Sites of multi-campus departments, recognized from the room names
+    pub campuses: Vec<Campus>,
+    /// Where the rooms are, shown with /ou and linked from the events
+    pub rooms: Vec<Room>,
+    /// Groups renamed at semester changes, applied to saved groups, subscriptions and roles
+    pub group_remaps: Vec<GroupRemap>,
+    /// Emoji shown before the subjects, by resource code (ex: `R3.04`)
+    pub subject_emojis: HashMap<String, String>,
+    /// Calendars published by teachers, their free slots are shown by /dispo
+    pub teachers: Vec<Teacher>,
+    /// Groups posted by the daily announcements, the ones with a role in the guild when empty
+    pub announced_promos: Vec<String>,
+}
+
+impl Config {
+    pub fn announcement_channel(&self) -> ChannelId {
+        self.announcement_channel
+            .map(ChannelId)
+            .unwrap_or(DEFAULT_ANNOUNCEMENT_CHANNEL)
+    }
+
+    pub fn announcement_time(&self) -> NaiveTime {
+        self.announcement_time
+            .unwrap_or_else(|| NaiveTime::from_hms_opt(DEFAULT_ANNOUNCEMENT_HOUR, 0, 0).unwrap())
+    }
+
+    pub fn is_exam(&self, evt: &Event) -> bool {
+        self.exam_patterns.iter().any(|p| p.is_match(&evt.summary))
+    }
+
+    /// Lesson of the event prefixed with the emoji of its resource code, when it has one
+    pub fn subject_label(&self, evt: &Event) -> String {
+        match self.subject_emojis.get(evt.lesson_code()) {
+            Some(emoji) => format!("{} {}", emoji, evt.lesson),
+            None => evt.lesson.clone(),
+        }
+    }
+
+    /// Campus of the room, `None` when no configured prefix matches
+    pub fn campus_of(&self, location: &str) -> Option<&str> {
+        let location = location.to_lowercase();
+        self.campuses
+            .iter()
+            .find(|c| {
+                c.prefixes
+                    .iter()
+                    .any(|p| location.starts_with(&p.to_lowercase()))
+            })
+            .map(|c| c.name.as_str())
+    }
+
+    pub fn secondary_feeds(&self) -> Vec<SecondaryFeed> {
+        self.feeds
+            .iter()
+            .map(|f| SecondaryFeed {
+                category: f.category.clone(),
+                url: f.url.clone(),
+            })
+            .collect()
+    }
+
+    /// Whether `date` is in one of the configured vacation periods
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays
+            .iter()
+            .any(|h| h.start <= date && date <= h.end)
+    }
+
+    /// Registered room of an event location, one of several comma-separated rooms works too
+    pub fn room_of(&self, location: &str) -> Option<&Room> {
+        location
+            .split(',')
+            .map(|l| l.trim())
+            .find_map(|l| self.rooms.iter().find(|r| r.name.eq_ignore_ascii_case(l)))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Holiday {
+    pub name: String,
+    pub start: NaiveDate,
+    /// Last day of the vacation (inclusive)
+    pub end: NaiveDate,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Campus {
+    pub name: String,
+    /// Beginnings of the room names of this campus (ex: `LAN-`)
+    pub prefixes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Room {
+    /// As written in ADE (ex: `B205`)
+    pub name: String,
+    pub building: Option<String>,
+    pub floor: Option<i32>,
+    /// Link to a map or a plan of the building
+    pub map: Option<String>,
+    /// Picture of the plan, shown by /ou
+    pub image: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Feed {
+    /// Shown next to the events and used to hide them with /prefs
+    pub category: String,
+    pub url: String,
+    pub emoji: String,
+    /// `#RRGGBB`
+    pub colour: Option<String>,
+}
+
+impl Feed {
+    pub fn colour(&self) -> Option<u32> {
+        let colour = self.colour.as_ref()?;
+        u32::from_str_radix(colour.trim_start_matches('#'), 16).ok()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Teacher {
+    pub name: String,
+    pub url: String,
+}
+
+/// Feed of the category an event comes from, if it comes from a secondary feed
+pub fn feed(category: &str) -> Option<Feed> {
+    get().feeds.iter().find(|f| f.category == category).cloned()
+}
+
+/// Configured campus with this name, ignoring case
+pub fn campus(name: &str) -> Option<Campus> {
+    get()
+        .campuses
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+/// Configured teacher with this name, ignoring case
+pub fn teacher(name: &str) -> Option<Teacher> {
+    get()
+        .teachers
+        .iter()
+        .find(|t| t.name.to_lowercase() == name.trim().to_lowercase())
+        .cloned()
+}
+
+fn load() -> Result<Config, String> {
+    let mut config: Config = match std::fs::read_to_string(CONFIG_PATH.as_str()) {
+        Ok(content) => toml::from_str(&content).map_err(|e| format!("{}", e))?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Config::default(),
+        Err(err) => return Err(format!("{}", err)),
+    };
+
+    let keywords: Vec<&str> = match &config.exam_keywords {
+        Some(keywords) => keywords.iter().map(|k| k.as_str()).collect(),
+        None => DEFAULT_EXAM_KEYWORDS.to_vec(),
+    };
+    config.exam_patterns = keywords
+        .into_iter()
+        .map(Regex::new)
+        .collect::<Result<Vec<Regex>, regex::Error>>()
+        .map_err(|e| format!("Invalid exam keyword: {}", e))?;
+
+    Ok(config)
+}
+
+pub fn get() -> Arc<Config> {
+    CONFIG.read().expect("Failed to lock config!").clone()
+}
+
+/// Reads the config file again and applies it, the current config is kept when the file is invalid
+pub fn reload() -> Result<(), String> {
+    let config = load()?;
+    calendar::set_group_grammar(&config.group_grammar)?;
+    if let Err(err) = calendar::set_group_remaps(&config.group_remaps) {
+        calendar::set_group_grammar(&get().group_grammar)?;
+        return Err(err);
+    }
+    calendar::set_secondary_feeds(config.secondary_feeds());
+    calendar::set_calendar_url(config.calendar_url.clone());
+
+    *CONFIG.write().expect("Failed to lock config!") = Arc::new(config);
+    Ok(())
+}
+
+/// Reloads the config when the process receives SIGHUP (`kill -HUP`, `docker kill -s HUP`)
+#[cfg(unix)]
+pub async fn reload_on_sighup() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(err) => {
+            println!("Failed to listen for SIGHUP: {}", err);
+            return;
+        }
+    };
+    while hangups.recv().await.is_some() {
+        match reload() {
+            Ok(()) => println!("Config reloaded"),
+            Err(err) => println!("Failed to reload config: {}", err),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::HashSet;
+
+use crate::calendar::Event;
+
+/// Pairs of classes of a timetable that overlap in different rooms, a frequent ADE mistake.
+/// `events` are the ones of a single promo sorted by start, as returned by `sort_events`.
+/// Cancelled classes and secondary feed events, which are optional, don't conflict.
+pub fn find_conflicts(events: &[Event]) -> Vec<(&Event, &Event)> {
+    let classes: Vec<&Event> = events
+        .iter()
+        .filter(|e| !e.cancelled && e.category.is_none())
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (i, first) in classes.iter().enumerate() {
+        for second in classes[i + 1..].iter().take_while(|e| e.start < first.end) {
+            if first.location != second.location {
+                conflicts.push((*first, *second));
+            }
+        }
+    }
+
+    conflicts
+}
+
+/// Uids of the events of `events` that are part of a conflict
+pub fn conflicting_uids(events: &[Event]) -> HashSet<&str> {
+    find_conflicts(events)
+        .into_iter()
+        .flat_map(|(a, b)| [a.uid.as_str(), b.uid.as_str()])
+        .collect()
+}
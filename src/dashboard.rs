@@ -0,0 +1,564 @@
+use std::sync::Arc;
+
+use agenda_bot::dates::short_label;
+use axum::{
+    extract::{Form, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Router,
+};
+use chrono::{Datelike, Duration, Local, NaiveDate, Timelike, Utc, Weekday};
+use poise::serenity_prelude::{Cache, GuildId, RoleId, UserId};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::Deserialize;
+
+use crate::{
+    calendar::{
+        get_events, parse_promo_name, parse_role_name, Event, EventType, GroupHierarchy, Promo,
+    },
+    config,
+    db::{DashboardSession, Database},
+    embed::{get_visible_events, EmbedOptions},
+};
+
+/// Hours covered by the grid, classes outside of it are clamped to its edges
+const GRID_START_HOUR: u32 = 8;
+const GRID_END_HOUR: u32 = 19;
+const PIXELS_PER_HOUR: u32 = 60;
+/// Info screens are left unattended, the page reloads itself to follow the changes
+const REFRESH_SECONDS: u32 = 600;
+const SESSION_COOKIE: &str = "session";
+/// Random value round-tripped through Discord to make sure the login was started here
+const STATE_COOKIE: &str = "oauth_state";
+const SESSION_DAYS: i64 = 30;
+const DISCORD_API: &str = "https://discord.com/api/v10";
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 1em; background: #f4f4f6; }
+form { margin-bottom: 1em; }
+nav { margin-bottom: 1em; }
+.week { display: flex; gap: 4px; }
+.day { flex: 1; min-width: 0; }
+.day h2 { font-size: 1em; text-align: center; margin: 0 0 4px; }
+.slots { position: relative; background: #fff; border-radius: 4px; }
+.event { position: absolute; left: 2px; right: 2px; overflow: hidden; border-radius: 4px;
+    padding: 2px 4px; font-size: 0.8em; box-sizing: border-box; border-left: 4px solid #555; }
+.CM { background: #fde2c8; } .TD { background: #cfe8fc; } .TP { background: #d5f5d5; }
+.OTHER { background: #e6e0f8; } .cancelled { opacity: 0.5; text-decoration: line-through; }
+";
+
+/// Discord application used to log in, set with `DISCORD_CLIENT_ID`, `DISCORD_CLIENT_SECRET`
+/// and `DASHBOARD_URL`. Without it the dashboard only shows the timetables.
+struct OAuth {
+    client_id: String,
+    client_secret: String,
+    /// Public URL of the dashboard, without the trailing slash
+    url: String,
+}
+
+impl OAuth {
+    fn from_env() -> Option<OAuth> {
+        Some(OAuth {
+            client_id: std::env::var("DISCORD_CLIENT_ID").ok()?,
+            client_secret: std::env::var("DISCORD_CLIENT_SECRET").ok()?,
+            url: std::env::var("DASHBOARD_URL")
+                .ok()?
+                .trim_end_matches('/')
+                .to_string(),
+        })
+    }
+
+    fn redirect_uri(&self) -> String {
+        format!("{}/callback", self.url)
+    }
+
+    /// Cookies are only sent over HTTPS when the dashboard is served with it
+    fn cookie(&self, name: &str, value: &str, max_age: i64) -> String {
+        let secure = if self.url.starts_with("https://") {
+            "; Secure"
+        } else {
+            ""
+        };
+        format!(
+            "{}={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{}",
+            name, value, max_age, secure
+        )
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    db: Database,
+    cache: Arc<Cache>,
+    oauth: Option<Arc<OAuth>>,
+}
+
+#[derive(Deserialize)]
+struct TimetableQuery {
+    group: Option<String>,
+    date: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+struct CallbackQuery {
+    code: String,
+    state: String,
+}
+
+#[derive(Deserialize)]
+struct SubscriptionForm {
+    actif: bool,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct DiscordUser {
+    id: String,
+    username: String,
+    global_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GuildMember {
+    roles: Vec<String>,
+}
+
+/// Pages of the dashboard, they read the same cached calendar as the bot
+pub fn router(db: Database, cache: Arc<Cache>) -> Router {
+    let oauth = OAuth::from_env().map(Arc::new);
+    if oauth.is_none() {
+        println!("Dashboard login disabled, the Discord OAuth variables aren't set");
+    }
+
+    Router::new()
+        .route("/", get(timetable))
+        .route("/login", get(login))
+        .route("/callback", get(callback))
+        .route("/logout", get(logout))
+        .route("/rappels", post(set_subscription))
+        .with_state(AppState { db, cache, oauth })
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn urlencode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn monday_of(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+fn random_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+fn get_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(';'))
+        .filter_map(|c| c.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Cookie token and session of the logged in user
+fn get_session(state: &AppState, headers: &HeaderMap) -> Option<(String, DashboardSession)> {
+    let token = get_cookie(headers, SESSION_COOKIE)?;
+    match state.db.get_dashboard_session(&token) {
+        Ok(session) => Some((token, session?)),
+        Err(err) => {
+            println!("Failed to read dashboard session: {}", err);
+            None
+        }
+    }
+}
+
+/// Group of the user's roles, then the one saved with /setgroup
+fn own_promo(state: &AppState, session: &DashboardSession) -> Option<Promo> {
+    session
+        .promo
+        .clone()
+        .or_else(|| state.db.get_user_group(session.user).ok().flatten())
+}
+
+fn error_page(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Html(page("", &format!("<p>❌ {}</p>", escape(message)))),
+    )
+        .into_response()
+}
+
+async fn timetable(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TimetableQuery>,
+) -> Response {
+    let session = get_session(&state, &headers).map(|(_, s)| s);
+    let date = query.date.unwrap_or_else(|| Local::now().date_naive());
+    let monday = monday_of(date);
+
+    let mut groups: Vec<String> = match get_events().await {
+        Ok(events) => GroupHierarchy::from_events(&events)
+            .all()
+            .iter()
+            .map(|p| p.to_string())
+            .collect(),
+        Err(err) => return error_page(StatusCode::BAD_GATEWAY, &err),
+    };
+    groups.sort();
+    let group = query
+        .group
+        .or_else(|| Some(own_promo(&state, session.as_ref()?)?.to_string()))
+        .or_else(|| groups.first().cloned());
+
+    let options = groups
+        .iter()
+        .map(|g| {
+            let selected = if Some(g) == group.as_ref() {
+                " selected"
+            } else {
+                ""
+            };
+            format!("<option{}>{}</option>", selected, escape(g))
+        })
+        .collect::<String>();
+    let group_param = urlencode(group.as_deref().unwrap_or_default());
+    let form = format!(
+        "<form><select name=\"group\">{}</select> <input type=\"date\" name=\"date\" value=\"{}\"> \
+         <button>Afficher</button> <a href=\"?group={}&amp;date={}\">◀</a> \
+         <a href=\"?group={}&amp;date={}\">▶</a></form>",
+        options,
+        date.format("%Y-%m-%d"),
+        group_param,
+        (monday - Duration::days(7)).format("%Y-%m-%d"),
+        group_param,
+        (monday + Duration::days(7)).format("%Y-%m-%d"),
+    );
+    let nav = nav(&state, session.as_ref());
+
+    let Some(promo) = group.as_deref().and_then(parse_promo_name) else {
+        return Html(page(&nav, &format!("{}<p>Groupe inconnu</p>", form))).into_response();
+    };
+    // logged in users get the categories and campus of their /prefs
+    let options = match &session {
+        Some(session) => match state.db.get_user_prefs(session.user) {
+            Ok(prefs) => EmbedOptions::default().with_prefs(&prefs),
+            Err(err) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, &err.to_string()),
+        },
+        None => EmbedOptions::default(),
+    };
+    let events = match get_visible_events(monday, monday + Duration::days(7), &options).await {
+        Ok(mut events) => events.remove(&promo).unwrap_or_default(),
+        Err(err) => return error_page(StatusCode::BAD_GATEWAY, &err),
+    };
+
+    let subscription = match &session {
+        Some(session) => subscription_form(&state, session),
+        None => String::new(),
+    };
+    Html(page(
+        &nav,
+        &format!(
+            "{}<h1>{} — semaine du {}</h1>{}{}",
+            form,
+            escape(&promo.to_string()),
+            monday.format("%d/%m/%Y"),
+            week_grid(monday, &events),
+            subscription
+        ),
+    ))
+    .into_response()
+}
+
+fn nav(state: &AppState, session: Option<&DashboardSession>) -> String {
+    match (session, &state.oauth) {
+        (Some(session), _) => format!(
+            "<nav>Connecté en tant que {} · <a href=\"/\">Mon emploi du temps</a> · \
+             <a href=\"/logout\">Déconnexion</a></nav>",
+            escape(&session.name)
+        ),
+        (None, Some(_)) => "<nav><a href=\"/login\">Connexion avec Discord</a></nav>".to_string(),
+        (None, None) => String::new(),
+    }
+}
+
+/// Exam reminders by DM, the same subscription as /rappels
+fn subscription_form(state: &AppState, session: &DashboardSession) -> String {
+    let subscription = state
+        .db
+        .get_exam_subscription(session.user)
+        .unwrap_or_else(|err| {
+            println!("Failed to read exam subscription: {}", err);
+            None
+        });
+
+    let (status, actif, button) = match subscription {
+        Some(promo) => (
+            format!("Activés pour {}", escape(&promo.to_string())),
+            false,
+            "Désactiver",
+        ),
+        None => ("Désactivés".to_string(), true, "Activer"),
+    };
+    format!(
+        "<h2>Rappels d'évaluations</h2><form method=\"post\" action=\"/rappels\">{} \
+         <input type=\"hidden\" name=\"actif\" value=\"{}\"><button>{}</button></form>",
+        status, actif, button
+    )
+}
+
+async fn set_subscription(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Form<SubscriptionForm>,
+) -> Response {
+    let Some((_, session)) = get_session(&state, &headers) else {
+        return Redirect::to("/login").into_response();
+    };
+
+    let promo = if form.actif {
+        match own_promo(&state, &session) {
+            Some(promo) => Some(promo),
+            None => {
+                return error_page(
+                    StatusCode::BAD_REQUEST,
+                    "Aucun groupe trouvé, utilisez /setgroup sur Discord",
+                )
+            }
+        }
+    } else {
+        None
+    };
+    if let Err(err) = state.db.set_exam_subscription(session.user, promo.as_ref()) {
+        return error_page(StatusCode::INTERNAL_SERVER_ERROR, &err.to_string());
+    }
+
+    Redirect::to("/").into_response()
+}
+
+async fn login(State(state): State<AppState>) -> Response {
+    let Some(oauth) = &state.oauth else {
+        return error_page(StatusCode::NOT_FOUND, "Connexion désactivée");
+    };
+
+    let csrf = random_token();
+    let url = format!(
+        "https://discord.com/oauth2/authorize?response_type=code&client_id={}\
+         &scope=identify%20guilds.members.read&redirect_uri={}&state={}",
+        urlencode(&oauth.client_id),
+        urlencode(&oauth.redirect_uri()),
+        csrf
+    );
+    (
+        [(header::SET_COOKIE, oauth.cookie(STATE_COOKIE, &csrf, 600))],
+        Redirect::to(&url),
+    )
+        .into_response()
+}
+
+async fn callback(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CallbackQuery>,
+) -> Response {
+    let Some(oauth) = &state.oauth else {
+        return error_page(StatusCode::NOT_FOUND, "Connexion désactivée");
+    };
+    if get_cookie(&headers, STATE_COOKIE).as_deref() != Some(query.state.as_str()) {
+        return error_page(StatusCode::BAD_REQUEST, "Connexion expirée, réessayez");
+    }
+
+    let session = match fetch_session(&state, oauth, &query.code).await {
+        Ok(session) => session,
+        Err(err) => {
+            println!("Dashboard login failed: {}", err);
+            return error_page(StatusCode::BAD_GATEWAY, "Connexion avec Discord impossible");
+        }
+    };
+    let token = random_token();
+    let expires_at = Utc::now() + Duration::days(SESSION_DAYS);
+    if let Err(err) = state.db.add_dashboard_session(&token, &session, expires_at) {
+        return error_page(StatusCode::INTERNAL_SERVER_ERROR, &err.to_string());
+    }
+
+    (
+        [
+            (
+                header::SET_COOKIE,
+                oauth.cookie(SESSION_COOKIE, &token, SESSION_DAYS * 24 * 60 * 60),
+            ),
+            (header::SET_COOKIE, oauth.cookie(STATE_COOKIE, "", 0)),
+        ],
+        Redirect::to("/"),
+    )
+        .into_response()
+}
+
+/// Exchanges the OAuth code for the user and the group of their roles in the bot's guilds
+async fn fetch_session(
+    state: &AppState,
+    oauth: &OAuth,
+    code: &str,
+) -> Result<DashboardSession, crate::Error> {
+    let client = reqwest::Client::new();
+    let redirect_uri = oauth.redirect_uri();
+    let response = client
+        .post(format!("{}/oauth2/token", DISCORD_API))
+        .form(&[
+            ("client_id", oauth.client_id.as_str()),
+            ("client_secret", oauth.client_secret.as_str()),
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri.as_str()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?;
+    let token: TokenResponse = serde_json::from_str(&response.text().await?)?;
+    let get = |path: String| {
+        client
+            .get(format!("{}{}", DISCORD_API, path))
+            .bearer_auth(&token.access_token)
+            .send()
+    };
+
+    let response = get("/users/@me".to_string()).await?.error_for_status()?;
+    let user: DiscordUser = serde_json::from_str(&response.text().await?)?;
+    let mut promo = None;
+    for guild in state.cache.guilds() {
+        let response = get(format!("/users/@me/guilds/{}/member", guild)).await?;
+        // not a member of this guild
+        if !response.status().is_success() {
+            continue;
+        }
+        let member: GuildMember = serde_json::from_str(&response.text().await?)?;
+        promo = member_promo(&state.cache, guild, &member.roles);
+        if promo.is_some() {
+            break;
+        }
+    }
+
+    Ok(DashboardSession {
+        user: UserId(user.id.parse()?),
+        name: user.global_name.unwrap_or(user.username),
+        promo,
+    })
+}
+
+/// Sous-groupe of the member's roles, like `get_user_promo` does for commands
+fn member_promo(cache: &Cache, guild: GuildId, roles: &[String]) -> Option<Promo> {
+    let guild_roles = cache.guild_roles(guild)?;
+    roles
+        .iter()
+        .filter_map(|id| guild_roles.get(&RoleId(id.parse().ok()?)))
+        .filter_map(|r| parse_role_name(&r.name))
+        .find(|p| p.group >= 10)
+}
+
+async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some((token, _)) = get_session(&state, &headers) {
+        if let Err(err) = state.db.remove_dashboard_session(&token) {
+            println!("Failed to remove dashboard session: {}", err);
+        }
+    }
+    let cookie = match &state.oauth {
+        Some(oauth) => oauth.cookie(SESSION_COOKIE, "", 0),
+        None => format!("{}=; Path=/; Max-Age=0", SESSION_COOKIE),
+    };
+
+    ([(header::SET_COOKIE, cookie)], Redirect::to("/")).into_response()
+}
+
+fn page(nav: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html lang=\"fr\"><head><meta charset=\"utf-8\">\
+         <meta http-equiv=\"refresh\" content=\"{}\"><title>Emploi du temps</title>\
+         <style>{}</style></head><body>{}{}</body></html>",
+        REFRESH_SECONDS, STYLE, nav, body
+    )
+}
+
+/// One column per weekday, and per weekend day that has events
+fn week_grid(monday: NaiveDate, events: &[Event]) -> String {
+    let height = (GRID_END_HOUR - GRID_START_HOUR) * PIXELS_PER_HOUR;
+    let mut grid = String::from("<div class=\"week\">");
+    for day in monday.iter_days().take(7) {
+        let day_events: Vec<&Event> = events
+            .iter()
+            .filter(|e| e.start.date_naive() == day)
+            .collect();
+        let weekend = matches!(day.weekday(), Weekday::Sat | Weekday::Sun);
+        if weekend && day_events.is_empty() {
+            continue;
+        }
+
+        grid.push_str(&format!(
+            "<div class=\"day\"><h2>{}</h2><div class=\"slots\" style=\"height: {}px\">",
+            short_label(day),
+            height
+        ));
+        for evt in day_events {
+            grid.push_str(&event_block(evt));
+        }
+        grid.push_str("</div></div>");
+    }
+    grid.push_str("</div>");
+
+    grid
+}
+
+/// Pixels from the top of the grid to `hour:minute`
+fn offset(hour: u32, minute: u32) -> u32 {
+    let minutes = (hour * 60 + minute).clamp(GRID_START_HOUR * 60, GRID_END_HOUR * 60);
+    (minutes - GRID_START_HOUR * 60) * PIXELS_PER_HOUR / 60
+}
+
+fn event_block(evt: &Event) -> String {
+    let top = offset(evt.start.hour(), evt.start.minute());
+    let bottom = offset(evt.end.hour(), evt.end.minute());
+    let kind = match evt.event_type {
+        EventType::CM => "CM",
+        EventType::TD => "TD",
+        EventType::TP => "TP",
+        EventType::OTHER => "OTHER",
+    };
+    let cancelled = if evt.cancelled { " cancelled" } else { "" };
+
+    format!(
+        "<div class=\"event {}{}\" style=\"top: {}px; height: {}px\">\
+         <b>{} - {}</b><br>{}<br>{}</div>",
+        kind,
+        cancelled,
+        top,
+        bottom.saturating_sub(top),
+        evt.start.format("%H:%M"),
+        evt.end.format("%H:%M"),
+        escape(&config::get().subject_label(evt)),
+        escape(&evt.location)
+    )
+}
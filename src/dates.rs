@@ -0,0 +1,180 @@
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveTime, TimeZone, Weekday};
+
+const WEEKDAYS: &[(&str, Weekday)] = &[
+    ("lundi", Weekday::Mon),
+    ("mardi", Weekday::Tue),
+    ("mercredi", Weekday::Wed),
+    ("jeudi", Weekday::Thu),
+    ("vendredi", Weekday::Fri),
+    ("samedi", Weekday::Sat),
+    ("dimanche", Weekday::Sun),
+    ("monday", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+];
+
+const SHORT_WEEKDAYS: [&str; 7] = ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."];
+const SHORT_MONTHS: [&str; 12] = [
+    "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+    "déc.",
+];
+
+/// Parses the date expressions accepted by commands, in French or English: `demain`,
+/// `lundi prochain`, `dans 3 jours`, `next week`, `14/11`, `14/11/2023`, `2023-11-14`...
+pub fn parse_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = normalize(input);
+    let words = input.split_whitespace().collect::<Vec<&str>>();
+
+    match words.as_slice() {
+        ["aujourd'hui" | "auj" | "today"] => Some(today),
+        ["demain" | "tomorrow"] => Some(today + Duration::days(1)),
+        ["apres-demain"] | ["apres", "demain"] => Some(today + Duration::days(2)),
+        ["hier" | "yesterday"] => Some(today - Duration::days(1)),
+        ["avant-hier"] => Some(today - Duration::days(2)),
+        ["semaine", "prochaine"] | ["next", "week"] => Some(monday_of(today) + Duration::days(7)),
+        ["cette", "semaine"] | ["this", "week"] => Some(monday_of(today)),
+        ["dans" | "in", amount, unit] => relative(today, amount, unit),
+        [day] if weekday(day).is_some() => Some(next_weekday(today, weekday(day)?, false)),
+        [day, "prochain"] | ["next", day] => Some(next_weekday(today, weekday(day)?, true)),
+        [date] => parse_numeric_date(date, today),
+        _ => None,
+    }
+}
+
+fn normalize(input: &str) -> String {
+    input
+        .trim()
+        .to_lowercase()
+        .replace(['é', 'è', 'ê'], "e")
+        .replace(['à', 'â'], "a")
+        .replace('’', "'")
+}
+
+fn weekday(name: &str) -> Option<Weekday> {
+    WEEKDAYS.iter().find(|(n, _)| *n == name).map(|(_, d)| *d)
+}
+
+fn monday_of(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+/// Next `weekday`, today included unless `strictly_after`
+fn next_weekday(today: NaiveDate, weekday: Weekday, strictly_after: bool) -> NaiveDate {
+    let mut days = (7 + weekday.num_days_from_monday() as i64
+        - today.weekday().num_days_from_monday() as i64)
+        % 7;
+    if days == 0 && strictly_after {
+        days = 7;
+    }
+
+    today + Duration::days(days)
+}
+
+/// `dans 3 jours`, `in 2 weeks`, `dans une semaine`
+fn relative(today: NaiveDate, amount: &str, unit: &str) -> Option<NaiveDate> {
+    let amount = match amount {
+        "un" | "une" | "a" | "one" => 1,
+        amount => amount.parse::<i64>().ok()?,
+    };
+    let days = match unit {
+        "jour" | "jours" | "day" | "days" => amount,
+        "semaine" | "semaines" | "week" | "weeks" => amount.checked_mul(7)?,
+        _ => return None,
+    };
+
+    today.checked_add_signed(Duration::try_days(days)?)
+}
+
+/// `14/11/2023`, `14/11/23`, `2023-11-14` or `14/11`. Without a year the closest 14/11 is used,
+/// so November dates typed in January stay in the current school year.
+fn parse_numeric_date(date: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let parts = date.split('/').collect::<Vec<&str>>();
+    match parts.as_slice() {
+        // %Y would read "23" as the year 23
+        [_, _, year] if year.len() == 2 => NaiveDate::parse_from_str(date, "%d/%m/%y").ok(),
+        [_, _, _] => NaiveDate::parse_from_str(date, "%d/%m/%Y").ok(),
+        [day, month] => closest_day_month(day.parse().ok()?, month.parse().ok()?, today),
+        _ => None,
+    }
+}
+
+fn closest_day_month(day: u32, month: u32, today: NaiveDate) -> Option<NaiveDate> {
+    let this_year = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+    if this_year < today - Duration::days(183) {
+        NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+    } else if this_year > today + Duration::days(183) {
+        NaiveDate::from_ymd_opt(today.year() - 1, month, day)
+    } else {
+        Some(this_year)
+    }
+}
+
+/// Short French label of a day, "jeu. 10 oct."
+pub fn short_label(date: NaiveDate) -> String {
+    format!(
+        "{} {} {}",
+        SHORT_WEEKDAYS[date.weekday().num_days_from_monday() as usize],
+        date.day(),
+        SHORT_MONTHS[date.month0() as usize]
+    )
+}
+
+/// Remaining time rounded up to the minute, "35 min", "1 h 20", "2 h"
+pub fn short_duration(duration: Duration) -> String {
+    let minutes = (duration.num_seconds().max(0) + 59) / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{} min", minutes),
+        (hours, 0) => format!("{} h", hours),
+        (hours, minutes) => format!("{} h {:02}", hours, minutes),
+    }
+}
+
+/// Time of day typed by a user: `7:30`, `07h30`, `7h`
+pub fn parse_time(input: &str) -> Option<NaiveTime> {
+    let input = input.trim().to_lowercase().replace('h', ":");
+    let input = input.strip_suffix(':').unwrap_or(&input);
+    NaiveTime::parse_from_str(input, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(&format!("{}:00", input), "%H:%M"))
+        .ok()
+}
+
+/// First time strictly after `now` at which the clock of its time zone shows `time`. When a DST
+/// change skips `time` it happens an hour later, when it repeats `time` only the first one counts.
+pub fn next_occurrence<Tz: TimeZone>(now: &DateTime<Tz>, time: NaiveTime) -> DateTime<Tz> {
+    let tz = now.timezone();
+    let mut date = now.date_naive();
+    loop {
+        let local = date.and_time(time);
+        let occurrence = match tz.from_local_datetime(&local) {
+            LocalResult::Single(occurrence) => Some(occurrence),
+            LocalResult::Ambiguous(first, _) => Some(first),
+            LocalResult::None => tz
+                .from_local_datetime(&(local + Duration::hours(1)))
+                .earliest(),
+        };
+        if let Some(occurrence) = occurrence.filter(|o| o > now) {
+            return occurrence;
+        }
+
+        date = date.succ_opt().expect("Date out of range");
+    }
+}
+
+/// First day of the semester of `date` and first day of the next one, semesters go from
+/// September to January and from February to August
+pub fn semester_bounds(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first = |year, month| NaiveDate::from_ymd_opt(year, month, 1).expect("Invalid semester");
+    match date.month() {
+        9..=12 => (first(date.year(), 9), first(date.year() + 1, 2)),
+        1 => (first(date.year() - 1, 9), first(date.year(), 2)),
+        _ => (first(date.year(), 2), first(date.year(), 9)),
+    }
+}
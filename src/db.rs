@@ -0,0 +1,1663 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Europe::Paris;
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId, ScheduledEventId, UserId};
+use rusqlite::{params, types::Type, Connection, OptionalExtension};
+
+use crate::{
+    calendar::{CustomEvent, Event, EventOverride, EventType, Promo, UploadedCalendar},
+    prefs::UserPrefs,
+};
+
+/// Applied in order, `PRAGMA user_version` records how many already ran
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS user_groups (
+        user_id INTEGER PRIMARY KEY,
+        promo TEXT NOT NULL
+    );",
+    "CREATE TABLE guild_settings (
+        guild_id INTEGER PRIMARY KEY,
+        show_gaps INTEGER NOT NULL DEFAULT 0
+    );",
+    "CREATE TABLE user_prefs (
+        user_id INTEGER PRIMARY KEY,
+        view TEXT NOT NULL,
+        ephemeral INTEGER NOT NULL,
+        hour12 INTEGER NOT NULL,
+        language TEXT NOT NULL
+    );",
+    "ALTER TABLE user_prefs ADD COLUMN timezone TEXT;",
+    "ALTER TABLE user_prefs ADD COLUMN format TEXT;",
+    "CREATE TABLE event_overrides (
+        uid TEXT PRIMARY KEY,
+        note TEXT,
+        cancelled INTEGER NOT NULL DEFAULT 0,
+        ends_at INTEGER NOT NULL
+    );",
+    "CREATE TABLE custom_events (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        title TEXT NOT NULL,
+        location TEXT NOT NULL,
+        start INTEGER NOT NULL,
+        end INTEGER NOT NULL,
+        groups TEXT NOT NULL
+    );",
+    "ALTER TABLE user_prefs ADD COLUMN hidden_categories TEXT;",
+    "CREATE TABLE exam_subscribers (
+        user_id INTEGER PRIMARY KEY,
+        promo TEXT NOT NULL
+    );
+    CREATE TABLE sent_exam_reminders (
+        uid TEXT NOT NULL,
+        days_before INTEGER NOT NULL,
+        PRIMARY KEY (uid, days_before)
+    );",
+    "ALTER TABLE guild_settings ADD COLUMN scheduled_event_promos TEXT;
+    CREATE TABLE synced_events (
+        guild_id INTEGER NOT NULL,
+        uid TEXT NOT NULL,
+        event_id INTEGER NOT NULL,
+        fingerprint TEXT NOT NULL,
+        ends_at INTEGER NOT NULL,
+        PRIMARY KEY (guild_id, uid)
+    );",
+    "ALTER TABLE guild_settings ADD COLUMN voice_category INTEGER;
+    CREATE TABLE voice_channels (
+        guild_id INTEGER NOT NULL,
+        uid TEXT NOT NULL,
+        channel_id INTEGER NOT NULL,
+        ends_at INTEGER NOT NULL,
+        PRIMARY KEY (guild_id, uid)
+    );",
+    "CREATE TABLE uploaded_calendars (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        start TEXT NOT NULL,
+        end TEXT NOT NULL,
+        replace INTEGER NOT NULL,
+        body TEXT NOT NULL
+    );",
+    "ALTER TABLE guild_settings ADD COLUMN campus TEXT;
+    ALTER TABLE user_prefs ADD COLUMN campus TEXT;",
+    "ALTER TABLE guild_settings ADD COLUMN announcement_pings INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE guild_settings ADD COLUMN quiet_start INTEGER;
+    ALTER TABLE guild_settings ADD COLUMN quiet_end INTEGER;",
+    "CREATE TABLE notification_opt_outs (
+        user_id INTEGER PRIMARY KEY
+    );",
+    "ALTER TABLE guild_settings ADD COLUMN announcement_forum INTEGER;
+    CREATE TABLE forum_posts (
+        guild_id INTEGER NOT NULL,
+        promo TEXT NOT NULL,
+        thread_id INTEGER NOT NULL,
+        PRIMARY KEY (guild_id, promo)
+    );",
+    "ALTER TABLE guild_settings ADD COLUMN announcement_cleanup TEXT;
+    ALTER TABLE guild_settings ADD COLUMN announcement_retention_days INTEGER NOT NULL DEFAULT 0;
+    CREATE TABLE announcement_messages (
+        channel_id INTEGER NOT NULL,
+        promo TEXT NOT NULL,
+        message_id INTEGER PRIMARY KEY,
+        posted_at INTEGER NOT NULL
+    );",
+    "ALTER TABLE guild_settings ADD COLUMN announcement_time TEXT;",
+    "ALTER TABLE guild_settings ADD COLUMN announce_empty_days INTEGER NOT NULL DEFAULT 0;",
+    "CREATE TABLE event_archive (
+        date TEXT NOT NULL,
+        uid TEXT NOT NULL,
+        summary TEXT NOT NULL,
+        start INTEGER NOT NULL,
+        end INTEGER NOT NULL,
+        location TEXT NOT NULL,
+        lesson TEXT NOT NULL,
+        grp TEXT NOT NULL,
+        teacher TEXT,
+        notes TEXT NOT NULL,
+        event_type TEXT NOT NULL,
+        PRIMARY KEY (date, uid)
+    );",
+    "CREATE TABLE attendance_promos (
+        guild_id INTEGER NOT NULL,
+        promo TEXT NOT NULL,
+        channel_id INTEGER NOT NULL,
+        PRIMARY KEY (guild_id, promo)
+    );
+    CREATE TABLE attendance_sessions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        guild_id INTEGER NOT NULL,
+        promo TEXT NOT NULL,
+        uid TEXT NOT NULL,
+        lesson TEXT NOT NULL,
+        starts_at INTEGER NOT NULL,
+        closes_at INTEGER NOT NULL,
+        UNIQUE (guild_id, promo, uid)
+    );
+    CREATE TABLE attendance (
+        session_id INTEGER NOT NULL,
+        user_id INTEGER NOT NULL,
+        checked_at INTEGER NOT NULL,
+        PRIMARY KEY (session_id, user_id)
+    );",
+    "CREATE TABLE feed_snapshots (
+        taken_at INTEGER NOT NULL,
+        uid TEXT NOT NULL,
+        summary TEXT NOT NULL,
+        start INTEGER NOT NULL,
+        end INTEGER NOT NULL,
+        location TEXT NOT NULL,
+        lesson TEXT NOT NULL,
+        grp TEXT NOT NULL,
+        teacher TEXT,
+        notes TEXT NOT NULL,
+        event_type TEXT NOT NULL,
+        PRIMARY KEY (taken_at, uid)
+    );",
+    "CREATE TABLE class_reminders (
+        user_id INTEGER NOT NULL,
+        uid TEXT NOT NULL,
+        PRIMARY KEY (user_id, uid)
+    );",
+    "CREATE TABLE group_aliases (
+        name TEXT PRIMARY KEY,
+        grp TEXT NOT NULL
+    );",
+    "CREATE TABLE dashboard_sessions (
+        token TEXT PRIMARY KEY,
+        user_id INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        promo TEXT,
+        expires_at INTEGER NOT NULL
+    );",
+    "CREATE TABLE announced_days (
+        channel_id INTEGER NOT NULL,
+        promo TEXT NOT NULL,
+        date TEXT NOT NULL,
+        PRIMARY KEY (channel_id, promo)
+    );",
+    "CREATE TABLE homework (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        guild_id INTEGER NOT NULL,
+        promo TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        due TEXT NOT NULL,
+        description TEXT NOT NULL,
+        author_id INTEGER NOT NULL
+    );",
+    "CREATE TABLE applied_group_remaps (
+        since TEXT NOT NULL,
+        from_promo TEXT NOT NULL,
+        to_promo TEXT NOT NULL,
+        PRIMARY KEY (since, from_promo, to_promo)
+    );",
+    "ALTER TABLE class_reminders ADD COLUMN guild_id INTEGER;",
+];
+
+/// Discord account logged into the web dashboard
+#[cfg(feature = "dashboard")]
+#[derive(Debug, Clone)]
+pub struct DashboardSession {
+    pub user: UserId,
+    pub name: String,
+    /// Read from the roles of the user when they logged in
+    pub promo: Option<Promo>,
+}
+
+/// Reads the `uid, summary, start, end, location, lesson, grp, teacher, notes, event_type`
+/// columns shared by the archive and the snapshots
+fn event_from_row(row: &rusqlite::Row) -> rusqlite::Result<Event> {
+    let to_datetime = |timestamp: i64| {
+        Paris.from_utc_datetime(
+            &DateTime::from_timestamp(timestamp, 0)
+                .unwrap_or_default()
+                .naive_utc(),
+        )
+    };
+
+    Ok(Event {
+        uid: row.get(0)?,
+        summary: row.get(1)?,
+        start: to_datetime(row.get(2)?),
+        end: to_datetime(row.get(3)?),
+        location: row.get(4)?,
+        lesson: row.get(5)?,
+        group: row.get(6)?,
+        teacher: row.get(7)?,
+        notes: row
+            .get::<_, String>(8)?
+            .lines()
+            .map(|l| l.to_string())
+            .collect(),
+        event_type: EventType::from_code(&row.get::<_, String>(9)?),
+        admin_note: None,
+        cancelled: false,
+        category: None,
+    })
+}
+
+/// What happens to the previous daily announcements, set with /config annonces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, poise::ChoiceParameter)]
+pub enum AnnouncementCleanup {
+    #[default]
+    #[name = "garder"]
+    Keep,
+    /// The announcement of the previous day is edited instead of posting a new one, or replaced
+    /// when the new one pings since edits don't
+    #[name = "modifier"]
+    Edit,
+    /// Announcements older than the retention are deleted
+    #[name = "supprimer"]
+    Delete,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GuildSettings {
+    pub show_gaps: bool,
+    /// Promos synced to Discord Scheduled Events, disabled when empty
+    pub scheduled_event_promos: Vec<Promo>,
+    /// Category receiving a voice channel per ongoing TP/TD, disabled when unset
+    pub voice_category: Option<ChannelId>,
+    /// Only the events of this campus are shown, set with /config campus
+    pub campus: Option<String>,
+    /// Mention the role of the promo in the daily announcements
+    pub announcement_pings: bool,
+    /// Hours (start inclusive, end exclusive) during which the announcements don't ping
+    pub quiet_hours: Option<(u32, u32)>,
+    /// Forum receiving one post per promo instead of the announcement channel, set with
+    /// /config forum
+    pub announcement_forum: Option<ChannelId>,
+    pub announcement_cleanup: AnnouncementCleanup,
+    /// Days an announcement is kept with `AnnouncementCleanup::Delete`, 0 only keeps today's
+    pub announcement_retention_days: u32,
+    /// Time of the daily announcements, 7:00 when unset
+    pub announcement_time: Option<NaiveTime>,
+    /// Post "Pas de cours" for the promos of the guild without classes instead of skipping them,
+    /// except on weekends and holidays
+    pub announce_empty_days: bool,
+}
+
+impl GuildSettings {
+    /// Whether pings are silenced at `hour`, quiet hours can wrap around midnight
+    pub fn is_quiet(&self, hour: u32) -> bool {
+        match self.quiet_hours {
+            Some((start, end)) if start <= end => hour >= start && hour < end,
+            Some((start, end)) => hour >= start || hour < end,
+            None => false,
+        }
+    }
+}
+
+/// Temporary voice channel of an ongoing class
+#[derive(Debug, Clone)]
+pub struct VoiceChannel {
+    pub uid: String,
+    pub channel_id: ChannelId,
+    pub ends_at: i64,
+}
+
+/// Discord Scheduled Event created for a calendar event
+#[derive(Debug, Clone)]
+pub struct SyncedEvent {
+    pub uid: String,
+    pub event_id: ScheduledEventId,
+    /// Changes when the Discord event has to be updated
+    pub fingerprint: String,
+    pub ends_at: i64,
+}
+
+/// Check-in opened for a class by the attendance helper
+#[derive(Debug, Clone)]
+pub struct AttendanceSession {
+    pub id: i64,
+    pub promo: String,
+    pub lesson: String,
+    pub starts_at: i64,
+    /// Clicks on the check-in button are refused after this time
+    pub closes_at: i64,
+}
+
+/// Assignment recorded by a delegate with /devoirs add
+#[derive(Debug, Clone)]
+pub struct Homework {
+    pub id: i64,
+    /// Each server keeps its own homework, its delegates only manage that one
+    pub guild: GuildId,
+    pub promo: Promo,
+    /// As typed by the delegate, matched loosely against the lessons
+    pub subject: String,
+    pub due: NaiveDate,
+    pub description: String,
+    pub author: UserId,
+}
+
+/// Saved groups are renamed in the database by `apply_group_remaps`, so they're read as is
+fn parse_saved_promo(promo: &str) -> Option<Promo> {
+    promo.parse().ok()
+}
+
+fn parse_promos(promos: Option<String>) -> Vec<Promo> {
+    promos
+        .map(|p| p.split(',').filter_map(parse_saved_promo).collect())
+        .unwrap_or_default()
+}
+
+/// Persistent storage for everything that has to survive a restart
+#[derive(Clone)]
+pub struct Database {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Database {
+    pub fn open(path: &str) -> rusqlite::Result<Database> {
+        let conn = Connection::open(path)?;
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(migration)?;
+            conn.pragma_update(None, "user_version", i + 1)?;
+        }
+
+        Ok(Database {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Size of the database file in bytes
+    pub fn size(&self) -> rusqlite::Result<u64> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let page_count: u64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let page_size: u64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+
+        Ok(page_count * page_size)
+    }
+
+    /// Renames the saved groups once for every remap whose date has come. The groups saved
+    /// after that keep their name, it's already the new one.
+    pub fn apply_group_remaps(
+        &self,
+        remaps: &[(NaiveDate, Promo, Promo)],
+        today: NaiveDate,
+    ) -> rusqlite::Result<()> {
+        let mut conn = self.conn.lock().expect("Failed to lock database!");
+        let tx = conn.transaction()?;
+        for (since, from, to) in remaps.iter().filter(|(since, _, _)| *since <= today) {
+            let (since, from, to) = (since.to_string(), from.to_string(), to.to_string());
+            let new = tx.execute(
+                "INSERT OR IGNORE INTO applied_group_remaps (since, from_promo, to_promo)
+                 VALUES (?1, ?2, ?3)",
+                params![since, from, to],
+            )?;
+            if new == 0 {
+                continue;
+            }
+
+            for table in [
+                "user_groups",
+                "exam_subscribers",
+                "attendance_promos",
+                "dashboard_sessions",
+                "homework",
+            ] {
+                tx.execute(
+                    &format!(
+                        "UPDATE OR REPLACE {} SET promo = ?2 WHERE promo = ?1",
+                        table
+                    ),
+                    params![from, to],
+                )?;
+            }
+
+            let guilds = {
+                let mut stmt = tx.prepare(
+                    "SELECT guild_id, scheduled_event_promos FROM guild_settings
+                     WHERE scheduled_event_promos IS NOT NULL",
+                )?;
+                let guilds = stmt
+                    .query_map([], |row| {
+                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<rusqlite::Result<Vec<(i64, String)>>>()?;
+                guilds
+            };
+            for (guild, promos) in guilds {
+                let renamed = promos
+                    .split(',')
+                    .map(|p| if p == from { to.as_str() } else { p })
+                    .collect::<Vec<&str>>()
+                    .join(",");
+                if renamed != promos {
+                    tx.execute(
+                        "UPDATE guild_settings SET scheduled_event_promos = ?2 WHERE guild_id = ?1",
+                        params![guild, renamed],
+                    )?;
+                }
+            }
+        }
+
+        tx.commit()
+    }
+
+    pub fn get_user_group(&self, user: UserId) -> rusqlite::Result<Option<Promo>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let promo: Option<String> = conn
+            .query_row(
+                "SELECT promo FROM user_groups WHERE user_id = ?1",
+                params![user.0 as i64],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(promo.and_then(|p| parse_saved_promo(&p)))
+    }
+
+    pub fn set_user_group(&self, user: UserId, promo: &Promo) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT INTO user_groups (user_id, promo) VALUES (?1, ?2)
+             ON CONFLICT(user_id) DO UPDATE SET promo = excluded.promo",
+            params![user.0 as i64, promo.to_string()],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_guild_settings(&self, guild: GuildId) -> rusqlite::Result<GuildSettings> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let settings = conn
+            .query_row(
+                "SELECT show_gaps, scheduled_event_promos, voice_category, campus,
+                    announcement_pings, quiet_start, quiet_end, announcement_forum,
+                    announcement_cleanup, announcement_retention_days, announcement_time,
+                    announce_empty_days
+                 FROM guild_settings WHERE guild_id = ?1",
+                params![guild.0 as i64],
+                |row| {
+                    Ok(GuildSettings {
+                        show_gaps: row.get(0)?,
+                        scheduled_event_promos: parse_promos(row.get(1)?),
+                        voice_category: row.get::<_, Option<i64>>(2)?.map(|c| ChannelId(c as u64)),
+                        campus: row.get(3)?,
+                        announcement_pings: row.get(4)?,
+                        quiet_hours: match (row.get(5)?, row.get(6)?) {
+                            (Some(start), Some(end)) => Some((start, end)),
+                            _ => None,
+                        },
+                        announcement_forum: row
+                            .get::<_, Option<i64>>(7)?
+                            .map(|c| ChannelId(c as u64)),
+                        announcement_cleanup: row
+                            .get::<_, Option<String>>(8)?
+                            .and_then(|c| c.parse().ok())
+                            .unwrap_or_default(),
+                        announcement_retention_days: row.get(9)?,
+                        announcement_time: row
+                            .get::<_, Option<String>>(10)?
+                            .and_then(|t| NaiveTime::parse_from_str(&t, "%H:%M").ok()),
+                        announce_empty_days: row.get(11)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(settings.unwrap_or_default())
+    }
+
+    pub fn set_guild_settings(
+        &self,
+        guild: GuildId,
+        settings: &GuildSettings,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT INTO guild_settings (guild_id, show_gaps, scheduled_event_promos, voice_category,
+                campus, announcement_pings, quiet_start, quiet_end, announcement_forum,
+                announcement_cleanup, announcement_retention_days, announcement_time,
+                announce_empty_days)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+             ON CONFLICT(guild_id) DO UPDATE SET show_gaps = excluded.show_gaps,
+                scheduled_event_promos = excluded.scheduled_event_promos,
+                voice_category = excluded.voice_category, campus = excluded.campus,
+                announcement_pings = excluded.announcement_pings,
+                quiet_start = excluded.quiet_start, quiet_end = excluded.quiet_end,
+                announcement_forum = excluded.announcement_forum,
+                announcement_cleanup = excluded.announcement_cleanup,
+                announcement_retention_days = excluded.announcement_retention_days,
+                announcement_time = excluded.announcement_time,
+                announce_empty_days = excluded.announce_empty_days",
+            params![
+                guild.0 as i64,
+                settings.show_gaps,
+                if settings.scheduled_event_promos.is_empty() {
+                    None
+                } else {
+                    Some(
+                        settings
+                            .scheduled_event_promos
+                            .iter()
+                            .map(|p| p.to_string())
+                            .collect::<Vec<String>>()
+                            .join(","),
+                    )
+                },
+                settings.voice_category.map(|c| c.0 as i64),
+                settings.campus,
+                settings.announcement_pings,
+                settings.quiet_hours.map(|(start, _)| start),
+                settings.quiet_hours.map(|(_, end)| end),
+                settings.announcement_forum.map(|c| c.0 as i64),
+                settings.announcement_cleanup.to_string(),
+                settings.announcement_retention_days,
+                settings
+                    .announcement_time
+                    .map(|t| t.format("%H:%M").to_string()),
+                settings.announce_empty_days
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_user_prefs(&self, user: UserId) -> rusqlite::Result<UserPrefs> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let prefs = conn
+            .query_row(
+                "SELECT view, ephemeral, hour12, language, timezone, format, hidden_categories,
+                    campus
+                 FROM user_prefs WHERE user_id = ?1",
+                params![user.0 as i64],
+                |row| {
+                    Ok(UserPrefs {
+                        view: row.get::<_, String>(0)?.parse().unwrap_or_default(),
+                        ephemeral: row.get(1)?,
+                        hour12: row.get(2)?,
+                        language: row.get::<_, String>(3)?.parse().unwrap_or_default(),
+                        timezone: row
+                            .get::<_, Option<String>>(4)?
+                            .and_then(|tz| tz.parse().ok()),
+                        format: row
+                            .get::<_, Option<String>>(5)?
+                            .and_then(|f| f.parse().ok())
+                            .unwrap_or_default(),
+                        hidden_categories: row
+                            .get::<_, Option<String>>(6)?
+                            .map(|c| c.split(',').map(|c| c.to_string()).collect())
+                            .unwrap_or_default(),
+                        campus: row.get(7)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(prefs.unwrap_or_default())
+    }
+
+    pub fn set_user_prefs(&self, user: UserId, prefs: &UserPrefs) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT INTO user_prefs (user_id, view, ephemeral, hour12, language, timezone, format,
+                hidden_categories, campus)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(user_id) DO UPDATE SET view = excluded.view,
+                ephemeral = excluded.ephemeral, hour12 = excluded.hour12,
+                language = excluded.language, timezone = excluded.timezone,
+                format = excluded.format, hidden_categories = excluded.hidden_categories,
+                campus = excluded.campus",
+            params![
+                user.0 as i64,
+                prefs.view.to_string(),
+                prefs.ephemeral,
+                prefs.hour12,
+                prefs.language.to_string(),
+                prefs.timezone.map(|tz| tz.name().to_string()),
+                prefs.format.to_string(),
+                if prefs.hidden_categories.is_empty() {
+                    None
+                } else {
+                    Some(prefs.hidden_categories.join(","))
+                },
+                prefs.campus
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Overrides of the events that haven't ended yet, older ones are deleted
+    pub fn get_event_overrides(&self) -> rusqlite::Result<HashMap<String, EventOverride>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM event_overrides WHERE ends_at < ?1",
+            params![Utc::now().timestamp()],
+        )?;
+
+        let mut stmt = conn.prepare("SELECT uid, note, cancelled FROM event_overrides")?;
+        let overrides = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    EventOverride {
+                        note: row.get(1)?,
+                        cancelled: row.get(2)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<HashMap<String, EventOverride>>>()?;
+
+        Ok(overrides)
+    }
+
+    pub fn get_group_aliases(&self) -> rusqlite::Result<HashMap<String, String>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare("SELECT name, grp FROM group_aliases")?;
+        let aliases = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<HashMap<String, String>>>()?;
+
+        Ok(aliases)
+    }
+
+    /// Maps the unknown group `name` to `group`, or removes its alias
+    pub fn set_group_alias(&self, name: &str, group: Option<&str>) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        match group {
+            Some(group) => conn.execute(
+                "INSERT INTO group_aliases (name, grp) VALUES (?1, ?2)
+                 ON CONFLICT(name) DO UPDATE SET grp = excluded.grp",
+                params![name, group],
+            )?,
+            None => conn.execute("DELETE FROM group_aliases WHERE name = ?1", params![name])?,
+        };
+
+        Ok(())
+    }
+
+    pub fn set_event_override<Tz: TimeZone>(
+        &self,
+        uid: &str,
+        event_override: &EventOverride,
+        ends_at: DateTime<Tz>,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT INTO event_overrides (uid, note, cancelled, ends_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(uid) DO UPDATE SET note = excluded.note,
+                cancelled = excluded.cancelled, ends_at = excluded.ends_at",
+            params![
+                uid,
+                event_override.note,
+                event_override.cancelled,
+                ends_at.timestamp()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn remove_event_override(&self, uid: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute("DELETE FROM event_overrides WHERE uid = ?1", params![uid])?;
+
+        Ok(())
+    }
+
+    pub fn get_custom_events(&self) -> rusqlite::Result<Vec<CustomEvent>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt =
+            conn.prepare("SELECT id, title, location, start, end, groups FROM custom_events")?;
+        let to_datetime = |timestamp: i64| {
+            Paris.from_utc_datetime(
+                &DateTime::from_timestamp(timestamp, 0)
+                    .unwrap_or_default()
+                    .naive_utc(),
+            )
+        };
+
+        let events = stmt
+            .query_map([], |row| {
+                Ok(CustomEvent {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    location: row.get(2)?,
+                    start: to_datetime(row.get(3)?),
+                    end: to_datetime(row.get(4)?),
+                    groups: row
+                        .get::<_, String>(5)?
+                        .split(',')
+                        .map(|g| g.to_string())
+                        .collect(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<CustomEvent>>>()?;
+
+        Ok(events)
+    }
+
+    /// Stores a new custom event, its `id` is ignored and the assigned one is returned
+    pub fn add_custom_event(&self, event: &CustomEvent) -> rusqlite::Result<i64> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT INTO custom_events (title, location, start, end, groups)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                event.title,
+                event.location,
+                event.start.timestamp(),
+                event.end.timestamp(),
+                event.groups.join(",")
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Returns whether the event existed
+    pub fn remove_custom_event(&self, id: i64) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let removed = conn.execute("DELETE FROM custom_events WHERE id = ?1", params![id])?;
+
+        Ok(removed > 0)
+    }
+
+    /// Uploaded calendars still covering today or later, the older ones are deleted
+    pub fn get_uploaded_calendars(&self) -> rusqlite::Result<Vec<UploadedCalendar>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM uploaded_calendars WHERE end < ?1",
+            params![Local::now().date_naive().to_string()],
+        )?;
+
+        let mut stmt =
+            conn.prepare("SELECT id, name, start, end, replace, body FROM uploaded_calendars")?;
+        let to_date = |value: String| {
+            NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|err| {
+                rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(err))
+            })
+        };
+
+        let calendars = stmt
+            .query_map([], |row| {
+                Ok(UploadedCalendar {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    start: to_date(row.get(2)?)?,
+                    end: to_date(row.get(3)?)?,
+                    replace: row.get(4)?,
+                    body: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<UploadedCalendar>>>()?;
+
+        Ok(calendars)
+    }
+
+    /// Stores an uploaded calendar, its `id` is ignored and the assigned one is returned
+    pub fn add_uploaded_calendar(&self, calendar: &UploadedCalendar) -> rusqlite::Result<i64> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT INTO uploaded_calendars (name, start, end, replace, body)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                calendar.name,
+                calendar.start.to_string(),
+                calendar.end.to_string(),
+                calendar.replace,
+                calendar.body
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Returns whether the calendar existed
+    pub fn remove_uploaded_calendar(&self, id: i64) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let removed = conn.execute("DELETE FROM uploaded_calendars WHERE id = ?1", params![id])?;
+
+        Ok(removed > 0)
+    }
+
+    /// Users who don't want to be pinged by the automated posts
+    pub fn get_notification_opt_outs(&self) -> rusqlite::Result<Vec<UserId>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare("SELECT user_id FROM notification_opt_outs")?;
+        let users = stmt
+            .query_map([], |row| Ok(UserId(row.get::<_, i64>(0)? as u64)))?
+            .collect::<rusqlite::Result<Vec<UserId>>>()?;
+
+        Ok(users)
+    }
+
+    pub fn set_notifications(&self, user: UserId, enabled: bool) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        if enabled {
+            conn.execute(
+                "DELETE FROM notification_opt_outs WHERE user_id = ?1",
+                params![user.0 as i64],
+            )?;
+        } else {
+            conn.execute(
+                "INSERT OR IGNORE INTO notification_opt_outs (user_id) VALUES (?1)",
+                params![user.0 as i64],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Subscribes the user to exam reminders by DM for `promo`, unsubscribes them with `None`
+    pub fn set_exam_subscription(
+        &self,
+        user: UserId,
+        promo: Option<&Promo>,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        match promo {
+            Some(promo) => conn.execute(
+                "INSERT INTO exam_subscribers (user_id, promo) VALUES (?1, ?2)
+                 ON CONFLICT(user_id) DO UPDATE SET promo = excluded.promo",
+                params![user.0 as i64, promo.to_string()],
+            )?,
+            None => conn.execute(
+                "DELETE FROM exam_subscribers WHERE user_id = ?1",
+                params![user.0 as i64],
+            )?,
+        };
+
+        Ok(())
+    }
+
+    pub fn get_exam_subscribers(&self) -> rusqlite::Result<Vec<(UserId, Promo)>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare("SELECT user_id, promo FROM exam_subscribers")?;
+        let subscribers = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<(i64, String)>>>()?;
+
+        Ok(subscribers
+            .into_iter()
+            .filter_map(|(user, promo)| Some((UserId(user as u64), parse_saved_promo(&promo)?)))
+            .collect())
+    }
+
+    /// Whether the reminder `days_before` the event was already sent
+    pub fn is_exam_reminder_sent(&self, uid: &str, days_before: i64) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let sent = conn
+            .query_row(
+                "SELECT 1 FROM sent_exam_reminders WHERE uid = ?1 AND days_before = ?2",
+                params![uid, days_before],
+                |_| Ok(()),
+            )
+            .optional()?;
+
+        Ok(sent.is_some())
+    }
+
+    /// Records the reminder sent `days_before` the event
+    pub fn mark_exam_reminder_sent(&self, uid: &str, days_before: i64) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT OR IGNORE INTO sent_exam_reminders (uid, days_before) VALUES (?1, ?2)",
+            params![uid, days_before],
+        )?;
+
+        Ok(())
+    }
+
+    /// Guilds that opted in to Discord Scheduled Events, with their promos
+    pub fn get_scheduled_event_guilds(&self) -> rusqlite::Result<Vec<(GuildId, Vec<Promo>)>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare(
+            "SELECT guild_id, scheduled_event_promos FROM guild_settings
+             WHERE scheduled_event_promos IS NOT NULL",
+        )?;
+        let guilds = stmt
+            .query_map([], |row| {
+                Ok((
+                    GuildId(row.get::<_, i64>(0)? as u64),
+                    parse_promos(row.get(1)?),
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<(GuildId, Vec<Promo>)>>>()?;
+
+        Ok(guilds)
+    }
+
+    pub fn get_synced_events(&self, guild: GuildId) -> rusqlite::Result<Vec<SyncedEvent>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare(
+            "SELECT uid, event_id, fingerprint, ends_at FROM synced_events WHERE guild_id = ?1",
+        )?;
+        let events = stmt
+            .query_map(params![guild.0 as i64], |row| {
+                Ok(SyncedEvent {
+                    uid: row.get(0)?,
+                    event_id: ScheduledEventId(row.get::<_, i64>(1)? as u64),
+                    fingerprint: row.get(2)?,
+                    ends_at: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<SyncedEvent>>>()?;
+
+        Ok(events)
+    }
+
+    pub fn set_synced_event(&self, guild: GuildId, event: &SyncedEvent) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT INTO synced_events (guild_id, uid, event_id, fingerprint, ends_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(guild_id, uid) DO UPDATE SET event_id = excluded.event_id,
+                fingerprint = excluded.fingerprint, ends_at = excluded.ends_at",
+            params![
+                guild.0 as i64,
+                event.uid,
+                event.event_id.0 as i64,
+                event.fingerprint,
+                event.ends_at
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn remove_synced_event(&self, guild: GuildId, uid: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM synced_events WHERE guild_id = ?1 AND uid = ?2",
+            params![guild.0 as i64, uid],
+        )?;
+
+        Ok(())
+    }
+
+    /// Guilds that enabled temporary voice channels, with their category
+    pub fn get_voice_channel_guilds(&self) -> rusqlite::Result<Vec<(GuildId, ChannelId)>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare(
+            "SELECT guild_id, voice_category FROM guild_settings WHERE voice_category IS NOT NULL",
+        )?;
+        let guilds = stmt
+            .query_map([], |row| {
+                Ok((
+                    GuildId(row.get::<_, i64>(0)? as u64),
+                    ChannelId(row.get::<_, i64>(1)? as u64),
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<(GuildId, ChannelId)>>>()?;
+
+        Ok(guilds)
+    }
+
+    pub fn get_voice_channels(&self, guild: GuildId) -> rusqlite::Result<Vec<VoiceChannel>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn
+            .prepare("SELECT uid, channel_id, ends_at FROM voice_channels WHERE guild_id = ?1")?;
+        let channels = stmt
+            .query_map(params![guild.0 as i64], |row| {
+                Ok(VoiceChannel {
+                    uid: row.get(0)?,
+                    channel_id: ChannelId(row.get::<_, i64>(1)? as u64),
+                    ends_at: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<VoiceChannel>>>()?;
+
+        Ok(channels)
+    }
+
+    pub fn add_voice_channel(
+        &self,
+        guild: GuildId,
+        channel: &VoiceChannel,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT OR REPLACE INTO voice_channels (guild_id, uid, channel_id, ends_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                guild.0 as i64,
+                channel.uid,
+                channel.channel_id.0 as i64,
+                channel.ends_at
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn remove_voice_channel(&self, guild: GuildId, uid: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM voice_channels WHERE guild_id = ?1 AND uid = ?2",
+            params![guild.0 as i64, uid],
+        )?;
+
+        Ok(())
+    }
+
+    /// Forum post reused for the daily announcements of the promo
+    pub fn get_forum_post(
+        &self,
+        guild: GuildId,
+        promo: &Promo,
+    ) -> rusqlite::Result<Option<ChannelId>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let thread = conn
+            .query_row(
+                "SELECT thread_id FROM forum_posts WHERE guild_id = ?1 AND promo = ?2",
+                params![guild.0 as i64, promo.to_string()],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+
+        Ok(thread.map(|t| ChannelId(t as u64)))
+    }
+
+    pub fn set_forum_post(
+        &self,
+        guild: GuildId,
+        promo: &Promo,
+        thread: ChannelId,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT OR REPLACE INTO forum_posts (guild_id, promo, thread_id) VALUES (?1, ?2, ?3)",
+            params![guild.0 as i64, promo.to_string(), thread.0 as i64],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn add_announcement(
+        &self,
+        channel: ChannelId,
+        promo: &Promo,
+        message: MessageId,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT OR REPLACE INTO announcement_messages (channel_id, promo, message_id, posted_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                channel.0 as i64,
+                promo.to_string(),
+                message.0 as i64,
+                Utc::now().timestamp()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Latest announcement of the promo in this channel
+    pub fn get_last_announcement(
+        &self,
+        channel: ChannelId,
+        promo: &Promo,
+    ) -> rusqlite::Result<Option<MessageId>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let message = conn
+            .query_row(
+                "SELECT message_id FROM announcement_messages WHERE channel_id = ?1 AND promo = ?2
+                 ORDER BY posted_at DESC LIMIT 1",
+                params![channel.0 as i64, promo.to_string()],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+
+        Ok(message.map(|m| MessageId(m as u64)))
+    }
+
+    pub fn get_announcements_before(
+        &self,
+        before: DateTime<Utc>,
+    ) -> rusqlite::Result<Vec<(ChannelId, MessageId)>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare(
+            "SELECT channel_id, message_id FROM announcement_messages WHERE posted_at < ?1",
+        )?;
+        let messages = stmt
+            .query_map(params![before.timestamp()], |row| {
+                Ok((
+                    ChannelId(row.get::<_, i64>(0)? as u64),
+                    MessageId(row.get::<_, i64>(1)? as u64),
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<(ChannelId, MessageId)>>>()?;
+
+        Ok(messages)
+    }
+
+    pub fn remove_announcement(&self, message: MessageId) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM announcement_messages WHERE message_id = ?1",
+            params![message.0 as i64],
+        )?;
+
+        Ok(())
+    }
+
+    /// Last day the timetable of the promo was announced from this channel
+    pub fn get_announced_day(
+        &self,
+        channel: ChannelId,
+        promo: &Promo,
+    ) -> rusqlite::Result<Option<NaiveDate>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let date = conn
+            .query_row(
+                "SELECT date FROM announced_days WHERE channel_id = ?1 AND promo = ?2",
+                params![channel.0 as i64, promo.to_string()],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+
+        Ok(date.and_then(|d| d.parse().ok()))
+    }
+
+    pub fn set_announced_day(
+        &self,
+        channel: ChannelId,
+        promo: &Promo,
+        date: NaiveDate,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT OR REPLACE INTO announced_days (channel_id, promo, date) VALUES (?1, ?2, ?3)",
+            params![channel.0 as i64, promo.to_string(), date.to_string()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Homework of the guild due on `from` or later, sorted by due date
+    pub fn get_homework(&self, guild: GuildId, from: NaiveDate) -> rusqlite::Result<Vec<Homework>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare(
+            "SELECT id, promo, subject, due, description, author_id FROM homework
+             WHERE guild_id = ?1 AND due >= ?2 ORDER BY due, id",
+        )?;
+        let homework = stmt
+            .query_map(params![guild.0 as i64, from.to_string()], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, i64>(5)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .filter_map(|(id, promo, subject, due, description, author)| {
+                Some(Homework {
+                    id,
+                    guild,
+                    promo: parse_saved_promo(&promo)?,
+                    subject,
+                    due: due.parse().ok()?,
+                    description,
+                    author: UserId(author as u64),
+                })
+            })
+            .collect();
+
+        Ok(homework)
+    }
+
+    /// Stores new homework, its `id` is ignored and the assigned one is returned
+    pub fn add_homework(&self, homework: &Homework) -> rusqlite::Result<i64> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "INSERT INTO homework (guild_id, promo, subject, due, description, author_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                homework.guild.0 as i64,
+                homework.promo.to_string(),
+                homework.subject,
+                homework.due.to_string(),
+                homework.description,
+                homework.author.0 as i64
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Returns whether the guild had this homework
+    pub fn remove_homework(&self, guild: GuildId, id: i64) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let removed = conn.execute(
+            "DELETE FROM homework WHERE guild_id = ?1 AND id = ?2",
+            params![guild.0 as i64, id],
+        )?;
+
+        Ok(removed > 0)
+    }
+
+    /// Deletes the homework due before `today`
+    pub fn remove_past_homework(&self, today: NaiveDate) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM homework WHERE due < ?1",
+            params![today.to_string()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Replaces the archive of every day that has events in `events`
+    pub fn archive_events(&self, events: &[Event]) -> rusqlite::Result<()> {
+        let mut conn = self.conn.lock().expect("Failed to lock database!");
+        let tx = conn.transaction()?;
+        let days: BTreeSet<String> = events
+            .iter()
+            .map(|e| e.start.date_naive().format("%Y-%m-%d").to_string())
+            .collect();
+        for day in &days {
+            tx.execute("DELETE FROM event_archive WHERE date = ?1", params![day])?;
+        }
+        for evt in events {
+            tx.execute(
+                "INSERT OR REPLACE INTO event_archive (date, uid, summary, start, end, location,
+                    lesson, grp, teacher, notes, event_type)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    evt.start.date_naive().format("%Y-%m-%d").to_string(),
+                    evt.uid,
+                    evt.summary,
+                    evt.start.timestamp(),
+                    evt.end.timestamp(),
+                    evt.location,
+                    evt.lesson,
+                    evt.group,
+                    evt.teacher,
+                    evt.notes.join("\n"),
+                    format!("{:?}", evt.event_type)
+                ],
+            )?;
+        }
+
+        tx.commit()
+    }
+
+    /// Events archived from `start` (inclusive) to `end` (exclusive), without the overrides
+    pub fn get_archived_events(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> rusqlite::Result<Vec<Event>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare(
+            "SELECT uid, summary, start, end, location, lesson, grp, teacher, notes, event_type
+             FROM event_archive WHERE date >= ?1 AND date < ?2",
+        )?;
+        let events = stmt
+            .query_map(
+                params![
+                    start.format("%Y-%m-%d").to_string(),
+                    end.format("%Y-%m-%d").to_string()
+                ],
+                event_from_row,
+            )?
+            .collect::<rusqlite::Result<Vec<Event>>>()?;
+
+        Ok(events)
+    }
+
+    /// Saves the whole feed as it is at `taken_at`, for /changements
+    pub fn save_snapshot(&self, taken_at: DateTime<Utc>, events: &[Event]) -> rusqlite::Result<()> {
+        let mut conn = self.conn.lock().expect("Failed to lock database!");
+        let tx = conn.transaction()?;
+        for evt in events {
+            tx.execute(
+                "INSERT OR REPLACE INTO feed_snapshots (taken_at, uid, summary, start, end,
+                    location, lesson, grp, teacher, notes, event_type)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    taken_at.timestamp(),
+                    evt.uid,
+                    evt.summary,
+                    evt.start.timestamp(),
+                    evt.end.timestamp(),
+                    evt.location,
+                    evt.lesson,
+                    evt.group,
+                    evt.teacher,
+                    evt.notes.join("\n"),
+                    format!("{:?}", evt.event_type)
+                ],
+            )?;
+        }
+
+        tx.commit()
+    }
+
+    pub fn get_last_snapshot_time(&self) -> rusqlite::Result<Option<DateTime<Utc>>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let taken_at: Option<i64> =
+            conn.query_row("SELECT MAX(taken_at) FROM feed_snapshots", [], |row| {
+                row.get(0)
+            })?;
+
+        Ok(taken_at.and_then(|t| DateTime::from_timestamp(t, 0)))
+    }
+
+    /// Latest snapshot taken at or before `at`, or the oldest one when they are all more recent
+    pub fn get_snapshot(
+        &self,
+        at: DateTime<Utc>,
+    ) -> rusqlite::Result<Option<(DateTime<Utc>, Vec<Event>)>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let taken_at: Option<i64> = conn.query_row(
+            "SELECT COALESCE(
+                (SELECT MAX(taken_at) FROM feed_snapshots WHERE taken_at <= ?1),
+                (SELECT MIN(taken_at) FROM feed_snapshots)
+            )",
+            params![at.timestamp()],
+            |row| row.get(0),
+        )?;
+        let Some(taken_at) = taken_at else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT uid, summary, start, end, location, lesson, grp, teacher, notes, event_type
+             FROM feed_snapshots WHERE taken_at = ?1",
+        )?;
+        let events = stmt
+            .query_map(params![taken_at], event_from_row)?
+            .collect::<rusqlite::Result<Vec<Event>>>()?;
+
+        Ok(DateTime::from_timestamp(taken_at, 0).map(|t| (t, events)))
+    }
+
+    pub fn remove_snapshots_before(&self, before: DateTime<Utc>) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM feed_snapshots WHERE taken_at < ?1",
+            params![before.timestamp()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Promos whose classes get a check-in button, with the channel it's posted in
+    pub fn get_attendance_promos(&self) -> rusqlite::Result<Vec<(GuildId, Promo, ChannelId)>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare("SELECT guild_id, promo, channel_id FROM attendance_promos")?;
+        let promos = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<(i64, String, i64)>>>()?;
+
+        Ok(promos
+            .into_iter()
+            .filter_map(|(guild, promo, channel)| {
+                Some((
+                    GuildId(guild as u64),
+                    parse_saved_promo(&promo)?,
+                    ChannelId(channel as u64),
+                ))
+            })
+            .collect())
+    }
+
+    /// Enables the check-ins of the promo in `channel`, or disables them with `None`
+    pub fn set_attendance_promo(
+        &self,
+        guild: GuildId,
+        promo: &Promo,
+        channel: Option<ChannelId>,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        match channel {
+            Some(channel) => conn.execute(
+                "INSERT OR REPLACE INTO attendance_promos (guild_id, promo, channel_id)
+                 VALUES (?1, ?2, ?3)",
+                params![guild.0 as i64, promo.to_string(), channel.0 as i64],
+            )?,
+            None => conn.execute(
+                "DELETE FROM attendance_promos WHERE guild_id = ?1 AND promo = ?2",
+                params![guild.0 as i64, promo.to_string()],
+            )?,
+        };
+
+        Ok(())
+    }
+
+    /// Opens the check-in of a class, returns `None` if it was already opened
+    pub fn add_attendance_session(
+        &self,
+        guild: GuildId,
+        promo: &Promo,
+        evt: &Event,
+        closes_at: i64,
+    ) -> rusqlite::Result<Option<i64>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO attendance_sessions (guild_id, promo, uid, lesson, starts_at,
+                closes_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                guild.0 as i64,
+                promo.to_string(),
+                evt.uid,
+                evt.lesson,
+                evt.start.timestamp(),
+                closes_at
+            ],
+        )?;
+
+        Ok((inserted > 0).then(|| conn.last_insert_rowid()))
+    }
+
+    /// Check-in `id` of the guild, `None` when it belongs to another guild
+    pub fn get_attendance_session(
+        &self,
+        guild: GuildId,
+        id: i64,
+    ) -> rusqlite::Result<Option<AttendanceSession>> {
+        Ok(self
+            .query_attendance_sessions(
+                "WHERE id = ?1 AND guild_id = ?2",
+                params![id, guild.0 as i64],
+            )?
+            .pop())
+    }
+
+    /// Latest check-ins of the guild, most recent first
+    pub fn get_attendance_sessions(
+        &self,
+        guild: GuildId,
+        limit: usize,
+    ) -> rusqlite::Result<Vec<AttendanceSession>> {
+        self.query_attendance_sessions(
+            "WHERE guild_id = ?1 ORDER BY starts_at DESC LIMIT ?2",
+            params![guild.0 as i64, limit as i64],
+        )
+    }
+
+    fn query_attendance_sessions(
+        &self,
+        filter: &str,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> rusqlite::Result<Vec<AttendanceSession>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, promo, lesson, starts_at, closes_at FROM attendance_sessions {}",
+            filter
+        ))?;
+        let sessions = stmt
+            .query_map(params, |row| {
+                Ok(AttendanceSession {
+                    id: row.get(0)?,
+                    promo: row.get(1)?,
+                    lesson: row.get(2)?,
+                    starts_at: row.get(3)?,
+                    closes_at: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<AttendanceSession>>>()?;
+
+        Ok(sessions)
+    }
+
+    /// Records the presence of the user, returns false if it already was
+    pub fn check_in(&self, session: i64, user: UserId) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO attendance (session_id, user_id, checked_at) VALUES (?1, ?2, ?3)",
+            params![session, user.0 as i64, Utc::now().timestamp()],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    /// Users who checked in and when, in order of arrival
+    pub fn get_attendance(&self, session: i64) -> rusqlite::Result<Vec<(UserId, i64)>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare(
+            "SELECT user_id, checked_at FROM attendance WHERE session_id = ?1 ORDER BY checked_at",
+        )?;
+        let attendance = stmt
+            .query_map(params![session], |row| {
+                Ok((UserId(row.get::<_, i64>(0)? as u64), row.get(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<(UserId, i64)>>>()?;
+
+        Ok(attendance)
+    }
+
+    /// Schedules a DM before the class, returns false if the user already asked for it
+    /// `guild` is where the reminder was scheduled, its homework is added to the DM
+    pub fn add_class_reminder(
+        &self,
+        user: UserId,
+        uid: &str,
+        guild: Option<GuildId>,
+    ) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO class_reminders (user_id, uid, guild_id) VALUES (?1, ?2, ?3)",
+            params![user.0 as i64, uid, guild.map(|g| g.0 as i64)],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    /// Every pending reminder, with the uid of its class and the guild it was scheduled in
+    pub fn get_class_reminders(&self) -> rusqlite::Result<Vec<(UserId, String, Option<GuildId>)>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let mut stmt = conn.prepare("SELECT user_id, uid, guild_id FROM class_reminders")?;
+        let reminders = stmt
+            .query_map([], |row| {
+                Ok((
+                    UserId(row.get::<_, i64>(0)? as u64),
+                    row.get(1)?,
+                    row.get::<_, Option<i64>>(2)?.map(|g| GuildId(g as u64)),
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<(UserId, String, Option<GuildId>)>>>()?;
+
+        Ok(reminders)
+    }
+
+    pub fn remove_class_reminder(&self, user: UserId, uid: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM class_reminders WHERE user_id = ?1 AND uid = ?2",
+            params![user.0 as i64, uid],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Used by the web dashboard only
+#[cfg(feature = "dashboard")]
+impl Database {
+    pub fn get_exam_subscription(&self, user: UserId) -> rusqlite::Result<Option<Promo>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let promo: Option<String> = conn
+            .query_row(
+                "SELECT promo FROM exam_subscribers WHERE user_id = ?1",
+                params![user.0 as i64],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(promo.and_then(|p| parse_saved_promo(&p)))
+    }
+
+    pub fn add_dashboard_session(
+        &self,
+        token: &str,
+        session: &DashboardSession,
+        expires_at: DateTime<Utc>,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM dashboard_sessions WHERE expires_at < ?1",
+            params![Utc::now().timestamp()],
+        )?;
+        conn.execute(
+            "INSERT INTO dashboard_sessions (token, user_id, name, promo, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                token,
+                session.user.0 as i64,
+                session.name,
+                session.promo.as_ref().map(|p| p.to_string()),
+                expires_at.timestamp()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Session of the cookie `token`, unless it expired
+    pub fn get_dashboard_session(&self, token: &str) -> rusqlite::Result<Option<DashboardSession>> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        let session = conn
+            .query_row(
+                "SELECT user_id, name, promo FROM dashboard_sessions
+                 WHERE token = ?1 AND expires_at >= ?2",
+                params![token, Utc::now().timestamp()],
+                |row| {
+                    Ok(DashboardSession {
+                        user: UserId(row.get::<_, i64>(0)? as u64),
+                        name: row.get(1)?,
+                        promo: row
+                            .get::<_, Option<String>>(2)?
+                            .and_then(|p| parse_saved_promo(&p)),
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(session)
+    }
+
+    pub fn remove_dashboard_session(&self, token: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().expect("Failed to lock database!");
+        conn.execute(
+            "DELETE FROM dashboard_sessions WHERE token = ?1",
+            params![token],
+        )?;
+
+        Ok(())
+    }
+}
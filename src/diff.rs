@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use crate::{calendar::Event, config};
+
+#[derive(Debug, Clone)]
+pub enum Change {
+    Added(Event),
+    Removed(Event),
+    Moved { before: Event, after: Event },
+    RoomChanged { before: Event, after: Event },
+}
+
+/// Compares two snapshots of the feed, matching events by uid
+pub fn diff_events(old: &[Event], new: &[Event]) -> Vec<Change> {
+    let old_by_uid: HashMap<&str, &Event> = old.iter().map(|e| (e.uid.as_str(), e)).collect();
+    let new_by_uid: HashMap<&str, &Event> = new.iter().map(|e| (e.uid.as_str(), e)).collect();
+
+    let mut changes: Vec<Change> = Vec::new();
+    for evt in new {
+        match old_by_uid.get(evt.uid.as_str()) {
+            None => changes.push(Change::Added(evt.clone())),
+            Some(before) if before.start != evt.start || before.end != evt.end => {
+                changes.push(Change::Moved {
+                    before: (*before).clone(),
+                    after: evt.clone(),
+                })
+            }
+            Some(before) if before.location != evt.location => changes.push(Change::RoomChanged {
+                before: (*before).clone(),
+                after: evt.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for evt in old {
+        if !new_by_uid.contains_key(evt.uid.as_str()) {
+            changes.push(Change::Removed(evt.clone()));
+        }
+    }
+
+    changes
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lesson = |e: &Event| config::get().subject_label(e);
+        let slot = |e: &Event| {
+            format!(
+                "{} {}-{}",
+                e.start.format("%d/%m"),
+                e.start.format("%H:%M"),
+                e.end.format("%H:%M")
+            )
+        };
+
+        match self {
+            Change::Added(evt) => write!(f, "➕ {} ({})", lesson(evt), slot(evt)),
+            Change::Removed(evt) => write!(f, "❌ {} ({})", lesson(evt), slot(evt)),
+            Change::Moved { before, after } => {
+                write!(
+                    f,
+                    "🔀 {}: {} → {}",
+                    lesson(after),
+                    slot(before),
+                    slot(after)
+                )
+            }
+            Change::RoomChanged { before, after } => write!(
+                f,
+                "🚪 {} ({}): {} → {}",
+                lesson(after),
+                slot(after),
+                before.location,
+                after.location
+            ),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::{Local, NaiveDate};
+
+use crate::calendar::{get_sorted_events, parse_promo_name, Promo};
+
+/// `--dump <group> [date]`: prints the parsed schedule of a group without connecting to Discord
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(group) = args.first() else {
+        return Err("usage: agenda-bot --dump <group> [date]".into());
+    };
+
+    let promo = parse_promo_name(group)
+        .or_else(|| group.parse::<Promo>().ok())
+        .ok_or(format!("Invalid group: {}", group))?;
+
+    let day = match args.get(1) {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(date, "%d/%m/%Y"))
+            .map_err(|_| format!("Invalid date: {}", date))?,
+        None => Local::now().date_naive(),
+    };
+
+    let events = get_sorted_events(day).await?;
+    let events = events.get(&promo).cloned().unwrap_or_default();
+
+    println!(
+        "{} — {} ({} events)",
+        promo,
+        day.format("%d/%m/%Y"),
+        events.len()
+    );
+    for evt in events {
+        println!(
+            "{} - {} | {:?} | {} | {} | group: {} | teacher: {} | uid: {}",
+            evt.start.format("%H:%M"),
+            evt.end.format("%H:%M"),
+            evt.event_type,
+            evt.lesson,
+            evt.location,
+            evt.group,
+            evt.teacher.as_deref().unwrap_or("-"),
+            evt.uid
+        );
+        println!("    summary: {}", evt.summary);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,590 @@
+use std::collections::{HashMap, HashSet};
+
+use agenda_bot::{conflicts::conflicting_uids, dates::short_duration};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::{Europe::Paris, Tz};
+use poise::serenity_prelude::{
+    ButtonStyle, Colour, CreateComponents, CreateEmbed, GuildId, ReactionType,
+};
+
+use crate::{
+    calendar::{get_sorted_events_range, Event, Promo},
+    class_reminders::remind_button_id,
+    config,
+    db::Database,
+    i18n::Language,
+    prefs::{Format, UserPrefs, View},
+};
+
+const REFRESH_BUTTON_ID: &str = "refresh";
+/// Shown before the classes that overlap another one in a different room
+const CONFLICT_MARKER: &str = "⚠️";
+
+/// "Semaine 41" followed by the A/B alternation when an anchor week is configured
+fn week_label(day: NaiveDate, options: &EmbedOptions) -> String {
+    let week_name = options.language.strings().week;
+    let week = day.iso_week().week();
+    let Some(anchor) = config::get().week_a_anchor else {
+        return format!("{} {}", week_name, week);
+    };
+
+    let weeks_since_anchor = (monday_of(day) - monday_of(anchor)).num_weeks();
+    let letter = if weeks_since_anchor.rem_euclid(2) == 0 {
+        "A"
+    } else {
+        "B"
+    };
+
+    format!("{} {} ({})", week_name, week, letter)
+}
+
+fn monday_of(day: NaiveDate) -> NaiveDate {
+    day - chrono::Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+/// Days shown by a timetable of `day`, the end is exclusive
+pub fn view_range(view: View, day: NaiveDate) -> (NaiveDate, NaiveDate) {
+    match view {
+        View::Day => (day, day + chrono::Duration::days(1)),
+        View::Week => (monday_of(day), monday_of(day) + chrono::Duration::days(7)),
+    }
+}
+
+/// Rendering settings of a timetable embed
+#[derive(Debug, Clone, Default)]
+pub struct EmbedOptions {
+    pub show_gaps: bool,
+    pub hour12: bool,
+    pub language: Language,
+    /// Paris when unset
+    pub timezone: Option<Tz>,
+    pub hidden_categories: Vec<String>,
+    /// Events in the rooms of other campuses are left out
+    pub campus: Option<String>,
+    /// Shows how long before today's events start or end, only for the messages rendered on
+    /// demand since a posted status would be wrong an hour later
+    pub live_status: bool,
+}
+
+impl EmbedOptions {
+    pub fn for_guild(db: &Database, guild: Option<GuildId>) -> rusqlite::Result<EmbedOptions> {
+        let Some(guild) = guild else {
+            return Ok(EmbedOptions::default());
+        };
+
+        let settings = db.get_guild_settings(guild)?;
+        Ok(EmbedOptions {
+            show_gaps: settings.show_gaps,
+            campus: settings.campus,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_prefs(self, prefs: &UserPrefs) -> EmbedOptions {
+        EmbedOptions {
+            hour12: prefs.hour12,
+            language: prefs.language,
+            hidden_categories: prefs.hidden_categories.clone(),
+            campus: prefs.campus.clone().or(self.campus),
+            ..self
+        }
+    }
+
+    /// Also applies the user's timezone, only meant for replies nobody else can see
+    pub fn with_private_prefs(self, prefs: &UserPrefs) -> EmbedOptions {
+        EmbedOptions {
+            timezone: prefs.timezone,
+            ..self.with_prefs(prefs)
+        }
+    }
+
+    fn format_time(&self, time: &DateTime<Tz>) -> String {
+        let time = match self.timezone {
+            Some(tz) => time.with_timezone(&tz),
+            None => *time,
+        };
+
+        if self.hour12 {
+            time.format("%-I:%M %p").to_string()
+        } else {
+            time.format("%H:%M").to_string()
+        }
+    }
+
+    fn no_events(&self, group: &Promo, day: NaiveDate) -> String {
+        let strings = self.language.strings();
+        format!(
+            "{} {} {} {}",
+            strings.no_events,
+            group,
+            strings.on,
+            day.format("%d/%m/%Y")
+        )
+    }
+}
+
+/// Same as `get_sorted_events_range` without the categories hidden in `options` and the events
+/// of other campuses
+pub async fn get_visible_events(
+    start: NaiveDate,
+    end: NaiveDate,
+    options: &EmbedOptions,
+) -> Result<HashMap<Promo, Vec<Event>>, String> {
+    let mut events = get_sorted_events_range(start, end)
+        .await
+        .map_err(|err| format!("Error: {:?}", err))?;
+    let config = config::get();
+    for group_events in events.values_mut() {
+        group_events.retain(|e| {
+            e.category
+                .as_ref()
+                .is_none_or(|c| !options.hidden_categories.contains(c))
+        });
+        if let Some(campus) = &options.campus {
+            group_events.retain(|e| config.campus_of(&e.location).is_none_or(|c| c == campus));
+        }
+    }
+    events.retain(|_, group_events| !group_events.is_empty());
+
+    Ok(events)
+}
+
+/// Emoji of the secondary feed an event comes from, followed by a space
+fn category_prefix(evt: &Event) -> String {
+    evt.category
+        .as_deref()
+        .and_then(config::feed)
+        .map(|f| format!("{} ", f.emoji))
+        .unwrap_or_default()
+}
+
+/// Warning shown before an event that overlaps another one, followed by a space
+fn conflict_prefix(evt: &Event, conflicts: &HashSet<&str>) -> String {
+    if conflicts.contains(evt.uid.as_str()) {
+        format!("{} ", CONFLICT_MARKER)
+    } else {
+        String::new()
+    }
+}
+
+/// Location of an event followed by a link to the map of the room when it is registered
+fn location_with_map(evt: &Event, options: &EmbedOptions) -> String {
+    match config::get()
+        .room_of(&evt.location)
+        .and_then(|r| r.map.as_ref())
+    {
+        Some(map) => format!(
+            "{} ([📍 {}]({}))",
+            evt.location,
+            options.language.strings().map,
+            map
+        ),
+        None => evt.location.clone(),
+    }
+}
+
+/// Title and content of the embed field describing a single event
+pub fn make_event_field(evt: &Event, options: &EmbedOptions) -> (String, String) {
+    let strings = options.language.strings();
+    let time = format!(
+        "{}{} - {}",
+        category_prefix(evt),
+        options.format_time(&evt.start),
+        options.format_time(&evt.end),
+    );
+    let location = location_with_map(evt, options);
+    let lesson = config::get().subject_label(evt);
+    let mut value = match &evt.category {
+        Some(category) => format!("{}: {}\n{}: {}", category, lesson, strings.room, location),
+        None if config::get().is_exam(evt) => format!(
+            "{}: 📝 {}\nType: {:?}\n{}: {}\n**⚠️ {}**",
+            strings.subject, lesson, evt.event_type, strings.room, location, strings.graded_warning
+        ),
+        None => format!(
+            "{}: {}\nType: {:?}\n{}: {}",
+            strings.subject, lesson, evt.event_type, strings.room, location
+        ),
+    };
+    for note in &evt.notes {
+        value.push_str(&format!("\nℹ️ {}", note));
+    }
+    if let Some(note) = &evt.admin_note {
+        value.push_str(&format!("\n📌 {}", note));
+    }
+
+    if evt.cancelled {
+        (format!("❌ ~~{}~~ {}", time, strings.cancelled), value)
+    } else {
+        (time, value)
+    }
+}
+
+/// Field name of an event of today with its live status, recomputed on each render: the time
+/// left before it starts or ends, struck through once finished
+pub fn with_live_status(
+    name: String,
+    evt: &Event,
+    now: DateTime<Utc>,
+    options: &EmbedOptions,
+) -> String {
+    let strings = options.language.strings();
+    if evt.cancelled {
+        name
+    } else if evt.end <= now {
+        format!("~~{}~~", name)
+    } else if evt.start <= now {
+        format!(
+            "{} — {} {}",
+            name,
+            strings.ends_in,
+            short_duration(evt.end.with_timezone(&Utc) - now)
+        )
+    } else {
+        format!(
+            "{} — {} {}",
+            name,
+            strings.starts_in,
+            short_duration(evt.start.with_timezone(&Utc) - now)
+        )
+    }
+}
+
+/// Cancellation and admin note of an event, appended to its one-line renderings
+fn override_suffix(evt: &Event, options: &EmbedOptions) -> String {
+    let mut suffix = String::new();
+    if evt.cancelled {
+        suffix.push_str(&format!(" ❌ {}", options.language.strings().cancelled));
+    }
+    if let Some(note) = &evt.admin_note {
+        suffix.push_str(&format!(" 📌 {}", note));
+    }
+
+    suffix
+}
+
+/// Lesson of an event rendered on a single line, with the evaluation marker
+fn lesson_name(evt: &Event, options: &EmbedOptions) -> String {
+    let config = config::get();
+    if config.is_exam(evt) {
+        format!(
+            "📝 {} **({})**",
+            config.subject_label(evt),
+            options.language.strings().graded
+        )
+    } else {
+        config.subject_label(evt)
+    }
+}
+
+/// Discord refuses message contents longer than this
+pub const MAX_CONTENT_LENGTH: usize = 2000;
+
+/// Content and embeds of a timetable message, errors are shown as the content. A text rendering
+/// too long for a message is replaced by the embed view.
+pub async fn make_timetable(
+    view: View,
+    format: Format,
+    group: Promo,
+    day: NaiveDate,
+    options: &EmbedOptions,
+) -> (String, Vec<CreateEmbed>) {
+    if format == Format::Text {
+        match make_timetable_text(view, group.clone(), day, options).await {
+            Ok((text, _)) if text.chars().count() > MAX_CONTENT_LENGTH => {}
+            res => return res.unwrap_or_else(|err| (err, Vec::new())),
+        }
+    }
+
+    let res = match view {
+        View::Day => make_events_embed(group, day, options)
+            .await
+            .map(|e| (String::new(), e)),
+        View::Week => make_week_embed(group, day, options)
+            .await
+            .map(|e| (String::new(), vec![e])),
+    };
+
+    res.unwrap_or_else(|err| (err, Vec::new()))
+}
+
+/// Chronological plain-text rendering, one line per event
+async fn make_timetable_text(
+    view: View,
+    group: Promo,
+    day: NaiveDate,
+    options: &EmbedOptions,
+) -> Result<(String, Vec<CreateEmbed>), String> {
+    let (start, end) = view_range(view, day);
+
+    let events = get_visible_events(start, end, options).await?;
+    let Some(events) = events.get(&group) else {
+        return Err(options.no_events(&group, start));
+    };
+
+    let strings = options.language.strings();
+    let conflicts = conflicting_uids(events);
+    let mut lines = vec![format!(
+        "{}: {} — {}",
+        strings.timetable,
+        group,
+        week_label(day, options)
+    )];
+
+    let mut previous_end: Option<DateTime<Tz>> = None;
+    for evt in events {
+        let date = evt.start.date_naive();
+        if previous_end.is_none_or(|end| end.date_naive() != date) {
+            lines.push(String::new());
+            lines.push(format!(
+                "{} {}",
+                strings.weekdays[date.weekday().num_days_from_monday() as usize],
+                date.format("%d/%m")
+            ));
+            previous_end = None;
+        }
+
+        if options.show_gaps {
+            if let Some(end) = previous_end.filter(|end| *end < evt.start) {
+                lines.push(format!(
+                    "{} - {}: {}",
+                    options.format_time(&end),
+                    options.format_time(&evt.start),
+                    strings.no_class
+                ));
+            }
+        }
+        previous_end = previous_end.max(Some(evt.end));
+
+        lines.push(format!(
+            "{}{}{} - {}: {}, {:?}, {} {}{}",
+            conflict_prefix(evt, &conflicts),
+            category_prefix(evt),
+            options.format_time(&evt.start),
+            options.format_time(&evt.end),
+            lesson_name(evt, options),
+            evt.event_type,
+            strings.room,
+            evt.location,
+            override_suffix(evt, options)
+        ));
+    }
+
+    if let Some(tz) = options.timezone {
+        lines.push(String::new());
+        lines.push(tz.name().to_string());
+    }
+
+    Ok((lines.join("\n"), Vec::new()))
+}
+
+/// Timetable of the day followed by one embed per secondary feed category, in its colour
+pub async fn make_events_embed(
+    group: Promo,
+    day: NaiveDate,
+    options: &EmbedOptions,
+) -> Result<Vec<CreateEmbed>, String> {
+    let events = get_visible_events(day, day + chrono::Duration::days(1), options).await?;
+    let Some(events) = events.get(&group) else {
+        return Err(options.no_events(&group, day));
+    };
+    let conflicts = conflicting_uids(events);
+    let (category_events, events): (Vec<Event>, Vec<Event>) =
+        events.iter().cloned().partition(|e| e.category.is_some());
+
+    let mut e = CreateEmbed::default();
+    e.title(format!(
+        "{}: {} — {}",
+        options.language.strings().timetable,
+        group,
+        week_label(day, options)
+    ));
+
+    let timestamp = day.and_hms_opt(0, 0, 0).unwrap();
+    e.timestamp(timestamp.and_utc().to_rfc3339());
+    if events.is_empty() {
+        e.description(options.no_events(&group, day));
+    }
+    let now = Utc::now();
+    let live_status = options.live_status && day == now.with_timezone(&Paris).date_naive();
+    let mut previous_end: Option<DateTime<Tz>> = None;
+    for evt in events {
+        if options.show_gaps {
+            if let Some(end) = previous_end.filter(|end| *end < evt.start) {
+                e.field(
+                    format!(
+                        "🕳️ {} – {}",
+                        options.format_time(&end),
+                        options.format_time(&evt.start)
+                    ),
+                    options.language.strings().no_class,
+                    false,
+                );
+            }
+        }
+        previous_end = previous_end.max(Some(evt.end));
+
+        let (mut name, value) = make_event_field(&evt, options);
+        if conflicts.contains(evt.uid.as_str()) {
+            name = format!("{} {}", CONFLICT_MARKER, name);
+        }
+        if live_status {
+            name = with_live_status(name, &evt, now, options);
+        }
+        e.field(name, value, false);
+    }
+    set_timezone_footer(&mut e, options);
+    e.color(Colour::FOOYOO);
+
+    let mut embeds = vec![e];
+    let mut categories = category_events
+        .iter()
+        .filter_map(|e| e.category.clone())
+        .collect::<Vec<String>>();
+    categories.sort();
+    categories.dedup();
+    for category in categories {
+        let feed = config::feed(&category);
+        let mut e = CreateEmbed::default();
+        e.title(match &feed {
+            Some(feed) => format!("{} {}", feed.emoji, category),
+            None => category.clone(),
+        });
+        for evt in category_events
+            .iter()
+            .filter(|e| e.category.as_ref() == Some(&category))
+        {
+            let (name, value) = make_event_field(evt, options);
+            e.field(name, value, false);
+        }
+        e.color(
+            feed.and_then(|f| f.colour())
+                .map_or(Colour::BLURPLE, Colour::new),
+        );
+        embeds.push(e);
+    }
+
+    Ok(embeds)
+}
+
+fn set_timezone_footer(e: &mut CreateEmbed, options: &EmbedOptions) {
+    if let Some(tz) = options.timezone {
+        e.footer(|f| f.text(format!("🌐 {}", tz.name())));
+    }
+}
+
+/// One field per day of the week containing `day`, one line per event
+pub async fn make_week_embed(
+    group: Promo,
+    day: NaiveDate,
+    options: &EmbedOptions,
+) -> Result<CreateEmbed, String> {
+    let monday = monday_of(day);
+    let events = get_visible_events(monday, monday + chrono::Duration::days(7), options).await?;
+    let Some(events) = events.get(&group) else {
+        return Err(options.no_events(&group, monday));
+    };
+
+    let strings = options.language.strings();
+    let conflicts = conflicting_uids(events);
+    let mut e = CreateEmbed::default();
+    e.title(format!(
+        "{}: {} — {}",
+        strings.timetable,
+        group,
+        week_label(day, options)
+    ));
+
+    let timestamp = monday.and_hms_opt(0, 0, 0).unwrap();
+    e.timestamp(timestamp.and_utc().to_rfc3339());
+    for date in monday.iter_days().take(7) {
+        let lines = events
+            .iter()
+            .filter(|evt| evt.start.date_naive() == date)
+            .map(|evt| {
+                format!(
+                    "`{}-{}` {}{}{} ({:?}) — {}{}",
+                    options.format_time(&evt.start),
+                    options.format_time(&evt.end),
+                    conflict_prefix(evt, &conflicts),
+                    category_prefix(evt),
+                    lesson_name(evt, options),
+                    evt.event_type,
+                    evt.location,
+                    override_suffix(evt, options)
+                )
+            })
+            .collect::<Vec<String>>();
+        if lines.is_empty() {
+            continue;
+        }
+
+        e.field(
+            format!(
+                "{} {}",
+                strings.weekdays[date.weekday().num_days_from_monday() as usize],
+                date.format("%d/%m")
+            ),
+            lines.join("\n"),
+            false,
+        );
+    }
+    set_timezone_footer(&mut e, options);
+    e.color(Colour::FOOYOO);
+
+    Ok(e)
+}
+
+/// Components attached to every timetable message. The button id carries what is displayed so
+/// it also works on announcements and survives restarts.
+pub fn make_timetable_components(
+    day: NaiveDate,
+    group: &Promo,
+    view: View,
+    format: Format,
+) -> CreateComponents {
+    let mut components = CreateComponents::default();
+    components.create_action_row(|r| {
+        r.create_button(|b| {
+            b.custom_id(format!(
+                "{}:{}:{}:{}:{}",
+                REFRESH_BUTTON_ID,
+                day.format("%Y-%m-%d"),
+                group,
+                view,
+                format
+            ))
+            .emoji(ReactionType::Unicode("🔄".to_string()))
+            .style(ButtonStyle::Secondary)
+        })
+        .create_button(|b| {
+            b.custom_id(remind_button_id(day, group, view))
+                .emoji(ReactionType::Unicode("🔔".to_string()))
+                .style(ButtonStyle::Secondary)
+        })
+    });
+
+    components
+}
+
+/// Day, promo, view and format of a refresh button. Buttons sent before views and formats
+/// existed only carry the day and promo, the missing parts are `None`.
+pub fn parse_refresh_button_id(
+    custom_id: &str,
+) -> Option<(NaiveDate, Promo, Option<View>, Option<Format>)> {
+    let split = custom_id.split(':').collect::<Vec<&str>>();
+    if !(3..=5).contains(&split.len()) || split[0] != REFRESH_BUTTON_ID {
+        return None;
+    }
+
+    let day = NaiveDate::parse_from_str(split[1], "%Y-%m-%d").ok()?;
+    let promo = split[2].parse::<Promo>().ok()?;
+    let view = match split.get(3) {
+        Some(view) => Some(view.parse::<View>().ok()?),
+        None => None,
+    };
+    let format = match split.get(4) {
+        Some(format) => Some(format.parse::<Format>().ok()?),
+        None => None,
+    };
+    Some((day, promo, view, format))
+}
@@ -0,0 +1,93 @@
+use agenda_bot::search::subject_score;
+use chrono::{Datelike, NaiveDate};
+use poise::serenity_prelude::GuildId;
+
+use crate::{
+    calendar::{parse_promo_name, Event, GroupHierarchy, Promo},
+    db::{Database, Homework},
+    i18n::Language,
+};
+
+/// Embed fields hold 1024 characters
+const MAX_FIELD_LENGTH: usize = 1024;
+
+/// Homework of the guild due on `day` for `promo`, including the one given to the groups
+/// containing it
+pub fn homework_due(
+    db: &Database,
+    guild: GuildId,
+    hierarchy: &GroupHierarchy,
+    promo: &Promo,
+    day: NaiveDate,
+) -> rusqlite::Result<Vec<Homework>> {
+    Ok(upcoming_homework(db, guild, hierarchy, promo, day)?
+        .into_iter()
+        .filter(|h| h.due == day)
+        .collect())
+}
+
+/// Homework of the guild due on `from` or later for `promo`, including the one given to the
+/// groups containing it
+pub fn upcoming_homework(
+    db: &Database,
+    guild: GuildId,
+    hierarchy: &GroupHierarchy,
+    promo: &Promo,
+    from: NaiveDate,
+) -> rusqlite::Result<Vec<Homework>> {
+    Ok(db
+        .get_homework(guild, from)?
+        .into_iter()
+        .filter(|h| hierarchy.targets(&h.promo).contains(promo))
+        .collect())
+}
+
+/// Homework of the guild to hand in at the class: due that day, for a group sharing the class
+/// and in its subject
+pub fn homework_for_class(
+    db: &Database,
+    guild: GuildId,
+    hierarchy: &GroupHierarchy,
+    evt: &Event,
+) -> rusqlite::Result<Vec<Homework>> {
+    let Some(promo) = parse_promo_name(&evt.group) else {
+        return Ok(Vec::new());
+    };
+    let day = evt.start.date_naive();
+    let targets = hierarchy.targets(&promo);
+
+    Ok(db
+        .get_homework(guild, day)?
+        .into_iter()
+        .filter(|h| h.due == day)
+        .filter(|h| targets.contains(&h.promo) || hierarchy.targets(&h.promo).contains(&promo))
+        .filter(|h| subject_score(&h.subject, &evt.lesson).is_some())
+        .collect())
+}
+
+/// One line per homework, cut to fit in an embed field
+pub fn homework_lines(homework: &[Homework], with_date: bool) -> String {
+    let weekdays = Language::French.strings().weekdays;
+    let mut lines = String::new();
+    for h in homework {
+        let mut line = format!("📚 **{}** : {}", h.subject, h.description);
+        if with_date {
+            line = format!(
+                "n°{} · {} {} — {}",
+                h.id,
+                weekdays[h.due.weekday().num_days_from_monday() as usize],
+                h.due.format("%d/%m"),
+                line
+            );
+        }
+        if lines.chars().count() + line.chars().count() + 1 > MAX_FIELD_LENGTH {
+            break;
+        }
+        if !lines.is_empty() {
+            lines.push('\n');
+        }
+        lines.push_str(&line);
+    }
+
+    lines
+}
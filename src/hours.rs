@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::calendar::{Event, EventType};
+
+/// Minutes of class already held and planned in total
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Hours {
+    pub done: i64,
+    pub planned: i64,
+}
+
+impl Hours {
+    pub fn remaining(&self) -> i64 {
+        self.planned - self.done
+    }
+
+    pub fn add(&mut self, other: Hours) {
+        self.done += other.done;
+        self.planned += other.planned;
+    }
+}
+
+/// Hours of every lesson by type. Cancelled classes and secondary feed events don't count, a
+/// class is done once it's over.
+pub fn count_hours(
+    events: &[Event],
+    now: DateTime<Utc>,
+) -> BTreeMap<String, BTreeMap<EventType, Hours>> {
+    let mut lessons: BTreeMap<String, BTreeMap<EventType, Hours>> = BTreeMap::new();
+    for evt in events
+        .iter()
+        .filter(|e| !e.cancelled && e.category.is_none())
+    {
+        let minutes = (evt.end - evt.start).num_minutes();
+        let hours = Hours {
+            done: if evt.end <= now { minutes } else { 0 },
+            planned: minutes,
+        };
+        lessons
+            .entry(evt.lesson.clone())
+            .or_default()
+            .entry(evt.event_type.clone())
+            .or_default()
+            .add(hours);
+    }
+
+    lessons
+}
+
+/// Sum of every type of a lesson
+pub fn total(hours: &BTreeMap<EventType, Hours>) -> Hours {
+    let mut total = Hours::default();
+    for h in hours.values() {
+        total.add(*h);
+    }
+
+    total
+}
@@ -0,0 +1,77 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{extract::State, http::StatusCode, routing::get, Router};
+use chrono::Utc;
+use poise::serenity_prelude::{gateway::ConnectionStage, ShardManager};
+use tokio::sync::Mutex;
+
+use crate::calendar::fetch_stats;
+
+const DEFAULT_ADDR: &str = "0.0.0.0:8080";
+/// The watcher fetches the feed every 10 minutes, a few missed fetches mean it's stuck
+const MAX_FETCH_AGE_MINUTES: i64 = 30;
+
+/// Serves `app` on `HTTP_ADDR`
+pub async fn serve(app: Router) {
+    let addr = std::env::var("HTTP_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_string());
+    let addr: SocketAddr = match addr.parse() {
+        Ok(addr) => addr,
+        Err(err) => {
+            println!("Invalid HTTP_ADDR {}: {}", addr, err);
+            return;
+        }
+    };
+
+    println!("HTTP server listening on {}", addr);
+    if let Err(err) = axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await
+    {
+        println!("HTTP server stopped: {}", err);
+    }
+}
+
+/// `/healthz` answers as long as the process runs, `/readyz` only once the gateway is connected
+/// and the feed was fetched recently, for the orchestrator to restart the bot otherwise
+pub fn health_router(shard_manager: Arc<Mutex<ShardManager>>) -> Router {
+    Router::new()
+        .route("/healthz", get(|| async { "ok" }))
+        .route("/readyz", get(ready))
+        .with_state(shard_manager)
+}
+
+async fn ready(State(shard_manager): State<Arc<Mutex<ShardManager>>>) -> (StatusCode, String) {
+    let runners = shard_manager.lock().await.runners.clone();
+    let runners = runners.lock().await;
+    if runners.is_empty() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "no shard running".to_string(),
+        );
+    }
+    if let Some((id, runner)) = runners
+        .iter()
+        .find(|(_, r)| r.stage != ConnectionStage::Connected)
+    {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("shard {} is {}", id.0, runner.stage),
+        );
+    }
+
+    match fetch_stats().await.last_success {
+        Some(fetched_at)
+            if Utc::now() - fetched_at <= chrono::Duration::minutes(MAX_FETCH_AGE_MINUTES) =>
+        {
+            (StatusCode::OK, "ok".to_string())
+        }
+        Some(fetched_at) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("calendar last fetched at {}", fetched_at.to_rfc3339()),
+        ),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "calendar never fetched".to_string(),
+        ),
+    }
+}
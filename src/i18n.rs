@@ -0,0 +1,81 @@
+/// Language of the rendered timetables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, poise::ChoiceParameter)]
+pub enum Language {
+    #[default]
+    #[name = "fr"]
+    French,
+    #[name = "en"]
+    English,
+}
+
+pub struct Strings {
+    pub timetable: &'static str,
+    pub week: &'static str,
+    pub subject: &'static str,
+    pub room: &'static str,
+    pub map: &'static str,
+    pub graded: &'static str,
+    pub graded_warning: &'static str,
+    pub no_class: &'static str,
+    pub cancelled: &'static str,
+    /// Followed by the group, `on` and the date
+    pub no_events: &'static str,
+    pub on: &'static str,
+    /// Followed by the time left, on the events of today
+    pub ends_in: &'static str,
+    pub starts_in: &'static str,
+    pub weekdays: [&'static str; 7],
+}
+
+const FRENCH: Strings = Strings {
+    timetable: "Emploi du temps",
+    week: "Semaine",
+    subject: "Matière",
+    room: "Salle",
+    map: "plan",
+    graded: "Devoir Noté",
+    graded_warning: "Évaluation notée, pensez à réviser !",
+    no_class: "pas de cours",
+    cancelled: "Annulé",
+    no_events: "Aucun cours pour",
+    on: "le",
+    ends_in: "en cours, fini dans",
+    starts_in: "dans",
+    weekdays: [
+        "Lundi", "Mardi", "Mercredi", "Jeudi", "Vendredi", "Samedi", "Dimanche",
+    ],
+};
+
+const ENGLISH: Strings = Strings {
+    timetable: "Timetable",
+    week: "Week",
+    subject: "Subject",
+    room: "Room",
+    map: "map",
+    graded: "Graded",
+    graded_warning: "Graded assessment, don't forget to revise!",
+    no_class: "no class",
+    cancelled: "Cancelled",
+    no_events: "There are no events for",
+    on: "on",
+    ends_in: "ongoing, ends in",
+    starts_in: "in",
+    weekdays: [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ],
+};
+
+impl Language {
+    pub fn strings(&self) -> &'static Strings {
+        match self {
+            Language::French => &FRENCH,
+            Language::English => &ENGLISH,
+        }
+    }
+}
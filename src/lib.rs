@@ -0,0 +1,7 @@
+pub mod availability;
+pub mod cache;
+pub mod calendar;
+pub mod conflicts;
+pub mod dates;
+pub mod hours;
+pub mod search;
@@ -1,183 +1,98 @@
 extern crate dotenv;
-mod calendar;
-
-use std::{collections::HashMap, sync::Mutex};
+use agenda_bot::calendar;
+mod alerts;
+mod announcements;
+mod archive;
+mod attendance;
+mod class_reminders;
+mod commands;
+mod config;
+#[cfg(feature = "dashboard")]
+mod dashboard;
+mod db;
+mod diff;
+mod dump;
+mod embed;
+mod homework;
+mod http;
+mod i18n;
+mod mentions;
+mod prefs;
+mod presence;
+mod reminders;
+mod scheduled_events;
+mod scheduler;
+mod state;
+mod voice_channels;
+mod watcher;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
 
-use calendar::{get_sorted_events, parse_promo_name, Promo};
-use lazy_static::lazy_static;
+use calendar::RECENT_ERRORS_HOURS;
+use db::Database;
+use embed::{make_timetable, make_timetable_components, parse_refresh_button_id, EmbedOptions};
 use poise::{
-    serenity_prelude::{
-        self as serenity, ChannelId, Colour, CreateEmbed, EventHandler, Member, ReactionType, Role,
-    },
+    serenity_prelude::{self as serenity, EventHandler, Interaction, ReactionType},
     Event,
 };
+use state::State;
 
-use chrono::{Days, Local, NaiveDate, Timelike};
+use chrono::{DateTime, Utc};
 use dotenv::dotenv;
-use regex::Regex;
 
-lazy_static! {
-    static ref ROLE_REGEX: Regex = Regex::new("[1-4]-[A-Z]*-[1-4][1-2]").unwrap();
-}
-
-struct Data {
-    edt_msgs: Mutex<HashMap<serenity::MessageId, (NaiveDate, Promo)>>,
+pub struct Data {
+    state: State,
+    db: Database,
+    started_at: Instant,
+    /// Times of the failed commands of the last `RECENT_ERRORS_HOURS`, shown by /botstats
+    command_errors: Mutex<Vec<DateTime<Utc>>>,
 } // User data, which is stored and accessible in all command invocations
-type Error = Box<dyn std::error::Error + Send + Sync>;
-type Context<'a> = poise::Context<'a, Data, Error>;
-
-fn get_user_groups(ctx: Context<'_>, member: Member) -> Option<Vec<Promo>> {
-    let roles = member.roles(ctx);
-    if let Some(roles) = roles {
-        let roles: Vec<&Role> = roles
-            .iter()
-            .filter(|r| ROLE_REGEX.is_match(&r.name))
-            .collect();
-
-        let mut promos: Vec<Promo> = Vec::new();
-        for role in roles {
-            let promo = parse_promo_name(&role.name);
-            if promo.is_none() {
-                continue;
-            }
-
-            promos.push(promo.unwrap());
-        }
-
-        return Some(promos);
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type Context<'a> = poise::Context<'a, Data, Error>;
+
+async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
+    if let poise::FrameworkError::Command { ctx, .. } = &error {
+        let now = Utc::now();
+        let mut errors = ctx
+            .data()
+            .command_errors
+            .lock()
+            .expect("Failed to lock mutex!");
+        errors.retain(|t| now - *t < chrono::Duration::hours(RECENT_ERRORS_HOURS));
+        errors.push(now);
     }
 
-    None
-}
-
-async fn make_events_embed(group: Promo, day: NaiveDate) -> Result<CreateEmbed, String> {
-    let events = get_sorted_events(day).await;
-    if let Err(err) = events.clone() {
-        return Err(format!("Error: {:?}", err));
-    }
-
-    let events = events.unwrap();
-    if events.len() == 0 {
-        return Err(format!(
-            "There are no events for {} on {}",
-            group,
-            day.format("%d/%m/%Y")
-        ));
-    }
-
-    let mut e = CreateEmbed::default();
-    e.title(format!("Emploi du temps: {}", group));
-
-    let timestamp = day.and_hms_opt(0, 0, 0).unwrap();
-    e.timestamp(timestamp.and_utc().to_rfc3339());
-    for evt in events[&group].clone() {
-        e.field(
-            format!(
-                "{} - {}",
-                evt.start.format("%H:%M"),
-                evt.end.format("%H:%M"),
-            ),
-            format!(
-                "Matière: {}\nType: {:?}\nSalle: {}",
-                if evt.summary.contains("eval") || evt.summary.contains("moodle") {
-                    format!("{} (Devoir Noté)", evt.lesson)
-                } else {
-                    evt.lesson
-                },
-                evt.event_type,
-                evt.location
-            ),
-            false,
+    #[cfg(feature = "sentry")]
+    if let poise::FrameworkError::Command { error, ctx } = &error {
+        let group = ctx.data().db.get_user_group(ctx.author().id).ok().flatten();
+        sentry::with_scope(
+            |scope| {
+                scope.set_tag("command", &ctx.command().qualified_name);
+                if let Some(guild) = ctx.guild_id() {
+                    scope.set_tag("guild", guild);
+                }
+                if let Some(group) = group {
+                    scope.set_tag("group", group);
+                }
+                scope.set_user(Some(sentry::User {
+                    id: Some(ctx.author().id.to_string()),
+                    ..Default::default()
+                }));
+                scope.set_extra("invocation", ctx.invocation_string().into());
+            },
+            || sentry::capture_error(error.as_ref()),
         );
     }
-    e.color(Colour::FOOYOO);
 
-    Ok(e)
-}
-
-/// Affiche l'emploie du temps d'un groupe ou d'un utilisateur
-#[poise::command(slash_command, prefix_command)]
-async fn edt(
-    ctx: Context<'_>,
-    #[description = "Utilisateur"] member: Option<serenity::Member>,
-    #[description = "Numéro du group (ex: 32)"] group: Option<String>,
-) -> Result<(), Error> {
-    let _ = ctx.defer().await;
-
-    let date = Local::now().date_naive();
-
-    let promo: Option<Promo> = if let Some(member) = member {
-        let groups = get_user_groups(ctx, member);
-        if let Some(groups) = groups {
-            if groups.len() == 0 {
-                None
-            } else {
-                Some(groups[0].clone())
-            }
-        } else {
-            None
-        }
-    } else if let Some(group) = group {
-        let promo = parse_promo_name(&group);
-        promo
-    } else {
-        let member = ctx.author_member().await.unwrap();
-        let groups = get_user_groups(ctx, member.into_owned());
-
-        if let Some(groups) = groups {
-            if groups.len() == 0 {
-                None
-            } else {
-                Some(groups[0].clone())
-            }
-        } else {
-            None
-        }
-    };
-
-    if let Some(promo) = promo {
-        let embed_res = make_events_embed(promo.clone(), date).await;
-        let reply = if let Ok(embed) = embed_res {
-            ctx.send(|m| {
-                m.embed(|e| {
-                    *e = embed;
-                    e
-                })
-            })
-            .await
-            .expect("Failed to send message!")
-        } else {
-            ctx.say(embed_res.unwrap_err())
-                .await
-                .expect("Failed to send message!")
-        };
-
-        if let Ok(msg) = reply.message().await {
-            ctx.data()
-                .edt_msgs
-                .lock()
-                .expect("Failed to lock mutex!")
-                .insert(msg.id, (date, promo));
-            let _ = msg
-                .react(
-                    &ctx,
-                    serenity::model::channel::ReactionType::Unicode("⏪".to_string()),
-                )
-                .await;
-
-            let _ = msg
-                .react(
-                    &ctx,
-                    serenity::model::channel::ReactionType::Unicode("⏩".to_string()),
-                )
-                .await;
-        }
-    } else {
-        let _ = ctx.say("Could not find group for user!").await;
-        return Ok(());
+    if let Err(err) = poise::builtins::on_error(error).await {
+        println!("Failed to handle error: {}", err);
     }
-
-    Ok(())
 }
 
 async fn event_handler(
@@ -193,74 +108,105 @@ async fn event_handler(
                 return Ok(());
             }
 
-            if data
-                .edt_msgs
-                .lock()
-                .expect("Failed to lock mutex!")
-                .contains_key(&add_reaction.message_id)
-            {
-                let mut date = data
-                    .edt_msgs
-                    .lock()
-                    .expect("Failed to lock mutex!")
-                    .get(&add_reaction.message_id)
-                    .unwrap()
-                    .0;
-
-                match add_reaction.emoji {
-                    ReactionType::Unicode(ref emoji) => {
-                        if emoji == "⏪" {
-                            date = date.checked_sub_days(Days::new(1)).unwrap();
-                        } else if emoji == "⏩" {
-                            date = date.checked_add_days(Days::new(1)).unwrap();
-                        } else {
-                            return Ok(());
-                        }
-                    }
-                    _ => {}
-                }
-
-                let promo = data
-                    .edt_msgs
-                    .lock()
-                    .expect("Failed to lock mutex!")
-                    .get(&add_reaction.message_id)
-                    .unwrap()
-                    .1
-                    .clone();
+            let forward = match &add_reaction.emoji {
+                ReactionType::Unicode(emoji) if emoji == "⏪" => false,
+                ReactionType::Unicode(emoji) if emoji == "⏩" => true,
+                _ => return Ok(()),
+            };
 
-                let embed_res = make_events_embed(promo.clone(), date).await;
+            if let Some(msg) = data
+                .state
+                .shift_timetable(add_reaction.message_id, forward)
+                .await
+            {
+                let (content, embeds) = make_timetable(
+                    msg.view,
+                    msg.format,
+                    msg.promo.clone(),
+                    msg.date,
+                    &msg.options,
+                )
+                .await;
                 add_reaction
                     .message(&ctx)
                     .await
                     .expect("Failed to get message!")
                     .edit(ctx, |m| {
-                        if let Ok(embed) = embed_res.clone() {
-                            m.embed(|e| {
-                                *e = embed;
-                                e
-                            });
-                            m.content("");
-                        } else {
-                            m.content(embed_res.unwrap_err());
-                            m.set_embeds(Vec::new());
-                        }
-
-                        m
+                        m.content(content).set_embeds(embeds).set_components(
+                            make_timetable_components(msg.date, &msg.promo, msg.view, msg.format),
+                        )
                     })
                     .await
                     .expect("Failed to edit message!");
 
-                data.edt_msgs
-                    .lock()
-                    .expect("Failed to lock mutex!")
-                    .insert(add_reaction.message_id, (date, promo));
+                if let Some(user) = add_reaction.user_id {
+                    data.state
+                        .set_last_displayed(user, msg.promo, msg.date)
+                        .await;
+                }
 
-                add_reaction
-                    .delete(ctx)
-                    .await
-                    .expect("Failed to delete reaction!");
+                // removing someone else's reaction is not allowed in DMs
+                let _ = add_reaction.delete(ctx).await;
+            }
+        }
+        Event::InteractionCreate {
+            interaction: Interaction::MessageComponent(component),
+        } => {
+            if attendance::handle_check_in(ctx, &data.db, component).await? {
+                return Ok(());
+            }
+            if class_reminders::handle_component(ctx, &data.db, component).await? {
+                return Ok(());
             }
+
+            let Some((date, promo, view, format)) =
+                parse_refresh_button_id(&component.data.custom_id)
+            else {
+                return Ok(());
+            };
+
+            // fetching the calendar can take longer than the interaction deadline
+            component.defer(ctx).await?;
+
+            // keep the options the message was rendered with when we still know them, else
+            // render it the way /edt would for the user who clicked
+            let prefs = data.db.get_user_prefs(component.user.id)?;
+            let view = view.unwrap_or(prefs.view);
+            let format = format.unwrap_or(prefs.format);
+            let options = match data.state.timetable(component.message.id).await {
+                Some(msg) => msg.options,
+                None => {
+                    let options = EmbedOptions::for_guild(&data.db, component.guild_id)?;
+                    let ephemeral = component
+                        .message
+                        .flags
+                        .is_some_and(|f| f.contains(serenity::MessageFlags::EPHEMERAL));
+                    let options = if ephemeral || component.guild_id.is_none() {
+                        options.with_private_prefs(&prefs)
+                    } else {
+                        options.with_prefs(&prefs)
+                    };
+                    EmbedOptions {
+                        live_status: true,
+                        ..options
+                    }
+                }
+            };
+
+            data.state
+                .set_last_displayed(component.user.id, promo.clone(), date)
+                .await;
+
+            calendar::refresh_calendar().await;
+            let (content, embeds) =
+                make_timetable(view, format, promo.clone(), date, &options).await;
+            component
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content(content)
+                        .set_embeds(embeds)
+                        .set_components(make_timetable_components(date, &promo, view, format))
+                })
+                .await?;
         }
         _ => {}
     }
@@ -268,81 +214,172 @@ async fn event_handler(
     Ok(())
 }
 
-struct Handler;
+/// Starts the background loops once the bot is connected
+struct Handler {
+    db: Database,
+    /// `ready` fires again when the gateway session is recreated, the loops only start once
+    started: AtomicBool,
+}
 
 #[serenity::async_trait]
 impl EventHandler for Handler {
     async fn ready(&self, ctx: serenity::Context, ready: serenity::Ready) {
         println!("{} is connected!", ready.user.name);
-        ctx.set_activity(serenity::Activity::watching("les emplois du temps!"))
-            .await;
-
-        tokio::spawn(async move {
-            loop {
-                let now = Local::now();
-                let next = now
-                    .checked_add_days(Days::new(1))
-                    .unwrap()
-                    .with_hour(7)
-                    .unwrap()
-                    .with_minute(0)
-                    .unwrap();
-
-                let duration = next - now;
-                let duration = duration.to_std().unwrap();
-
-                tokio::time::sleep(duration).await;
-
-                let events = get_sorted_events(Local::now().date_naive()).await;
-                if let Err(err) = events.clone() {
-                    println!("Error: {:?}", err);
-                }
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
 
-                let events = events.unwrap();
-                let channel = ChannelId(1157420627901292704);
+        tokio::spawn(presence::rotate_presence(ctx.clone()));
+        tokio::spawn(watcher::watch_changes(ctx.clone(), self.db.clone()));
+        tokio::spawn(scheduler::run(ctx.clone(), self.db.clone()));
+        tokio::spawn(scheduled_events::sync_scheduled_events(
+            ctx.clone(),
+            self.db.clone(),
+        ));
 
-                for promo in events.keys() {
-                    let embed = make_events_embed(promo.clone(), Local::now().date_naive()).await;
-                    if let Ok(embed) = embed {
-                        let _ = channel
-                            .send_message(&ctx, |m| {
-                                m.embed(|e| {
-                                    *e = embed;
-                                    e
-                                })
-                            })
-                            .await;
-                    }
-                }
-            }
-        });
+        tokio::spawn(attendance::run(ctx.clone(), self.db.clone()));
+        tokio::spawn(announcements::run(ctx.clone(), self.db.clone()));
     }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
+    // kept until the end of main, events are flushed when it's dropped
+    #[cfg(feature = "sentry")]
+    let _sentry = std::env::var("SENTRY_DSN").ok().map(|dsn| {
+        sentry::init((
+            dsn,
+            sentry::ClientOptions {
+                release: sentry::release_name!(),
+                ..Default::default()
+            },
+        ))
+    });
+    // fail at startup rather than on the first embed if the config file is invalid
+    calendar::set_group_grammar(&config::get().group_grammar)?;
+    calendar::set_group_remaps(&config::get().group_remaps)?;
+    calendar::set_calendar_url(config::get().calendar_url.clone());
+    #[cfg(feature = "redis")]
+    if let Ok(url) = std::env::var("REDIS_URL") {
+        // the bot still works without the shared cache, each instance fetches for itself
+        match agenda_bot::cache::RedisCache::connect(&url).await {
+            Ok(cache) => calendar::set_cache_backend(Arc::new(cache)),
+            Err(err) => println!(
+                "Failed to connect to Redis, using the memory cache: {}",
+                err
+            ),
+        }
+    }
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|a| a.as_str()) == Some("--dump") {
+        return dump::run(&args[1..]).await;
+    }
+
+    let db =
+        Database::open(&std::env::var("DATABASE_PATH").unwrap_or_else(|_| "agenda.db".to_string()))
+            .expect("Failed to open database!");
+    db.apply_group_remaps(&calendar::group_remaps(), chrono::Local::now().date_naive())?;
+    calendar::set_overrides(db.get_event_overrides()?);
+    calendar::set_group_aliases(db.get_group_aliases()?);
+    calendar::set_custom_events(&db.get_custom_events()?);
+    calendar::set_uploaded_calendars(&db.get_uploaded_calendars()?);
+    let archive = db.clone();
+    calendar::set_archive_reader(Arc::new(move |start, end| {
+        archive
+            .get_archived_events(start, end)
+            .unwrap_or_else(|err| {
+                println!("Failed to read the event archive: {}", err);
+                Vec::new()
+            })
+    }));
+    calendar::set_secondary_feeds(config::get().secondary_feeds());
+    #[cfg(unix)]
+    tokio::spawn(config::reload_on_sighup());
+
+    tokio::spawn(archive::run(db.clone()));
+    #[cfg(feature = "dashboard")]
+    let dashboard_db = db.clone();
+
+    let handler = Handler {
+        db: db.clone(),
+        started: AtomicBool::new(false),
+    };
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![edt()],
+            commands: vec![
+                commands::appel::appel(),
+                commands::botstats::botstats(),
+                commands::calendar::calendar(),
+                commands::changements::changements(),
+                commands::config::config(),
+                commands::devoirs::devoirs(),
+                commands::dispo::dispo(),
+                commands::edt::edt(),
+                commands::evenement::evenement(),
+                commands::exams::exams(),
+                commands::export::export(),
+                commands::feedback::feedback(),
+                commands::groupes::groupes(),
+                commands::help::help(),
+                commands::heures::heures(),
+                commands::next::next(),
+                commands::notifications::notifications(),
+                commands::ou::ou(),
+                commands::overrides::override_event(),
+                commands::prefs::prefs(),
+                commands::quand::quand(),
+                commands::rappels::rappels(),
+                commands::register::register(),
+                commands::reload::reload(),
+                commands::semaine::semaine(),
+                commands::setgroup::setgroup(),
+                commands::vacances::vacances(),
+                commands::validate::validate(),
+            ],
             event_handler: |_ctx, event, _framework, _data| {
                 Box::pin(event_handler(_ctx, event, _framework, _data))
             },
+            on_error: |error| Box::pin(on_error(error)),
             ..Default::default()
         })
         .token(std::env::var("DISCORD_TOKEN").expect("missing DISCORD_TOKEN"))
         .intents(serenity::GatewayIntents::non_privileged())
-        .client_settings(|client_builder| client_builder.event_handler(Handler))
+        .client_settings(move |client_builder| client_builder.event_handler(handler))
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
-                poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+                // global commands take up to an hour to update, a test guild gets them at once
+                let commands = &framework.options().commands;
+                let dev_guild = std::env::var("DEV_GUILD_ID")
+                    .ok()
+                    .and_then(|id| id.parse().ok());
+                match dev_guild.map(serenity::GuildId) {
+                    Some(guild) => {
+                        poise::builtins::register_in_guild(ctx, commands, guild).await?;
+                        println!("Commands registered in the development guild {}", guild);
+                    }
+                    None => poise::builtins::register_globally(ctx, commands).await?,
+                }
                 Ok(Data {
-                    edt_msgs: Mutex::new(HashMap::new()),
+                    state: State::default(),
+                    db,
+                    started_at: Instant::now(),
+                    command_errors: Mutex::new(Vec::new()),
                 })
             })
         });
 
-    framework.run().await.unwrap();
+    let framework = framework.build().await?;
+    let app = http::health_router(framework.shard_manager().clone());
+    // the dashboard reads the roles of the users from the bot's cache
+    #[cfg(feature = "dashboard")]
+    let app = app.merge(dashboard::router(
+        dashboard_db,
+        framework.client().cache_and_http.cache.clone(),
+    ));
+    tokio::spawn(http::serve(app));
+
+    framework.start().await.unwrap();
     Ok(())
 }
@@ -0,0 +1,118 @@
+use poise::serenity_prelude::{
+    self as serenity, CreateAllowedMentions, GuildId, Member, Mentionable, RoleId, UserId,
+};
+
+use crate::db::Database;
+
+/// Discord doesn't ping more users than this in a single message
+const MAX_USER_MENTIONS: usize = 100;
+/// Page size of the member list endpoint
+const MEMBERS_PAGE: u64 = 1000;
+
+/// Text and allowed mentions of an automated post
+#[derive(Debug, Clone, Default)]
+pub struct Mentions {
+    pub content: String,
+    pub roles: Vec<RoleId>,
+    pub users: Vec<UserId>,
+}
+
+impl Mentions {
+    fn roles(roles: &[RoleId], ping: bool) -> Mentions {
+        Mentions {
+            content: roles
+                .iter()
+                .map(|r| r.mention().to_string())
+                .collect::<Vec<String>>()
+                .join(" "),
+            roles: if ping { roles.to_vec() } else { Vec::new() },
+            users: Vec::new(),
+        }
+    }
+
+    /// Whether the post notifies someone, edits never do
+    pub fn pings(&self) -> bool {
+        !self.roles.is_empty() || !self.users.is_empty()
+    }
+
+    pub fn allowed<'a>(&self, a: &'a mut CreateAllowedMentions) -> &'a mut CreateAllowedMentions {
+        a.roles(self.roles.clone()).users(self.users.clone())
+    }
+}
+
+/// Pings `roles`, unless some of their members opted out with /notifications. A role ping can't
+/// leave anyone out, the other members are then pinged one by one instead.
+/// Listing the members needs the Server Members intent to be enabled in the developer portal.
+pub async fn role_mentions(
+    ctx: &serenity::Context,
+    db: &Database,
+    guild: GuildId,
+    roles: &[RoleId],
+) -> Mentions {
+    if roles.is_empty() {
+        return Mentions::default();
+    }
+
+    let opted_out = match db.get_notification_opt_outs() {
+        Ok(opted_out) => opted_out,
+        Err(err) => {
+            println!("Failed to get notification opt-outs: {}", err);
+            Vec::new()
+        }
+    };
+    if opted_out.is_empty() {
+        return Mentions::roles(roles, true);
+    }
+
+    let members = match fetch_members(ctx, guild).await {
+        Ok(members) => members,
+        Err(err) => {
+            println!("Failed to fetch members of {}, not pinging: {}", guild, err);
+            return Mentions::roles(roles, false);
+        }
+    };
+    let concerned = members
+        .iter()
+        .filter(|m| !m.user.bot && m.roles.iter().any(|r| roles.contains(r)))
+        .map(|m| m.user.id)
+        .collect::<Vec<UserId>>();
+    if !concerned.iter().any(|u| opted_out.contains(u)) {
+        return Mentions::roles(roles, true);
+    }
+
+    let users = concerned
+        .into_iter()
+        .filter(|u| !opted_out.contains(u))
+        .collect::<Vec<UserId>>();
+    if users.len() > MAX_USER_MENTIONS {
+        println!("Too many members to ping in {}, not pinging", guild);
+        return Mentions::roles(roles, false);
+    }
+
+    Mentions {
+        content: users
+            .iter()
+            .map(|u| u.mention().to_string())
+            .collect::<Vec<String>>()
+            .join(" "),
+        roles: Vec::new(),
+        users,
+    }
+}
+
+async fn fetch_members(
+    ctx: &serenity::Context,
+    guild: GuildId,
+) -> Result<Vec<Member>, serenity::Error> {
+    let mut members: Vec<Member> = Vec::new();
+    loop {
+        let page = guild
+            .members(ctx, Some(MEMBERS_PAGE), members.last().map(|m| m.user.id))
+            .await?;
+        let done = (page.len() as u64) < MEMBERS_PAGE;
+        members.extend(page);
+        if done {
+            return Ok(members);
+        }
+    }
+}
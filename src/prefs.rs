@@ -0,0 +1,39 @@
+use chrono_tz::Tz;
+
+use crate::i18n::Language;
+
+/// Layout of the timetable shown by /edt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, poise::ChoiceParameter)]
+pub enum View {
+    #[default]
+    #[name = "jour"]
+    Day,
+    #[name = "semaine"]
+    Week,
+}
+
+/// Embeds are hard to read with screen readers, the text format is a plain chronological list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, poise::ChoiceParameter)]
+pub enum Format {
+    #[default]
+    #[name = "embed"]
+    Embed,
+    #[name = "texte"]
+    Text,
+}
+
+/// Per-user display defaults set with /prefs
+#[derive(Debug, Clone, Default)]
+pub struct UserPrefs {
+    pub view: View,
+    pub format: Format,
+    pub ephemeral: bool,
+    pub hour12: bool,
+    pub language: Language,
+    /// Only applied to private replies, public posts stay on Paris time
+    pub timezone: Option<Tz>,
+    /// Categories of secondary feeds left out of the timetable
+    pub hidden_categories: Vec<String>,
+    /// Overrides the campus of the guild
+    pub campus: Option<String>,
+}
@@ -0,0 +1,69 @@
+use std::{collections::HashSet, time::Duration};
+
+use chrono::{Local, Utc};
+use poise::serenity_prelude::{self as serenity, Activity};
+
+use crate::{
+    calendar::{get_events, get_sorted_events_range, parse_promo_name},
+    config,
+};
+
+const ROTATION_INTERVAL: Duration = Duration::from_secs(60 * 3);
+const DEFAULT_ACTIVITY: &str = "les emplois du temps!";
+
+/// Cycles the bot's activity between the next class of the configured group, the number of
+/// classes today and the default message
+pub async fn rotate_presence(ctx: serenity::Context) {
+    let mut step: usize = 0;
+    loop {
+        let activity = match step % 3 {
+            0 => next_class().await,
+            1 => classes_today().await,
+            _ => None,
+        };
+        ctx.set_activity(activity.unwrap_or_else(|| Activity::watching(DEFAULT_ACTIVITY)))
+            .await;
+
+        step += 1;
+        tokio::time::sleep(ROTATION_INTERVAL).await;
+    }
+}
+
+/// "Prochain cours: R3.04 à 10:00", within the next week
+async fn next_class() -> Option<Activity> {
+    let promo = parse_promo_name(config::get().presence_group.as_ref()?)?;
+    let today = Local::now().date_naive();
+    let events = get_sorted_events_range(today, today + chrono::Duration::days(7))
+        .await
+        .ok()?;
+
+    let now = Utc::now();
+    let next = events
+        .get(&promo)?
+        .iter()
+        .find(|e| e.start > now && !e.cancelled && e.category.is_none())?;
+    let time = if next.start.date_naive() == today {
+        next.start.format("%H:%M").to_string()
+    } else {
+        next.start.format("%d/%m %H:%M").to_string()
+    };
+
+    Some(Activity::playing(format!(
+        "Prochain cours: {} à {}",
+        next.lesson_code(),
+        time
+    )))
+}
+
+async fn classes_today() -> Option<Activity> {
+    let today = Local::now().date_naive();
+    let events = get_events().await.ok()?;
+    let count = events
+        .iter()
+        .filter(|e| e.start.date_naive() == today && !e.cancelled)
+        .map(|e| &e.uid)
+        .collect::<HashSet<&String>>()
+        .len();
+
+    Some(Activity::watching(format!("{} cours aujourd'hui", count)))
+}
@@ -0,0 +1,138 @@
+use chrono::{Local, Timelike};
+use poise::serenity_prelude::{self as serenity, ChannelId, Colour, CreateEmbed};
+
+use crate::{
+    calendar::{parse_promo_name, Event, GroupHierarchy},
+    config,
+    db::Database,
+    embed::{make_event_field, EmbedOptions},
+    homework::{homework_due, homework_lines},
+    mentions::{role_mentions, Mentions},
+    watcher::concerned_roles,
+};
+
+/// Reminders aren't sent during the night, they go out with the first check after this hour
+const REMINDER_HOUR: u32 = 8;
+const REMINDER_DAYS: &[i64] = &[7, 1];
+
+/// Reminds every detected evaluation 7 days and 1 day before, in the channel and by DM to
+/// the users who subscribed with /rappels
+pub async fn send_reminders(
+    ctx: &serenity::Context,
+    channel: ChannelId,
+    db: &Database,
+    events: &[Event],
+) {
+    if Local::now().hour() < REMINDER_HOUR {
+        return;
+    }
+
+    let today = Local::now().date_naive();
+    let hierarchy = GroupHierarchy::from_events(events);
+
+    for days_before in REMINDER_DAYS {
+        let day = today + chrono::Duration::days(*days_before);
+        for evt in events
+            .iter()
+            .filter(|e| e.start.date_naive() == day && !e.cancelled && config::get().is_exam(e))
+        {
+            match db.is_exam_reminder_sent(&evt.uid, *days_before) {
+                Ok(false) => {}
+                Ok(true) => continue,
+                Err(err) => {
+                    println!("Failed to check exam reminder: {}", err);
+                    continue;
+                }
+            }
+
+            // a failed post is retried at the next check
+            if let Err(err) = send_reminder(ctx, channel, db, &hierarchy, evt, *days_before).await {
+                println!("Failed to send exam reminder: {}", err);
+                continue;
+            }
+            if let Err(err) = db.mark_exam_reminder_sent(&evt.uid, *days_before) {
+                println!("Failed to record exam reminder: {}", err);
+            }
+        }
+    }
+}
+
+async fn send_reminder(
+    ctx: &serenity::Context,
+    channel: ChannelId,
+    db: &Database,
+    hierarchy: &GroupHierarchy,
+    evt: &Event,
+    days_before: i64,
+) -> Result<(), serenity::Error> {
+    let content = if days_before == 1 {
+        "📝 Évaluation demain !".to_string()
+    } else {
+        format!("📝 Évaluation dans {} jours", days_before)
+    };
+    let (name, value) = make_event_field(evt, &EmbedOptions::default());
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(format!("{} — {}", evt.group, evt.start.format("%d/%m/%Y")))
+        .field(name, value, false)
+        .color(Colour::GOLD);
+
+    let roles = concerned_roles(ctx, channel, hierarchy, evt);
+    let mentions = match ctx.cache.guild_channel(channel) {
+        Some(channel) => role_mentions(ctx, db, channel.guild_id, &roles).await,
+        None => Mentions::default(),
+    };
+    channel
+        .send_message(ctx, |m| {
+            m.content(format!("{} {}", content, mentions.content))
+                .set_embed(embed.clone())
+                .allowed_mentions(|a| mentions.allowed(a))
+        })
+        .await?;
+
+    // the DMs are best effort, the reminder counts as sent once it's in the channel
+    let Some(promo) = parse_promo_name(&evt.group) else {
+        return Ok(());
+    };
+    let promos = hierarchy.targets(&promo);
+    let subscribers = match db.get_exam_subscribers() {
+        Ok(subscribers) => subscribers,
+        Err(err) => {
+            println!("Failed to get exam subscribers: {}", err);
+            return Ok(());
+        }
+    };
+
+    let guild = ctx.cache.guild_channel(channel).map(|c| c.guild_id);
+    let day = evt.start.date_naive();
+    for (user, promo) in subscribers.iter().filter(|(_, p)| promos.contains(p)) {
+        let homework = guild
+            .map(|guild| homework_due(db, guild, hierarchy, promo, day))
+            .transpose()
+            .unwrap_or_else(|err| {
+                println!("Failed to get the homework of {}: {}", promo, err);
+                None
+            })
+            .unwrap_or_default();
+        let mut embed = embed.clone();
+        if !homework.is_empty() {
+            embed.field(
+                "📚 Devoirs à rendre ce jour-là",
+                homework_lines(&homework, false),
+                false,
+            );
+        }
+        let res = match user.create_dm_channel(ctx).await {
+            Ok(dm) => dm
+                .send_message(ctx, |m| m.content(&content).set_embed(embed))
+                .await
+                .map(|_| ()),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = res {
+            println!("Failed to send exam reminder to {}: {}", user, err);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{Local, Utc};
+use poise::serenity_prelude::{self as serenity, GuildId, ScheduledEventType};
+
+use crate::{
+    calendar::{dedup_events, get_sorted_events_range, Event, Promo},
+    db::{Database, SyncedEvent},
+};
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(60 * 15);
+/// Discord rejects longer event names
+const MAX_NAME_LENGTH: usize = 100;
+
+/// Mirrors the classes of today and tomorrow as Discord Scheduled Events in the guilds that
+/// opted in with /config evenements
+pub async fn sync_scheduled_events(ctx: serenity::Context, db: Database) {
+    loop {
+        match db.get_scheduled_event_guilds() {
+            Ok(guilds) => {
+                for (guild, promos) in guilds {
+                    if let Err(err) = sync_guild(&ctx, &db, guild, &promos).await {
+                        println!("Failed to sync scheduled events of {}: {}", guild, err);
+                    }
+                }
+            }
+            Err(err) => println!("Failed to get scheduled event guilds: {}", err),
+        }
+
+        tokio::time::sleep(SYNC_INTERVAL).await;
+    }
+}
+
+fn event_name(evt: &Event) -> String {
+    format!("{} ({:?}) — {}", evt.lesson, evt.event_type, evt.group)
+        .chars()
+        .take(MAX_NAME_LENGTH)
+        .collect()
+}
+
+/// External events need a location, the group stands in when ADE has none
+fn location(evt: &Event) -> &str {
+    if evt.location.is_empty() {
+        &evt.group
+    } else {
+        &evt.location
+    }
+}
+
+fn fingerprint(evt: &Event) -> String {
+    format!(
+        "{}|{}|{}|{}",
+        event_name(evt),
+        evt.location,
+        evt.start.timestamp(),
+        evt.end.timestamp()
+    )
+}
+
+async fn sync_guild(
+    ctx: &serenity::Context,
+    db: &Database,
+    guild: GuildId,
+    promos: &[Promo],
+) -> Result<(), crate::Error> {
+    let now = Utc::now();
+    let today = Local::now().date_naive();
+    let events = get_sorted_events_range(today, today + chrono::Duration::days(2)).await?;
+
+    // semester-wide events are fanned out to several promos but synced once
+    let mut combined: Vec<Event> = promos
+        .iter()
+        .filter_map(|p| events.get(p))
+        .flatten()
+        .filter(|e| !e.cancelled && e.end > now)
+        .cloned()
+        .collect();
+    dedup_events(&mut combined);
+    let wanted: HashMap<String, Event> = combined.into_iter().map(|e| (e.uid.clone(), e)).collect();
+
+    let synced = db.get_synced_events(guild)?;
+    for synced_event in &synced {
+        if wanted.contains_key(&synced_event.uid) {
+            continue;
+        }
+
+        // finished events are closed by Discord, only the ones that disappeared are deleted
+        if synced_event.ends_at > now.timestamp() {
+            if let Err(err) = guild
+                .delete_scheduled_event(ctx, synced_event.event_id)
+                .await
+            {
+                println!("Failed to delete scheduled event: {}", err);
+            }
+        }
+        db.remove_synced_event(guild, &synced_event.uid)?;
+    }
+
+    for (uid, evt) in wanted {
+        let fingerprint = fingerprint(&evt);
+        let existing = synced.iter().find(|s| s.uid == uid);
+        // Discord doesn't accept a start time in the past
+        if existing.is_some_and(|s| s.fingerprint == fingerprint) || evt.start <= now {
+            continue;
+        }
+
+        let description = match &evt.teacher {
+            Some(teacher) => format!("{}\n{}", evt.group, teacher),
+            None => evt.group.clone(),
+        };
+        let event = match existing {
+            Some(existing) => {
+                guild
+                    .edit_scheduled_event(ctx, existing.event_id, |e| {
+                        e.name(event_name(&evt))
+                            .description(description)
+                            .location(location(&evt))
+                            .start_time(evt.start)
+                            .end_time(evt.end)
+                    })
+                    .await
+            }
+            None => {
+                guild
+                    .create_scheduled_event(ctx, |e| {
+                        e.kind(ScheduledEventType::External)
+                            .name(event_name(&evt))
+                            .description(description)
+                            .location(location(&evt))
+                            .start_time(evt.start)
+                            .end_time(evt.end)
+                    })
+                    .await
+            }
+        };
+
+        match event {
+            Ok(event) => db.set_synced_event(
+                guild,
+                &SyncedEvent {
+                    uid,
+                    event_id: event.id,
+                    fingerprint,
+                    ends_at: evt.end.timestamp(),
+                },
+            )?,
+            Err(err) => println!("Failed to sync scheduled event {}: {}", uid, err),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use chrono::Local;
+use poise::serenity_prelude as serenity;
+
+use crate::{
+    calendar::{get_events, group_remaps},
+    class_reminders, config,
+    db::Database,
+    reminders, voice_channels,
+};
+
+const TICK: Duration = Duration::from_secs(60);
+
+/// Runs the time-based tasks: exam and class reminders, temporary voice channels, the group
+/// renames of the semester changes and the cleanup of past homework
+pub async fn run(ctx: serenity::Context, db: Database) {
+    loop {
+        let today = Local::now().date_naive();
+        if let Err(err) = db.apply_group_remaps(&group_remaps(), today) {
+            println!("Failed to rename the saved groups: {}", err);
+        }
+        if let Err(err) = db.remove_past_homework(today) {
+            println!("Failed to remove past homework: {}", err);
+        }
+        match get_events().await {
+            Ok(events) => {
+                let channel = config::get().announcement_channel();
+                reminders::send_reminders(&ctx, channel, &db, &events).await;
+                class_reminders::send_class_reminders(&ctx, &db, &events).await;
+                voice_channels::sync_voice_channels(&ctx, &db, &events).await;
+            }
+            Err(err) => println!("Failed to get events for the scheduler: {}", err),
+        }
+
+        tokio::time::sleep(TICK).await;
+    }
+}
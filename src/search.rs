@@ -0,0 +1,148 @@
+use crate::calendar::Promo;
+
+/// Lowercase without accents nor punctuation, "R3.04 Qualité" becomes "r304 qualite"
+fn normalize(input: &str) -> String {
+    input
+        .to_lowercase()
+        .replace(['é', 'è', 'ê', 'ë'], "e")
+        .replace(['à', 'â'], "a")
+        .replace(['î', 'ï'], "i")
+        .replace('ô', "o")
+        .replace(['ù', 'û'], "u")
+        .replace('ç', "c")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// How well `query` designates the subject `lesson`, lower is better and `None` is no match:
+/// 0 for the resource code, 1 when the name contains the query, 2 when every word of the query
+/// starts a word of the name and 3 when they are a typo away
+pub fn subject_score(query: &str, lesson: &str) -> Option<u8> {
+    let query = normalize(query);
+    let lesson = normalize(lesson);
+    let query = query.trim();
+    if query.is_empty() {
+        return None;
+    }
+
+    let words: Vec<&str> = lesson.split_whitespace().collect();
+    if words.first() == Some(&query) {
+        return Some(0);
+    }
+    if lesson.contains(query) {
+        return Some(1);
+    }
+    if query
+        .split_whitespace()
+        .all(|q| words.iter().any(|w| w.starts_with(q)))
+    {
+        return Some(2);
+    }
+    // short words and codes like "r305" would match too many subjects with a typo
+    let close = |q: &str| {
+        q.len() >= 4
+            && !q.contains(|c: char| c.is_ascii_digit())
+            && words.iter().any(|w| levenshtein(q, w) <= 1)
+    };
+    if query.split_whitespace().all(close) {
+        return Some(3);
+    }
+
+    None
+}
+
+/// Words and numbers of a loosely typed group, "info32" gives "info" and "32"
+fn group_tokens(input: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    for c in input.chars() {
+        if !c.is_ascii_alphanumeric() {
+            previous = None;
+            continue;
+        }
+        match (previous, tokens.last_mut()) {
+            (Some(p), Some(token)) if p.is_ascii_digit() == c.is_ascii_digit() => {
+                token.push(c.to_ascii_lowercase())
+            }
+            _ => tokens.push(c.to_ascii_lowercase().to_string()),
+        }
+        previous = Some(c);
+    }
+
+    tokens
+}
+
+/// Promos of `known` designated by a loosely typed group: "32", "info32", "3 INFO 31",
+/// "3-info-a-31". The last number is the group and a number before it the year, a department
+/// name and a parcours letter narrow it down. Bare groups found in several years are narrowed to
+/// the year, department and parcours of `hint`, the promo of the user, when it has some.
+pub fn resolve_group(input: &str, known: &[Promo], hint: Option<&Promo>) -> Vec<Promo> {
+    let tokens = group_tokens(input);
+    let mut numbers = tokens
+        .iter()
+        .filter(|t| t.starts_with(|c: char| c.is_ascii_digit()));
+    let (year, group) = match (numbers.next(), numbers.next(), numbers.next()) {
+        (Some(group), None, None) => (None, group),
+        (Some(year), Some(group), None) => (Some(year), group),
+        _ => return Vec::new(),
+    };
+    let (Ok(group), Ok(year)) = (
+        group.parse::<i8>(),
+        year.map(|y| y.parse::<i8>()).transpose(),
+    ) else {
+        return Vec::new();
+    };
+    let words: Vec<&String> = tokens
+        .iter()
+        .filter(|t| t.starts_with(|c: char| c.is_ascii_alphabetic()))
+        .collect();
+    let parcours = words
+        .iter()
+        .find(|w| w.len() == 1)
+        .and_then(|w| w.chars().next())
+        .map(|c| c.to_ascii_uppercase());
+    // other words ("groupe", "td") are ignored
+    let department_name = |p: &Promo| p.deparment.to_string().to_lowercase();
+    let department = words
+        .iter()
+        .find(|w| known.iter().any(|p| department_name(p) == ***w));
+
+    let candidates: Vec<Promo> = known
+        .iter()
+        .filter(|p| p.group == group && year.is_none_or(|y| p.year == y))
+        .filter(|p| parcours.is_none_or(|c| p.parcours == Some(c)))
+        .filter(|p| department.is_none_or(|d| department_name(p) == **d))
+        .cloned()
+        .collect();
+    let Some(hint) = hint.filter(|_| candidates.len() > 1) else {
+        return candidates;
+    };
+
+    let narrowed: Vec<Promo> = candidates
+        .iter()
+        .filter(|p| p.year == hint.year && p.deparment == hint.deparment)
+        .filter(|p| hint.parcours.is_none() || p.parcours == hint.parcours)
+        .cloned()
+        .collect();
+    if narrowed.is_empty() {
+        candidates
+    } else {
+        narrowed
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use chrono::{Days, NaiveDate};
+use poise::serenity_prelude::{MessageId, UserId};
+use tokio::sync::RwLock;
+
+use crate::{
+    calendar::Promo,
+    embed::EmbedOptions,
+    prefs::{Format, View},
+};
+
+/// State of a timetable message that can be navigated with reactions
+#[derive(Clone)]
+pub struct TimetableMessage {
+    pub date: NaiveDate,
+    pub promo: Promo,
+    pub view: View,
+    pub format: Format,
+    pub options: EmbedOptions,
+}
+
+/// Navigation state shared by the commands and the event handler. The locks are async so that
+/// a handler waiting for one yields to the executor, and each accessor takes a lock only once.
+#[derive(Default)]
+pub struct State {
+    timetables: RwLock<HashMap<MessageId, TimetableMessage>>,
+    /// Last group and date each user looked at, attached to their /feedback reports
+    last_displayed: RwLock<HashMap<UserId, (Promo, NaiveDate)>>,
+}
+
+impl State {
+    pub async fn timetable(&self, message: MessageId) -> Option<TimetableMessage> {
+        self.timetables.read().await.get(&message).cloned()
+    }
+
+    pub async fn set_timetable(&self, message: MessageId, timetable: TimetableMessage) {
+        self.timetables.write().await.insert(message, timetable);
+    }
+
+    /// Moves a timetable a day or a week forward or back depending on its view, returns its new
+    /// state or `None` when the message isn't known
+    pub async fn shift_timetable(
+        &self,
+        message: MessageId,
+        forward: bool,
+    ) -> Option<TimetableMessage> {
+        let mut timetables = self.timetables.write().await;
+        let timetable = timetables.get_mut(&message)?;
+        let step = match timetable.view {
+            View::Day => Days::new(1),
+            View::Week => Days::new(7),
+        };
+        timetable.date = if forward {
+            timetable.date.checked_add_days(step)?
+        } else {
+            timetable.date.checked_sub_days(step)?
+        };
+
+        Some(timetable.clone())
+    }
+
+    pub async fn last_displayed(&self, user: UserId) -> Option<(Promo, NaiveDate)> {
+        self.last_displayed.read().await.get(&user).cloned()
+    }
+
+    pub async fn set_last_displayed(&self, user: UserId, promo: Promo, date: NaiveDate) {
+        self.last_displayed
+            .write()
+            .await
+            .insert(user, (promo, date));
+    }
+}
@@ -0,0 +1,121 @@
+use chrono::Utc;
+use poise::serenity_prelude::{self as serenity, ChannelId, ChannelType, GuildId};
+
+use crate::{
+    calendar::{parse_promo_name, parse_role_name, Event, EventType, GroupHierarchy, Promo},
+    db::{Database, VoiceChannel},
+};
+
+/// Creates a voice channel per ongoing TP/TD in the guilds that enabled them with /config vocal,
+/// and deletes it once the class is over
+pub async fn sync_voice_channels(ctx: &serenity::Context, db: &Database, events: &[Event]) {
+    let guilds = match db.get_voice_channel_guilds() {
+        Ok(guilds) => guilds,
+        Err(err) => {
+            println!("Failed to get voice channel guilds: {}", err);
+            return;
+        }
+    };
+
+    let hierarchy = GroupHierarchy::from_events(events);
+    for (guild, category) in guilds {
+        if let Err(err) = sync_guild(ctx, db, guild, category, events, &hierarchy).await {
+            println!("Failed to sync voice channels of {}: {}", guild, err);
+        }
+    }
+}
+
+/// "TP R3.04 — B110"
+fn channel_name(evt: &Event) -> String {
+    if evt.location.is_empty() {
+        format!("{:?} {}", evt.event_type, evt.lesson_code())
+    } else {
+        format!(
+            "{:?} {} — {}",
+            evt.event_type,
+            evt.lesson_code(),
+            evt.location
+        )
+    }
+}
+
+async fn sync_guild(
+    ctx: &serenity::Context,
+    db: &Database,
+    guild: GuildId,
+    category: ChannelId,
+    events: &[Event],
+    hierarchy: &GroupHierarchy,
+) -> Result<(), crate::Error> {
+    let now = Utc::now();
+    let guild_promos: Vec<Promo> = ctx
+        .cache
+        .guild_roles(guild)
+        .unwrap_or_default()
+        .values()
+        .filter_map(|r| parse_role_name(&r.name))
+        .collect();
+
+    let ongoing: Vec<&Event> = events
+        .iter()
+        .filter(|e| matches!(e.event_type, EventType::TP | EventType::TD))
+        .filter(|e| !e.cancelled && e.start <= now && e.end > now)
+        .filter(|e| {
+            parse_promo_name(&e.group).is_some_and(|p| {
+                hierarchy
+                    .targets(&p)
+                    .iter()
+                    .any(|t| guild_promos.contains(t))
+            })
+        })
+        .collect();
+
+    let existing = db.get_voice_channels(guild)?;
+    for channel in &existing {
+        if channel.ends_at > now.timestamp() && ongoing.iter().any(|e| e.uid == channel.uid) {
+            continue;
+        }
+
+        // the row is kept to retry at the next sync, unless the channel was already deleted
+        match channel.channel_id.delete(ctx).await {
+            Ok(_) => {}
+            Err(serenity::Error::Http(err))
+                if err.status_code() == Some(serenity::StatusCode::NOT_FOUND) => {}
+            Err(err) => {
+                println!("Failed to delete voice channel: {}", err);
+                continue;
+            }
+        }
+        db.remove_voice_channel(guild, &channel.uid)?;
+    }
+
+    for evt in ongoing {
+        if existing
+            .iter()
+            .any(|c| c.uid == evt.uid && c.ends_at > now.timestamp())
+        {
+            continue;
+        }
+
+        let channel = guild
+            .create_channel(ctx, |c| {
+                c.name(channel_name(evt))
+                    .kind(ChannelType::Voice)
+                    .category(category)
+            })
+            .await;
+        match channel {
+            Ok(channel) => db.add_voice_channel(
+                guild,
+                &VoiceChannel {
+                    uid: evt.uid.clone(),
+                    channel_id: channel.id,
+                    ends_at: evt.end.timestamp(),
+                },
+            )?,
+            Err(err) => println!("Failed to create voice channel: {}", err),
+        }
+    }
+
+    Ok(())
+}
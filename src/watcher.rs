@@ -0,0 +1,164 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use agenda_bot::conflicts::find_conflicts;
+use chrono::{Local, Utc};
+use poise::serenity_prelude::{self as serenity, ChannelId, RoleId};
+
+use crate::{
+    alerts::{Alert, AlertQueue},
+    calendar::{
+        calendar_last_modified, get_events, invalidate_cache, parse_promo_name, parse_role_name,
+        sort_events, Event, GroupHierarchy,
+    },
+    config,
+    db::Database,
+    diff::{diff_events, Change},
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 10);
+/// Between full checks, a change of `Last-Modified` triggers one right away
+const HEAD_POLL_INTERVAL: Duration = Duration::from_secs(60 * 2);
+/// New events starting this soon are the changes students most often miss
+const LATE_ADDITION_WINDOW_HOURS: i64 = 48;
+/// Changes making two classes overlap are reported when they happen in the coming weeks
+const CONFLICT_DAYS: i64 = 28;
+
+/// Periodically refetches the calendar and reports changes against the previous snapshot, sooner
+/// when the feed's `Last-Modified` header changes
+pub async fn watch_changes(ctx: serenity::Context, db: Database) {
+    let mut previous: Option<Vec<Event>> = None;
+    let mut last_modified: Option<String> = None;
+    let mut last_check: Option<Instant> = None;
+    let alerts = AlertQueue::start(ctx.clone(), db.clone());
+    loop {
+        let modified = match calendar_last_modified().await {
+            Ok(value) => {
+                let modified = last_modified.is_some() && value.is_some() && value != last_modified;
+                last_modified = value;
+                modified
+            }
+            Err(err) => {
+                println!("Failed to poll calendar: {}", err);
+                false
+            }
+        };
+
+        if modified || last_check.is_none_or(|t| t.elapsed() >= CHECK_INTERVAL) {
+            if modified {
+                println!("Calendar modified, checking changes early");
+            }
+            let channel = config::get().announcement_channel();
+            check_changes(&ctx, channel, &alerts, &mut previous).await;
+            last_check = Some(Instant::now());
+        }
+
+        tokio::time::sleep(HEAD_POLL_INTERVAL).await;
+    }
+}
+
+async fn check_changes(
+    ctx: &serenity::Context,
+    channel: ChannelId,
+    alerts: &AlertQueue,
+    previous: &mut Option<Vec<Event>>,
+) {
+    invalidate_cache().await;
+    match get_events().await {
+        Ok(events) => {
+            if let Some(previous) = previous {
+                let changes = diff_events(previous, &events);
+                for change in &changes {
+                    println!("Calendar change: {}", change);
+                }
+
+                notify_changes(ctx, channel, alerts, &events, &changes);
+            }
+
+            *previous = Some(events);
+        }
+        Err(err) => println!("Failed to check calendar changes: {}", err),
+    }
+}
+
+/// Roles of the channel's guild matching the promos concerned by an event
+pub fn concerned_roles(
+    ctx: &serenity::Context,
+    channel: ChannelId,
+    hierarchy: &GroupHierarchy,
+    evt: &Event,
+) -> Vec<RoleId> {
+    let Some(promo) = parse_promo_name(&evt.group) else {
+        return Vec::new();
+    };
+    let promos = hierarchy.targets(&promo);
+
+    let roles = ctx
+        .cache
+        .guild_channel(channel)
+        .and_then(|c| ctx.cache.guild_roles(c.guild_id))
+        .unwrap_or_default();
+
+    roles
+        .values()
+        .filter(|r| parse_role_name(&r.name).is_some_and(|p| promos.contains(&p)))
+        .map(|r| r.id)
+        .collect()
+}
+
+/// Event of the feed each upcoming class overlaps in another room, by uid
+fn upcoming_conflicts(events: &[Event]) -> HashMap<String, Event> {
+    let today = Local::now().date_naive();
+    let sorted = sort_events(events, today, today + chrono::Duration::days(CONFLICT_DAYS));
+
+    let mut conflicts = HashMap::new();
+    for (first, second) in sorted.values().flat_map(|events| find_conflicts(events)) {
+        conflicts
+            .entry(first.uid.clone())
+            .or_insert_with(|| second.clone());
+        conflicts
+            .entry(second.uid.clone())
+            .or_insert_with(|| first.clone());
+    }
+
+    conflicts
+}
+
+/// Alerts about the classes added at the last minute and the changes that make two classes
+/// overlap, so that delegates can report them early
+fn notify_changes(
+    ctx: &serenity::Context,
+    channel: ChannelId,
+    alerts: &AlertQueue,
+    events: &[Event],
+    changes: &[Change],
+) {
+    let now = Utc::now();
+    let window_end = now + chrono::Duration::hours(LATE_ADDITION_WINDOW_HOURS);
+    let hierarchy = GroupHierarchy::from_events(events);
+    let conflicts = upcoming_conflicts(events);
+
+    for change in changes {
+        let evt = match change {
+            Change::Added(evt) | Change::Moved { after: evt, .. } => evt,
+            Change::Removed(_) | Change::RoomChanged { .. } => continue,
+        };
+
+        let start = evt.start.with_timezone(&Utc);
+        let late = matches!(change, Change::Added(_)) && start >= now && start <= window_end;
+        let conflict = conflicts.get(&evt.uid).cloned();
+        if !late && conflict.is_none() {
+            continue;
+        }
+
+        alerts.push(Alert {
+            channel,
+            group: evt.group.clone(),
+            roles: concerned_roles(ctx, channel, &hierarchy, evt),
+            change: change.clone(),
+            conflict,
+        });
+    }
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use agenda_bot::{
+    availability::free_slots,
+    calendar::{Event, EventType},
+};
+use chrono::{DateTime, Duration};
+use chrono_tz::Tz;
+use common::{at, event};
+
+fn busy(uid: &str, start: DateTime<Tz>, end: DateTime<Tz>) -> Event {
+    Event {
+        summary: "Réunion".to_string(),
+        lesson: "Réunion".to_string(),
+        group: String::new(),
+        event_type: EventType::OTHER,
+        category: Some("Dupont".to_string()),
+        ..event(uid, start, end)
+    }
+}
+
+#[test]
+fn free_slots_between_events() {
+    let mut cancelled = busy("D", at(16, 0), at(17, 0));
+    cancelled.cancelled = true;
+    let events = [
+        busy("B", at(10, 0), at(12, 0)),
+        // overlaps B, the slot only starts after both
+        busy("A", at(11, 0), at(12, 30)),
+        // leaves a 15 minutes gap, too short to be listed
+        busy("C", at(12, 45), at(14, 0)),
+        cancelled,
+        busy("E", at(7, 0), at(8, 30)),
+    ];
+
+    let slots = free_slots(&events, at(8, 0), at(18, 0), Duration::minutes(30));
+    assert_eq!(slots, vec![(at(8, 30), at(10, 0)), (at(14, 0), at(18, 0))]);
+    assert_eq!(
+        free_slots(&[], at(8, 0), at(18, 0), Duration::minutes(30)),
+        vec![(at(8, 0), at(18, 0))]
+    );
+}
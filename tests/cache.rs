@@ -0,0 +1,27 @@
+mod common;
+
+use agenda_bot::cache::{CacheBackend, MemoryCache};
+use common::{at, event};
+
+#[tokio::test]
+async fn memory_cache_keeps_each_url() {
+    let cache = MemoryCache::default();
+    assert!(cache.get("https://ade/a").await.is_none());
+
+    cache
+        .set("https://ade/a", &(1, vec![event("a", at(8, 0), at(10, 0))]))
+        .await;
+    cache
+        .set("https://ade/b", &(2, vec![event("b", at(8, 0), at(10, 0))]))
+        .await;
+    let (fetched_at, events) = cache.get("https://ade/a").await.unwrap();
+    assert_eq!(fetched_at, 1);
+    assert_eq!(events[0].uid, "a");
+
+    cache.invalidate("https://ade/a").await;
+    assert!(cache.get("https://ade/a").await.is_none());
+    assert!(cache.get("https://ade/b").await.is_some());
+
+    cache.clear().await;
+    assert!(cache.get("https://ade/b").await.is_none());
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use agenda_bot::calendar::{
+    apply_group_aliases, fetch_calendar, fetch_last_modified, fetch_secondary_calendar,
+    parse_events_report, remap_promo, set_group_aliases, set_group_remaps, sort_events, Event,
+    EventType, GroupRemap, Promo, UploadedCalendar,
+};
+use chrono::{NaiveDate, Timelike};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// Serves `tests/fixtures/<fixture>` the way ADE does and fetches it through the bot's parser
+async fn fetch_fixture(fixture: &str) -> Vec<Event> {
+    let server = serve_fixture(fixture).await;
+    fetch_calendar(&format!("{}/calendar.ics", server.uri()))
+        .await
+        .expect("Failed to fetch calendar!")
+}
+
+fn read_fixture(fixture: &str) -> String {
+    std::fs::read_to_string(format!(
+        "{}/tests/fixtures/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        fixture
+    ))
+    .expect("Failed to read fixture!")
+}
+
+async fn serve_fixture(fixture: &str) -> MockServer {
+    let body = read_fixture(fixture);
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/calendar.ics"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn sorted_on(events: &[Event], date: NaiveDate) -> HashMap<Promo, Vec<Event>> {
+    sort_events(events, date, date + chrono::Duration::days(1))
+}
+
+fn promo(name: &str) -> Promo {
+    name.parse().unwrap()
+}
+
+fn uids(events: &[Event]) -> Vec<&str> {
+    events.iter().map(|e| e.uid.as_str()).collect()
+}
+
+#[tokio::test]
+async fn normal_day() {
+    let events = fetch_fixture("normal_day.ics").await;
+    assert_eq!(events.len(), 4);
+
+    let sorted = sorted_on(&events, day(2023, 10, 10));
+    assert_eq!(sorted.len(), 2);
+    assert_eq!(uids(&sorted[&promo("1-INFO-32")]), ["ADE-N2", "ADE-N1"]);
+    assert_eq!(uids(&sorted[&promo("1-INFO-31")]), ["ADE-N3"]);
+
+    let first = &sorted[&promo("1-INFO-32")][0];
+    assert_eq!(first.lesson, "R3.04 Qualité");
+    assert_eq!(first.location, "B110");
+    assert_eq!(first.teacher.as_deref(), Some("DUPONT Jean"));
+    assert!(matches!(first.event_type, EventType::TP));
+    assert_eq!(first.start.hour(), 8);
+}
+
+#[tokio::test]
+async fn malformed_events_are_skipped() {
+    let events = fetch_fixture("malformed.ics").await;
+    assert_eq!(uids(&events), ["ADE-M1", "ADE-M5"]);
+
+    let sorted = sorted_on(&events, day(2023, 10, 10));
+    assert_eq!(uids(&sorted[&promo("1-INFO-32")]), ["ADE-M1", "ADE-M5"]);
+}
+
+#[test]
+fn skipped_events_are_reported() {
+    let report = parse_events_report(&read_fixture("malformed.ics")).unwrap();
+    assert_eq!(uids(&report.events), ["ADE-M1", "ADE-M5"]);
+
+    let skipped = report
+        .skipped
+        .iter()
+        .map(|(uid, reason)| (uid.as_str(), reason.split(':').next().unwrap()))
+        .collect::<Vec<(&str, &str)>>();
+    assert_eq!(
+        skipped,
+        [
+            ("ADE-M2", "Failed to find dtend"),
+            ("ADE-M3", "Invalid description"),
+            ("ADE-M4", "Invalid start date"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn dst_transition() {
+    let events = fetch_fixture("dst_transition.ics").await;
+
+    // both classes start at 08:00 in Paris, on each side of the switch to winter time
+    for date in [day(2023, 10, 27), day(2023, 10, 30)] {
+        let sorted = sorted_on(&events, date);
+        let events = &sorted[&promo("1-INFO-32")];
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].start.date_naive(), date);
+        assert_eq!(events[0].start.hour(), 8);
+        assert_eq!(events[0].end.hour(), 10);
+    }
+}
+
+#[tokio::test]
+async fn semester_wide_events_fan_out() {
+    let events = fetch_fixture("semester_wide.ics").await;
+    let sorted = sorted_on(&events, day(2023, 10, 10));
+
+    assert_eq!(uids(&sorted[&promo("3-INFO-0")]), ["ADE-S1"]);
+    assert_eq!(uids(&sorted[&promo("3-INFO-3")]), ["ADE-S1", "ADE-S2"]);
+    assert_eq!(
+        uids(&sorted[&promo("3-INFO-31")]),
+        ["ADE-S1", "ADE-S2", "ADE-S3"]
+    );
+    assert_eq!(
+        uids(&sorted[&promo("3-INFO-32")]),
+        ["ADE-S1", "ADE-S2", "ADE-S4"]
+    );
+    assert_eq!(uids(&sorted[&promo("3-INFO-4")]), ["ADE-S1"]);
+    assert_eq!(uids(&sorted[&promo("3-INFO-41")]), ["ADE-S1", "ADE-S5"]);
+}
+
+#[tokio::test]
+async fn parcours_groups_fan_out() {
+    let events = fetch_fixture("parcours.ics").await;
+    let sorted = sorted_on(&events, day(2023, 10, 10));
+
+    assert_eq!(uids(&sorted[&promo("3-INFO-0")]), ["ADE-P1"]);
+    assert_eq!(uids(&sorted[&promo("3-INFO-A-0")]), ["ADE-P1", "ADE-P2"]);
+    assert_eq!(
+        uids(&sorted[&promo("3-INFO-A-3")]),
+        ["ADE-P1", "ADE-P2", "ADE-P3"]
+    );
+    assert_eq!(
+        uids(&sorted[&promo("3-INFO-A-31")]),
+        ["ADE-P1", "ADE-P2", "ADE-P3", "ADE-P4"]
+    );
+    assert_eq!(uids(&sorted[&promo("3-INFO-B-31")]), ["ADE-P1", "ADE-P5"]);
+}
+
+#[tokio::test]
+async fn fanned_out_copies_are_shown_once() {
+    let events = fetch_fixture("fanned_out.ics").await;
+    let sorted = sorted_on(&events, day(2023, 10, 10));
+
+    assert_eq!(uids(&sorted[&promo("3-INFO-3")]), ["ADE-F1"]);
+    assert_eq!(uids(&sorted[&promo("3-INFO-31")]), ["ADE-F1", "ADE-F4"]);
+    assert_eq!(uids(&sorted[&promo("3-INFO-32")]), ["ADE-F1", "ADE-F5"]);
+}
+
+#[tokio::test]
+async fn secondary_feed_reaches_every_promo() {
+    let mut events = fetch_fixture("normal_day.ics").await;
+    let server = serve_fixture("secondary_feed.ics").await;
+    events.extend(
+        fetch_secondary_calendar(&format!("{}/calendar.ics", server.uri()), "BDE")
+            .await
+            .expect("Failed to fetch calendar!"),
+    );
+
+    let sorted = sorted_on(&events, day(2023, 10, 10));
+    assert_eq!(
+        uids(&sorted[&promo("1-INFO-31")]),
+        ["ADE-N3", "bde-1@google.com"]
+    );
+    assert_eq!(
+        uids(&sorted[&promo("1-INFO-32")]),
+        ["ADE-N2", "ADE-N1", "bde-1@google.com"]
+    );
+
+    let party = &sorted[&promo("1-INFO-0")][0];
+    assert_eq!(party.category.as_deref(), Some("BDE"));
+    assert_eq!(party.start.hour(), 18);
+    assert_eq!(party.location, "Foyer");
+
+    // all-day events stay within their day
+    let sorted = sorted_on(&events, day(2023, 10, 11));
+    assert_eq!(uids(&sorted[&promo("1-INFO-31")]), ["bde-2@google.com"]);
+    assert!(sorted_on(&events, day(2023, 10, 12)).is_empty());
+
+    // converted from the zone of the event
+    let call = &sorted_on(&events, day(2023, 10, 13))[&promo("1-INFO-31")][0];
+    assert_eq!(call.uid, "bde-3@google.com");
+    assert_eq!((call.start.hour(), call.end.hour()), (14, 16));
+}
+
+#[tokio::test]
+async fn calendar_unavailable() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    assert!(fetch_calendar(&format!("{}/calendar.ics", server.uri()))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn description_notes() {
+    let events = fetch_fixture("notes.ics").await;
+    assert_eq!(uids(&events), ["ADE-O1", "ADE-O2"]);
+
+    assert_eq!(events[0].teacher.as_deref(), Some("DUPONT Jean"));
+    assert_eq!(
+        events[0].notes,
+        ["Apporter calculatrice, stylo", "Contrôle sur table"]
+    );
+
+    // the export date isn't mistaken for the teacher
+    assert_eq!(events[1].teacher, None);
+    assert!(events[1].notes.is_empty());
+}
+
+#[test]
+fn uploaded_calendar_covers_its_dates() {
+    let body = read_fixture("normal_day.ics");
+    let upload = UploadedCalendar {
+        id: 1,
+        name: "normal_day.ics".to_string(),
+        start: day(2023, 10, 11),
+        end: day(2023, 10, 12),
+        replace: true,
+        body,
+    };
+
+    assert_eq!(uids(&upload.events().unwrap()), ["ADE-N4"]);
+}
+
+#[test]
+fn group_remaps_chain() {
+    let remap = |from: &str, to: &str, since| GroupRemap {
+        from: from.to_string(),
+        to: to.to_string(),
+        since,
+    };
+    set_group_remaps(&[
+        remap("1-INFO-21", "1-INFO-31", day(2024, 9, 2)),
+        remap("1-INFO-11", "1-INFO-21", day(2024, 1, 22)),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        remap_promo(promo("1-INFO-11"), day(2024, 1, 21)),
+        promo("1-INFO-11")
+    );
+    assert_eq!(
+        remap_promo(promo("1-INFO-11"), day(2024, 1, 22)),
+        promo("1-INFO-21")
+    );
+    assert_eq!(
+        remap_promo(promo("1-INFO-11"), day(2024, 10, 1)),
+        promo("1-INFO-31")
+    );
+    assert_eq!(
+        remap_promo(promo("1-INFO-12"), day(2024, 10, 1)),
+        promo("1-INFO-12")
+    );
+    assert!(set_group_remaps(&[remap("1-INFO-11", "invalid", day(2024, 1, 22))]).is_err());
+}
+
+#[tokio::test]
+async fn unknown_groups_are_quarantined_until_aliased() {
+    let mut events = fetch_fixture("normal_day.ics").await;
+    let first = events.iter().position(|e| e.uid == "ADE-N3").unwrap();
+    events[first].group = "INFO1 TP3-1".to_string();
+    let second = events.iter().position(|e| e.uid == "ADE-N1").unwrap();
+    events[second].group = "Groupe X".to_string();
+    set_group_aliases(HashMap::from([(
+        "INFO1 TP3-1".to_string(),
+        "1-INFO-31".to_string(),
+    )]));
+
+    let unclassified = apply_group_aliases(&mut events);
+    assert_eq!(
+        unclassified.into_iter().collect::<Vec<(String, usize)>>(),
+        [("Groupe X".to_string(), 1)]
+    );
+    let sorted = sorted_on(&events, day(2023, 10, 10));
+    assert_eq!(uids(&sorted[&promo("1-INFO-31")]), ["ADE-N3"]);
+    assert_eq!(uids(&sorted[&promo("1-INFO-32")]), ["ADE-N2"]);
+}
+
+#[tokio::test]
+async fn last_modified_header() {
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/calendar.ics"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Last-Modified", "Tue, 10 Oct 2023 06:00:00 GMT"),
+        )
+        .mount(&server)
+        .await;
+
+    let last_modified = fetch_last_modified(&format!("{}/calendar.ics", server.uri()))
+        .await
+        .expect("Failed to fetch headers!");
+    assert_eq!(
+        last_modified.as_deref(),
+        Some("Tue, 10 Oct 2023 06:00:00 GMT")
+    );
+}
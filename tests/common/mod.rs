@@ -0,0 +1,33 @@
+//! Event builders shared by the integration tests
+#![allow(dead_code)]
+
+use agenda_bot::calendar::{Event, EventType};
+use chrono::{DateTime, NaiveDate, TimeZone};
+use chrono_tz::{Europe::Paris, Tz};
+
+/// Monday 07/10/2024 at the given time
+pub fn at(hour: u32, minute: u32) -> DateTime<Tz> {
+    let day = NaiveDate::from_ymd_opt(2024, 10, 7).unwrap();
+    Paris
+        .from_local_datetime(&day.and_hms_opt(hour, minute, 0).unwrap())
+        .unwrap()
+}
+
+/// A TD of 1-INFO-32, tests override the fields they care about
+pub fn event(uid: &str, start: DateTime<Tz>, end: DateTime<Tz>) -> Event {
+    Event {
+        uid: uid.to_string(),
+        summary: "R3.04_TD".to_string(),
+        start,
+        end,
+        location: String::new(),
+        lesson: "R3.04 Qualité".to_string(),
+        group: "1-INFO-32".to_string(),
+        teacher: None,
+        notes: Vec::new(),
+        event_type: EventType::TD,
+        admin_note: None,
+        cancelled: false,
+        category: None,
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use agenda_bot::{
+    calendar::Event,
+    conflicts::{conflicting_uids, find_conflicts},
+};
+use chrono::DateTime;
+use chrono_tz::Tz;
+use common::{at, event};
+
+fn class(uid: &str, start: DateTime<Tz>, end: DateTime<Tz>, location: &str) -> Event {
+    Event {
+        location: location.to_string(),
+        ..event(uid, start, end)
+    }
+}
+
+fn pairs(events: &[Event]) -> Vec<(&str, &str)> {
+    find_conflicts(events)
+        .into_iter()
+        .map(|(a, b)| (a.uid.as_str(), b.uid.as_str()))
+        .collect()
+}
+
+#[test]
+fn overlapping_classes_in_different_rooms_conflict() {
+    let events = vec![
+        class("a", at(8, 0), at(10, 0), "B110"),
+        class("b", at(9, 0), at(11, 0), "B204"),
+        class("c", at(10, 0), at(12, 0), "B110"),
+        class("d", at(13, 30), at(15, 30), "B110"),
+    ];
+    assert_eq!(pairs(&events), [("a", "b"), ("b", "c")]);
+
+    let uids = conflicting_uids(&events);
+    assert!(uids.contains("a") && uids.contains("c"));
+    assert!(!uids.contains("d"));
+}
+
+#[test]
+fn same_room_cancelled_and_feed_events_dont_conflict() {
+    let mut cancelled = class("b", at(8, 0), at(10, 0), "B204");
+    cancelled.cancelled = true;
+    let mut feed = class("c", at(8, 0), at(10, 0), "Foyer");
+    feed.category = Some("BDE".to_string());
+    let events = vec![
+        class("a", at(8, 0), at(10, 0), "B110"),
+        cancelled,
+        feed,
+        class("d", at(9, 0), at(10, 0), "B110"),
+    ];
+    assert!(pairs(&events).is_empty());
+}
@@ -0,0 +1,126 @@
+use agenda_bot::dates::{next_occurrence, parse_date, parse_time, short_duration, short_label};
+use chrono::{Duration, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Europe::Paris;
+
+fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// Thursday 10/10/2024
+fn today() -> NaiveDate {
+    day(2024, 10, 10)
+}
+
+#[test]
+fn relative_days() {
+    assert_eq!(parse_date("aujourd'hui", today()), Some(today()));
+    assert_eq!(parse_date("Demain", today()), Some(day(2024, 10, 11)));
+    assert_eq!(parse_date("après-demain", today()), Some(day(2024, 10, 12)));
+    assert_eq!(parse_date("hier", today()), Some(day(2024, 10, 9)));
+    assert_eq!(parse_date("tomorrow", today()), Some(day(2024, 10, 11)));
+    assert_eq!(parse_date("dans 3 jours", today()), Some(day(2024, 10, 13)));
+    assert_eq!(
+        parse_date("dans une semaine", today()),
+        Some(day(2024, 10, 17))
+    );
+    assert_eq!(parse_date("in 2 weeks", today()), Some(day(2024, 10, 24)));
+    assert_eq!(
+        parse_date("semaine prochaine", today()),
+        Some(day(2024, 10, 14))
+    );
+    assert_eq!(parse_date("this week", today()), Some(day(2024, 10, 7)));
+}
+
+#[test]
+fn weekdays() {
+    assert_eq!(parse_date("jeudi", today()), Some(today()));
+    assert_eq!(
+        parse_date("jeudi prochain", today()),
+        Some(day(2024, 10, 17))
+    );
+    assert_eq!(parse_date("lundi", today()), Some(day(2024, 10, 14)));
+    assert_eq!(parse_date("next friday", today()), Some(day(2024, 10, 11)));
+}
+
+#[test]
+fn numeric_dates() {
+    assert_eq!(parse_date("14/11/2024", today()), Some(day(2024, 11, 14)));
+    assert_eq!(parse_date("14/11/24", today()), Some(day(2024, 11, 14)));
+    assert_eq!(parse_date("2024-11-14", today()), Some(day(2024, 11, 14)));
+    assert_eq!(parse_date("14/11", today()), Some(day(2024, 11, 14)));
+    // the closest 15/01 is the next one, not the one of the past school year
+    assert_eq!(parse_date("15/01", today()), Some(day(2025, 1, 15)));
+    assert_eq!(parse_date("15/01", day(2025, 6, 1)), Some(day(2025, 1, 15)));
+}
+
+#[test]
+fn invalid_dates() {
+    assert_eq!(parse_date("31/02", today()), None);
+    assert_eq!(parse_date("dans trois ans", today()), None);
+    assert_eq!(parse_date("bientôt", today()), None);
+    assert_eq!(parse_date("dans 999999999999999 jours", today()), None);
+    assert_eq!(
+        parse_date("dans 999999999999999999 semaines", today()),
+        None
+    );
+}
+
+#[test]
+fn short_labels() {
+    assert_eq!(short_label(today()), "jeu. 10 oct.");
+    assert_eq!(short_label(day(2024, 8, 1)), "jeu. 1 août");
+}
+
+#[test]
+fn short_durations() {
+    assert_eq!(short_duration(Duration::minutes(35)), "35 min");
+    assert_eq!(short_duration(Duration::seconds(30)), "1 min");
+    assert_eq!(short_duration(Duration::minutes(80)), "1 h 20");
+    assert_eq!(short_duration(Duration::minutes(65)), "1 h 05");
+    assert_eq!(short_duration(Duration::hours(2)), "2 h");
+}
+
+#[test]
+fn times() {
+    let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+    assert_eq!(parse_time("7:30"), Some(time(7, 30)));
+    assert_eq!(parse_time("07h30"), Some(time(7, 30)));
+    assert_eq!(parse_time("7h"), Some(time(7, 0)));
+    assert_eq!(parse_time("18"), Some(time(18, 0)));
+    assert_eq!(parse_time("25:00"), None);
+    assert_eq!(parse_time("midi"), None);
+}
+
+#[test]
+fn next_occurrences() {
+    let seven = NaiveTime::from_hms_opt(7, 0, 0).unwrap();
+    let at = |d: u32, m: u32, h: u32, min: u32| {
+        Paris
+            .from_local_datetime(&day(2024, m, d).and_hms_opt(h, min, 0).unwrap())
+            .earliest()
+            .unwrap()
+    };
+
+    // started before the announcement time, it still happens the same day
+    assert_eq!(next_occurrence(&at(10, 10, 6, 0), seven), at(10, 10, 7, 0));
+    assert_eq!(next_occurrence(&at(10, 10, 7, 0), seven), at(11, 10, 7, 0));
+    assert_eq!(next_occurrence(&at(10, 10, 23, 0), seven), at(11, 10, 7, 0));
+
+    // the night of the DST changes lasts 23 or 25 hours, the wall clock time is kept
+    let before_spring = next_occurrence(&at(30, 3, 8, 0), seven);
+    assert_eq!(before_spring, at(31, 3, 7, 0));
+    assert_eq!(before_spring - at(30, 3, 8, 0), Duration::hours(22));
+    let before_autumn = next_occurrence(&at(26, 10, 8, 0), seven);
+    assert_eq!(before_autumn - at(26, 10, 8, 0), Duration::hours(24));
+
+    // 2:30 doesn't exist on the 31/03 and happens twice on the 27/10
+    let half_past_two = NaiveTime::from_hms_opt(2, 30, 0).unwrap();
+    assert_eq!(
+        next_occurrence(&at(31, 3, 0, 0), half_past_two),
+        at(31, 3, 3, 30)
+    );
+    assert_eq!(
+        next_occurrence(&at(27, 10, 0, 0), half_past_two).timestamp(),
+        at(27, 10, 2, 30).timestamp()
+    );
+}
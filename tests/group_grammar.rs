@@ -0,0 +1,50 @@
+use agenda_bot::calendar::{parse_promo_name, set_group_grammar, GroupGrammar};
+
+fn parsed(name: &str) -> Option<String> {
+    parse_promo_name(name).map(|p| p.to_string())
+}
+
+/// The grammar is global, both schemes are checked in a single test
+#[test]
+fn group_grammar() {
+    assert_eq!(parsed("1-INFO-32").as_deref(), Some("1-INFO-32"));
+    assert_eq!(parsed("1-INFO-3").as_deref(), Some("1-INFO-3"));
+    assert_eq!(parsed("2-GEII-S3").as_deref(), Some("2-GEII-0"));
+    assert_eq!(parsed("3-INFO-A-31").as_deref(), Some("3-INFO-A-31"));
+    assert_eq!(parsed("3-INFO-B-S5").as_deref(), Some("3-INFO-B-0"));
+    assert_eq!(parsed("3-INFO-S5").as_deref(), Some("3-INFO-0"));
+    assert_eq!(parsed("INFO 1A groupe 3"), None);
+
+    set_group_grammar(&GroupGrammar {
+        templates: vec![
+            "{year}-{department}-{group}".to_string(),
+            "{department} {year}A groupe {group}".to_string(),
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(parsed("1-INFO-32").as_deref(), Some("1-INFO-32"));
+    assert_eq!(parsed("INFO 1A groupe 3").as_deref(), Some("1-INFO-3"));
+
+    // the whole name has to match, not only a part of it
+    set_group_grammar(&GroupGrammar {
+        templates: vec!["{year}-{department}-{group}".to_string()],
+        group: "[1-9]".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(parsed("3-INFO-3").as_deref(), Some("3-INFO-3"));
+    assert_eq!(parsed("3-INFO-31"), None);
+    assert_eq!(parsed("X3-INFO-3"), None);
+
+    let missing_group = GroupGrammar {
+        templates: vec!["{year}-{department}".to_string()],
+        ..Default::default()
+    };
+    assert!(set_group_grammar(&missing_group).is_err());
+    let unknown_segment = GroupGrammar {
+        templates: vec!["{year}-{department}-{semester}{group}".to_string()],
+        ..Default::default()
+    };
+    assert!(set_group_grammar(&unknown_segment).is_err());
+}
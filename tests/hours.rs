@@ -0,0 +1,82 @@
+mod common;
+
+use agenda_bot::{
+    calendar::{Event, EventType},
+    dates::semester_bounds,
+    hours::{count_hours, total, Hours},
+};
+use chrono::{NaiveDate, TimeZone, Utc};
+use chrono_tz::Europe::Paris;
+use common::event;
+
+fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn class(uid: &str, lesson: &str, event_type: EventType, d: u32, hour: u32, minutes: i64) -> Event {
+    let start = Paris
+        .from_local_datetime(&day(2024, 10, d).and_hms_opt(hour, 0, 0).unwrap())
+        .unwrap();
+    Event {
+        summary: lesson.to_string(),
+        lesson: lesson.to_string(),
+        event_type,
+        ..event(uid, start, start + chrono::Duration::minutes(minutes))
+    }
+}
+
+#[test]
+fn hours_per_lesson_and_type() {
+    let mut cancelled = class("C", "R1.01 Dev", EventType::TD, 9, 8, 120);
+    cancelled.cancelled = true;
+    let events = [
+        class("A", "R1.01 Dev", EventType::CM, 7, 8, 90),
+        class("B", "R1.01 Dev", EventType::TD, 8, 8, 120),
+        cancelled,
+        class("D", "R1.01 Dev", EventType::TD, 14, 8, 120),
+        class("E", "R1.02 Web", EventType::TP, 15, 14, 180),
+    ];
+    // the 10/10 at noon
+    let now = Utc.with_ymd_and_hms(2024, 10, 10, 10, 0, 0).unwrap();
+
+    let hours = count_hours(&events, now);
+    let dev = &hours["R1.01 Dev"];
+    assert_eq!(
+        dev[&EventType::CM],
+        Hours {
+            done: 90,
+            planned: 90
+        }
+    );
+    assert_eq!(
+        dev[&EventType::TD],
+        Hours {
+            done: 120,
+            planned: 240
+        }
+    );
+    assert_eq!(total(dev).remaining(), 120);
+    assert_eq!(
+        hours["R1.02 Web"][&EventType::TP],
+        Hours {
+            done: 0,
+            planned: 180
+        }
+    );
+}
+
+#[test]
+fn semesters() {
+    assert_eq!(
+        semester_bounds(day(2024, 10, 10)),
+        (day(2024, 9, 1), day(2025, 2, 1))
+    );
+    assert_eq!(
+        semester_bounds(day(2025, 1, 15)),
+        (day(2024, 9, 1), day(2025, 2, 1))
+    );
+    assert_eq!(
+        semester_bounds(day(2025, 3, 3)),
+        (day(2025, 2, 1), day(2025, 9, 1))
+    );
+}
@@ -0,0 +1,64 @@
+use agenda_bot::{
+    calendar::Promo,
+    search::{resolve_group, subject_score},
+};
+
+const LESSON: &str = "R3.04 Qualité de développement";
+
+#[test]
+fn subject_scores() {
+    assert_eq!(subject_score("R3.04", LESSON), Some(0));
+    assert_eq!(subject_score("r304", LESSON), Some(0));
+    assert_eq!(subject_score("qualite", LESSON), Some(1));
+    assert_eq!(subject_score("qual dev", LESSON), Some(2));
+    assert_eq!(subject_score("developement", LESSON), Some(3));
+    assert_eq!(subject_score("R3.05", LESSON), None);
+    assert_eq!(subject_score("", LESSON), None);
+}
+
+fn promos(names: &[&str]) -> Vec<Promo> {
+    names.iter().map(|n| n.parse().unwrap()).collect()
+}
+
+fn resolved(input: &str, hint: Option<&str>) -> Vec<String> {
+    let known = promos(&[
+        "1-INFO-31",
+        "1-INFO-32",
+        "2-INFO-32",
+        "2-GEII-32",
+        "3-INFO-A-31",
+        "3-INFO-B-31",
+    ]);
+    let hint = hint.map(|h| h.parse::<Promo>().unwrap());
+    resolve_group(input, &known, hint.as_ref())
+        .iter()
+        .map(|p| p.to_string())
+        .collect()
+}
+
+#[test]
+fn relaxed_group_inputs() {
+    assert_eq!(resolved("1 INFO 31", None), ["1-INFO-31"]);
+    assert_eq!(resolved("geii32", None), ["2-GEII-32"]);
+    assert_eq!(resolved("2-geii-32", None), ["2-GEII-32"]);
+    assert_eq!(resolved("3 INFO A 31", None), ["3-INFO-A-31"]);
+    assert_eq!(resolved("groupe 1 32", None), ["1-INFO-32"]);
+    assert!(resolved("33", None).is_empty());
+    assert!(resolved("1 2 32", None).is_empty());
+}
+
+#[test]
+fn bare_groups_use_the_year_of_the_user() {
+    assert_eq!(
+        resolved("32", None),
+        ["1-INFO-32", "2-INFO-32", "2-GEII-32"]
+    );
+    assert_eq!(resolved("32", Some("2-INFO-31")), ["2-INFO-32"]);
+    assert_eq!(resolved("31", Some("3-INFO-B-32")), ["3-INFO-B-31"]);
+    assert_eq!(resolved("info31", Some("1-INFO-32")), ["1-INFO-31"]);
+    // nothing in the year of the user, the other years are kept
+    assert_eq!(
+        resolved("31", Some("2-INFO-32")),
+        ["1-INFO-31", "3-INFO-A-31", "3-INFO-B-31"]
+    );
+}
@@ -0,0 +1,41 @@
+use agenda_bot::calendar::{get_sorted_events, Promo};
+use chrono::NaiveDate;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// Goes through `CALENDAR_URL` and the cache like the bot does, so it lives in its own test
+/// binary: the URL is only read once per process
+#[tokio::test]
+async fn get_sorted_events_from_calendar_url() {
+    let body = std::fs::read_to_string(format!(
+        "{}/tests/fixtures/normal_day.ics",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .expect("Failed to read fixture!");
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/calendar.ics"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .expect(1)
+        .mount(&server)
+        .await;
+    std::env::set_var("CALENDAR_URL", format!("{}/calendar.ics", server.uri()));
+
+    let day = NaiveDate::from_ymd_opt(2023, 10, 10).unwrap();
+    let sorted = get_sorted_events(day).await.expect("Failed to get events!");
+    let group = "1-INFO-32".parse::<Promo>().unwrap();
+    let uids = sorted[&group]
+        .iter()
+        .map(|e| e.uid.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(uids, ["ADE-N2", "ADE-N1"]);
+
+    // the next day is served from the cache, the mock checks it was only hit once
+    let sorted = get_sorted_events(day.succ_opt().unwrap())
+        .await
+        .expect("Failed to get events!");
+    assert_eq!(sorted[&group].len(), 1);
+}